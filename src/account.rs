@@ -1,4 +1,12 @@
-use crate::{contract_types::UserOperation, types::*, Result};
+use crate::{
+    contract_types::UserOperation, contracts::IEntryPoint, network::NetworkEnvironment, types::*,
+    Result,
+};
+use alloy::{
+    primitives::{aliases::U192, B256, U256},
+    providers::Provider,
+};
+use std::collections::HashMap;
 use async_trait::async_trait;
 
 /// Multi-chain smart account trait
@@ -16,6 +24,18 @@ pub trait MultiChainSmartAccount: Send + Sync {
     /// Converts an array of Call objects into callData hex
     async fn encode_calls(&self, chain_id: ChainId, calls: Vec<Call>) -> Result<Hex>;
 
+    /// Encode a DELEGATECALL from the account to `target` with `data`
+    /// Batches using runtime variables run through the RuntimeVarsHelper this way; accounts
+    /// that cannot DELEGATECALL fail with [`crate::EilError::DelegateCallUnsupported`].
+    async fn encode_delegate_call(
+        &self,
+        chain_id: ChainId,
+        _target: Address,
+        _data: Hex,
+    ) -> Result<Hex> {
+        Err(crate::EilError::DelegateCallUnsupported(chain_id))
+    }
+
     /// Encode static calls (no runtime variables)
     async fn encode_static_calls(&self, chain_id: ChainId, calls: Vec<Call>) -> Result<Hex> {
         // Default implementation same as encode_calls
@@ -30,13 +50,18 @@ pub trait MultiChainSmartAccount: Send + Sync {
         -> Result<()>;
 
     /// Get nonce for the account on a specific chain
-    async fn get_nonce(&self, chain_id: ChainId) -> Result<alloy::primitives::U256>;
+    async fn get_nonce(&self, chain_id: ChainId) -> Result<U256>;
 
     /// Get factory args for account deployment (if not deployed)
     async fn get_factory_args(&self, chain_id: ChainId) -> Result<(Option<Address>, Option<Hex>)>;
+
+    /// Signer of the account, used for off-chain signatures such as permits
+    fn signer(&self) -> Option<&dyn Signer> {
+        None
+    }
 }
 
-impl MultiChainEntity for dyn MultiChainSmartAccount {
+impl MultiChainEntity for dyn MultiChainSmartAccount + '_ {
     fn address_on(&self, chain_id: ChainId) -> Option<Address> {
         MultiChainSmartAccount::address_on(self, chain_id).ok()
     }
@@ -46,11 +71,195 @@ impl MultiChainEntity for dyn MultiChainSmartAccount {
 /// Provides common functionality for smart account implementations
 pub struct BaseMultichainSmartAccount {
     /// Addresses per chain
-    pub addresses: std::collections::HashMap<ChainId, Address>,
+    pub addresses: HashMap<ChainId, Address>,
     /// Signer (for signing UserOps)
     pub signer: Box<dyn Signer>,
     /// Bundler manager (for sending UserOps)
     pub bundler_manager: Box<dyn BundlerManager>,
+    /// How UserOperations are signed
+    pub signing_mode: SigningMode,
+}
+
+impl BaseMultichainSmartAccount {
+    /// Create an account at its counterfactual address on every configured chain
+    /// See [`counterfactual_addresses`]
+    pub fn counterfactual(
+        config: &crate::config::CrossChainConfig,
+        factory: Address,
+        salt: B256,
+        init_code_hash: B256,
+        signer: Box<dyn Signer>,
+        bundler_manager: Box<dyn BundlerManager>,
+    ) -> Self {
+        Self::new(
+            counterfactual_addresses(config, factory, salt, init_code_hash),
+            signer,
+            bundler_manager,
+        )
+    }
+
+    /// Create a new account signing each UserOperation separately
+    pub fn new(
+        addresses: HashMap<ChainId, Address>,
+        signer: Box<dyn Signer>,
+        bundler_manager: Box<dyn BundlerManager>,
+    ) -> Self {
+        Self {
+            addresses,
+            signer,
+            bundler_manager,
+            signing_mode: SigningMode::default(),
+        }
+    }
+
+    /// Set the signing mode
+    pub fn with_signing_mode(mut self, signing_mode: SigningMode) -> Self {
+        self.signing_mode = signing_mode;
+        self
+    }
+}
+
+/// Factory used to deploy an account that does not exist yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountFactory {
+    /// Factory contract address
+    pub factory: Address,
+    /// Calldata passed to the factory (e.g. `createAccount(owner, salt)`)
+    pub factory_data: Hex,
+}
+
+/// Compute the CREATE2 address of an account deployed by `factory`
+/// `keccak256(0xff ++ factory ++ salt ++ init_code_hash)[12..]`
+pub fn counterfactual_address(factory: Address, salt: B256, init_code_hash: B256) -> Address {
+    factory.create2(salt, init_code_hash)
+}
+
+/// Compute the CREATE2 account address on every chain in the configuration
+/// The factory is expected to be deployed at the same address on all chains
+pub fn counterfactual_addresses(
+    config: &crate::config::CrossChainConfig,
+    factory: Address,
+    salt: B256,
+    init_code_hash: B256,
+) -> HashMap<ChainId, Address> {
+    let address = counterfactual_address(factory, salt, init_code_hash);
+    config
+        .chain_infos
+        .iter()
+        .map(|info| (info.chain_id, address))
+        .collect()
+}
+
+/// Get the EntryPoint nonce of `sender` for the given nonce key
+pub async fn get_entry_point_nonce(
+    network_env: &NetworkEnvironment,
+    chain_id: ChainId,
+    sender: Address,
+    key: U192,
+) -> Result<U256> {
+    let provider = network_env.create_provider(chain_id).await?;
+    let entry_point = IEntryPoint::new(network_env.entry_point(chain_id)?, provider);
+    let nonce = entry_point
+        .getNonce(sender, key)
+        .call()
+        .await
+        .map_err(|e| crate::EilError::AlloyContract(format!("getNonce: {}", e)))?
+        .nonce;
+    Ok(nonce)
+}
+
+/// Check whether code is deployed at `address` on a chain
+pub async fn is_deployed(
+    network_env: &NetworkEnvironment,
+    chain_id: ChainId,
+    address: Address,
+) -> Result<bool> {
+    let provider = network_env.create_provider(chain_id).await?;
+    let code = provider
+        .get_code_at(address)
+        .await
+        .map_err(|e| crate::EilError::AlloyProvider(e.to_string()))?;
+    Ok(!code.is_empty())
+}
+
+/// Check on every chain whether the account is deployed
+pub async fn deployment_status(
+    network_env: &NetworkEnvironment,
+    addresses: &HashMap<ChainId, Address>,
+) -> Result<HashMap<ChainId, bool>> {
+    let checks = addresses.iter().map(|(chain_id, address)| async move {
+        let deployed = is_deployed(network_env, *chain_id, *address).await?;
+        Ok::<_, crate::EilError>((*chain_id, deployed))
+    });
+    Ok(futures::future::try_join_all(checks)
+        .await?
+        .into_iter()
+        .collect())
+}
+
+/// Get the factory args for `address`, or `(None, None)` if it is already deployed
+pub async fn get_factory_args_if_undeployed(
+    network_env: &NetworkEnvironment,
+    chain_id: ChainId,
+    address: Address,
+    factory: Option<&AccountFactory>,
+) -> Result<(Option<Address>, Option<Hex>)> {
+    let Some(factory) = factory else {
+        return Ok((None, None));
+    };
+    if is_deployed(network_env, chain_id, address).await? {
+        return Ok((None, None));
+    }
+    Ok((Some(factory.factory), Some(factory.factory_data.clone())))
+}
+
+/// Signing mode for a set of UserOperations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SigningMode {
+    /// Sign each UserOperation hash separately (one signature prompt per chain)
+    #[default]
+    PerOperation,
+    /// Sign the Merkle root of all UserOperation hashes once.
+    /// Each signature carries the root signature and the op's Merkle proof,
+    /// see [`crate::merkle::encode_signature`]
+    MerkleRoot,
+}
+
+/// Sign UserOperations with the given signer and signing mode
+pub async fn sign_user_ops_with_mode(
+    signer: &dyn Signer,
+    signing_mode: SigningMode,
+    mut user_ops: Vec<UserOperation>,
+) -> Result<Vec<UserOperation>> {
+    match signing_mode {
+        SigningMode::PerOperation => {
+            for user_op in &mut user_ops {
+                let hash = crate::user_op::compute_user_op_hash(user_op)?;
+                user_op.signature = signer.sign(&hash.0).await?;
+            }
+        }
+        SigningMode::MerkleRoot => {
+            if user_ops.is_empty() {
+                return Ok(user_ops);
+            }
+
+            let hashes = user_ops
+                .iter()
+                .map(crate::user_op::compute_user_op_hash)
+                .collect::<Result<Vec<_>>>()?;
+            let tree = crate::merkle::MerkleTree::new(hashes)?;
+            let root_signature = signer.sign(&tree.root().0).await?;
+
+            for (index, user_op) in user_ops.iter_mut().enumerate() {
+                user_op.signature = crate::merkle::encode_signature(
+                    tree.root(),
+                    &tree.proof(index)?,
+                    &root_signature,
+                );
+            }
+        }
+    }
+    Ok(user_ops)
 }
 
 /// Signer trait for signing UserOperations
@@ -59,6 +268,13 @@ pub trait Signer: Send + Sync {
     /// Sign a UserOperation hash
     async fn sign(&self, hash: &[u8; 32]) -> Result<Hex>;
 
+    /// Sign an EIP-712 signing hash (`keccak256(0x1901 || domainSeparator || hashStruct)`)
+    /// as is, without an EIP-191 message prefix
+    /// Signers without raw-hash signing fail with [`crate::EilError::SignerUnavailable`].
+    async fn sign_typed_data_hash(&self, _hash: &[u8; 32]) -> Result<Hex> {
+        Err(crate::EilError::SignerUnavailable)
+    }
+
     /// Get the signer address
     fn address(&self) -> Address;
 }
@@ -83,16 +299,11 @@ impl MultiChainSmartAccount for BaseMultichainSmartAccount {
         self.addresses
             .get(&chain_id)
             .copied()
-            .ok_or_else(|| crate::EilError::UnsupportedChain(chain_id))
+            .ok_or(crate::EilError::UnsupportedChain(chain_id))
     }
 
-    async fn sign_user_ops(&self, mut user_ops: Vec<UserOperation>) -> Result<Vec<UserOperation>> {
-        for user_op in &mut user_ops {
-            let hash = compute_user_op_hash(user_op)?;
-            let signature = self.signer.sign(&hash).await?;
-            user_op.signature = signature;
-        }
-        Ok(user_ops)
+    async fn sign_user_ops(&self, user_ops: Vec<UserOperation>) -> Result<Vec<UserOperation>> {
+        sign_user_ops_with_mode(self.signer.as_ref(), self.signing_mode, user_ops).await
     }
 
     async fn encode_calls(&self, _chain_id: ChainId, calls: Vec<Call>) -> Result<Hex> {
@@ -126,20 +337,301 @@ impl MultiChainSmartAccount for BaseMultichainSmartAccount {
             .await
     }
 
-    async fn get_nonce(&self, _chain_id: ChainId) -> Result<alloy::primitives::U256> {
+    async fn get_nonce(&self, _chain_id: ChainId) -> Result<U256> {
         // Placeholder - would query EntryPoint contract
-        Ok(alloy::primitives::U256::from(0))
+        Ok(U256::from(0))
     }
 
     async fn get_factory_args(&self, _chain_id: ChainId) -> Result<(Option<Address>, Option<Hex>)> {
         // Placeholder - would return factory and initCode if account not deployed
         Ok((None, None))
     }
+
+    fn signer(&self) -> Option<&dyn Signer> {
+        Some(self.signer.as_ref())
+    }
+}
+
+/// Account-specific encoding of the calls a [`SmartAccount`] executes
+pub trait CallEncoder: Send + Sync {
+    /// Encode calls as the account's execution calldata
+    fn encode_calls(&self, calls: &[Call]) -> Hex;
+
+    /// Encode a DELEGATECALL of `target` with `data`, if the account supports it
+    fn encode_delegate_call(&self, _target: Address, _data: &[u8]) -> Option<Hex> {
+        None
+    }
+}
+
+/// EntryPoint account deployed through a factory, executing calls encoded by `E`
+///
+/// Nonces are read from the EntryPoint under the account's nonce key, and the factory
+/// args are only returned on chains where the account is not deployed yet.
+pub struct SmartAccount<E> {
+    base: BaseMultichainSmartAccount,
+    network_env: NetworkEnvironment,
+    factory: Option<AccountFactory>,
+    nonce_key: U192,
+    encoder: E,
+}
+
+impl<E: CallEncoder + Default> SmartAccount<E> {
+    /// Create a new account from a base account and network environment
+    pub fn new(base: BaseMultichainSmartAccount, network_env: NetworkEnvironment) -> Self {
+        Self::with_encoder(base, network_env, E::default())
+    }
+}
+
+impl<E: CallEncoder> SmartAccount<E> {
+    /// Create a new account executing calls encoded by `encoder`
+    pub fn with_encoder(
+        base: BaseMultichainSmartAccount,
+        network_env: NetworkEnvironment,
+        encoder: E,
+    ) -> Self {
+        Self {
+            base,
+            network_env,
+            factory: None,
+            nonce_key: U192::ZERO,
+            encoder,
+        }
+    }
+
+    /// Set the factory used to deploy the account where it does not exist yet
+    pub fn with_factory(mut self, factory: Address, factory_data: Hex) -> Self {
+        self.factory = Some(AccountFactory {
+            factory,
+            factory_data,
+        });
+        self
+    }
+
+    /// Set the EntryPoint nonce key
+    /// Validator-based accounts usually encode the validator address in the key
+    pub fn with_nonce_key(mut self, nonce_key: U192) -> Self {
+        self.nonce_key = nonce_key;
+        self
+    }
+
+    /// Check on every chain whether the account is deployed
+    pub async fn deployment_status(&self) -> Result<HashMap<ChainId, bool>> {
+        deployment_status(&self.network_env, &self.base.addresses).await
+    }
+
+    /// Call encoder of the account
+    pub fn encoder(&self) -> &E {
+        &self.encoder
+    }
+
+    /// Mutable call encoder of the account
+    pub fn encoder_mut(&mut self) -> &mut E {
+        &mut self.encoder
+    }
+}
+
+#[async_trait]
+impl<E: CallEncoder> MultiChainSmartAccount for SmartAccount<E> {
+    fn address_on(&self, chain_id: ChainId) -> Result<Address> {
+        self.base.address_on(chain_id)
+    }
+
+    async fn sign_user_ops(&self, user_ops: Vec<UserOperation>) -> Result<Vec<UserOperation>> {
+        self.base.sign_user_ops(user_ops).await
+    }
+
+    async fn encode_calls(&self, _chain_id: ChainId, calls: Vec<Call>) -> Result<Hex> {
+        Ok(self.encoder.encode_calls(&calls))
+    }
+
+    async fn encode_delegate_call(
+        &self,
+        chain_id: ChainId,
+        target: Address,
+        data: Hex,
+    ) -> Result<Hex> {
+        self.encoder
+            .encode_delegate_call(target, &data)
+            .ok_or(crate::EilError::DelegateCallUnsupported(chain_id))
+    }
+
+    async fn send_user_operation(&self, user_op: UserOperation) -> Result<Hex> {
+        self.base.send_user_operation(user_op).await
+    }
+
+    async fn verify_bundler_config(&self, chain_id: ChainId, entry_point: Address) -> Result<()> {
+        self.base.verify_bundler_config(chain_id, entry_point).await
+    }
+
+    async fn get_nonce(&self, chain_id: ChainId) -> Result<U256> {
+        let sender = self.address_on(chain_id)?;
+        get_entry_point_nonce(&self.network_env, chain_id, sender, self.nonce_key).await
+    }
+
+    async fn get_factory_args(&self, chain_id: ChainId) -> Result<(Option<Address>, Option<Hex>)> {
+        let address = self.address_on(chain_id)?;
+        get_factory_args_if_undeployed(&self.network_env, chain_id, address, self.factory.as_ref())
+            .await
+    }
+
+    fn signer(&self) -> Option<&dyn Signer> {
+        self.base.signer()
+    }
 }
 
-/// Compute UserOperation hash for signing
-fn compute_user_op_hash(user_op: &UserOperation) -> Result<[u8; 32]> {
-    // Placeholder - actual implementation would use EIP-712 hashing
-    // with proper domain separator including chainId and entryPoint
-    Ok([0u8; 32])
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        create_test_config, create_test_user_op, MockBundlerManager, MockRpcServer,
+    };
+    use alloy::primitives::B256;
+    use std::sync::{Arc, Mutex};
+
+    /// Signer recording every hash it is asked to sign
+    struct RecordingSigner {
+        signed: Arc<Mutex<Vec<[u8; 32]>>>,
+    }
+
+    #[async_trait]
+    impl Signer for RecordingSigner {
+        async fn sign(&self, hash: &[u8; 32]) -> Result<Hex> {
+            self.signed.lock().unwrap().push(*hash);
+            Ok(Hex::from(vec![0xab; 65]))
+        }
+
+        fn address(&self) -> Address {
+            Address::ZERO
+        }
+    }
+
+    fn create_test_account(
+        signing_mode: SigningMode,
+    ) -> (BaseMultichainSmartAccount, Arc<Mutex<Vec<[u8; 32]>>>) {
+        let signed = Arc::new(Mutex::new(Vec::new()));
+        let account = BaseMultichainSmartAccount::new(
+            HashMap::new(),
+            Box::new(RecordingSigner {
+                signed: signed.clone(),
+            }),
+            Box::new(MockBundlerManager::new()),
+        )
+        .with_signing_mode(signing_mode);
+        (account, signed)
+    }
+
+    fn create_test_user_ops() -> Vec<UserOperation> {
+        [1u64, 10, 42161]
+            .into_iter()
+            .map(create_test_user_op)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_sign_typed_data_hash_unavailable_by_default() {
+        let signer = RecordingSigner {
+            signed: Arc::new(Mutex::new(Vec::new())),
+        };
+        assert!(matches!(
+            signer.sign_typed_data_hash(&[1u8; 32]).await,
+            Err(crate::EilError::SignerUnavailable)
+        ));
+        assert!(signer.signed.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_counterfactual_address() {
+        // EIP-1014 examples with init code `0x00`
+        let init_code_hash = alloy::primitives::keccak256([0u8]);
+        assert_eq!(
+            counterfactual_address(Address::ZERO, B256::ZERO, init_code_hash),
+            "0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38"
+                .parse::<Address>()
+                .unwrap()
+        );
+        assert_eq!(
+            counterfactual_address(
+                "0xdeadbeef00000000000000000000000000000000".parse().unwrap(),
+                B256::ZERO,
+                init_code_hash
+            ),
+            "0xB928f69Bb1D91Cd65274e3c79d8986362984fDA3"
+                .parse::<Address>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_counterfactual_addresses_all_chains() {
+        let config = create_test_config(vec![1, 10, 42161]);
+        let addresses = counterfactual_addresses(
+            &config,
+            Address::repeat_byte(0xfa),
+            B256::repeat_byte(1),
+            B256::repeat_byte(2),
+        );
+
+        assert_eq!(addresses.len(), 3);
+        let expected = counterfactual_address(
+            Address::repeat_byte(0xfa),
+            B256::repeat_byte(1),
+            B256::repeat_byte(2),
+        );
+        assert!(addresses.values().all(|address| *address == expected));
+    }
+
+    #[tokio::test]
+    async fn test_deployment_status() {
+        let mainnet = MockRpcServer::start(|method, _| match method {
+            "eth_chainId" => Ok(serde_json::json!("0x1")),
+            _ => Ok(serde_json::json!("0x")),
+        })
+        .await;
+        let optimism = MockRpcServer::start(|method, _| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            _ => Ok(serde_json::json!("0x6080")),
+        })
+        .await;
+        let mut config = create_test_config(vec![1, 10]);
+        config.chain_infos[0].rpc_url = mainnet.url.clone();
+        config.chain_infos[1].rpc_url = optimism.url.clone();
+        let network_env = NetworkEnvironment::new(&config);
+        let addresses = counterfactual_addresses(
+            &config,
+            Address::repeat_byte(0xfa),
+            B256::ZERO,
+            B256::ZERO,
+        );
+
+        let status = deployment_status(&network_env, &addresses).await.unwrap();
+        assert_eq!(status, HashMap::from([(1, false), (10, true)]));
+    }
+
+    #[tokio::test]
+    async fn test_sign_user_ops_per_operation() {
+        let (account, signed) = create_test_account(SigningMode::PerOperation);
+        let user_ops = account.sign_user_ops(create_test_user_ops()).await.unwrap();
+
+        assert_eq!(signed.lock().unwrap().len(), 3);
+        for user_op in &user_ops {
+            assert_eq!(user_op.signature.len(), 65);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sign_user_ops_merkle_root_signs_once() {
+        let (account, signed) = create_test_account(SigningMode::MerkleRoot);
+        let user_ops = account.sign_user_ops(create_test_user_ops()).await.unwrap();
+
+        let signed = signed.lock().unwrap();
+        assert_eq!(signed.len(), 1);
+
+        for user_op in &user_ops {
+            let hash = crate::user_op::compute_user_op_hash(user_op).unwrap();
+            let (root, root_signature) =
+                crate::merkle::verify_signature(hash, &user_op.signature).unwrap();
+            assert_eq!(root, B256::from(signed[0]));
+            assert_eq!(root_signature, Hex::from(vec![0xab; 65]));
+        }
+    }
 }
@@ -1,9 +1,21 @@
 use crate::{
+    account::Signer,
     contract_types::SdkVoucherRequest,
+    contracts::{IERC20Permit, IPermit2, Permit, PermitTransferFrom, TokenPermissions},
     multichain::MultichainToken,
     types::*,
     Result,
 };
+use alloy::{
+    json_abi::JsonAbi,
+    primitives::{
+        aliases::{U160, U48},
+        keccak256,
+        ruint::UintTryFrom,
+        B256, U256,
+    },
+    sol_types::{Eip712Domain, SolCall, SolStruct},
+};
 use async_trait::async_trait;
 
 /// Base trait for all actions
@@ -13,6 +25,17 @@ pub trait Action: Send + Sync {
     /// Encode this action as an array of Call objects
     /// The batch parameter provides context like chain ID
     async fn encode_call(&self, batch: &crate::builder::BatchBuilder) -> Result<Vec<Call>>;
+
+    /// Runtime variable set by this action, if any
+    /// Later actions in the same batch may read it
+    fn defined_var(&self) -> Option<&str> {
+        None
+    }
+
+    /// ABI of the called contract, used to decode its custom errors
+    fn abi(&self) -> Option<&JsonAbi> {
+        None
+    }
 }
 
 /// Transfer ERC20 tokens
@@ -29,58 +52,32 @@ pub struct TransferAction {
 #[async_trait]
 impl Action for TransferAction {
     async fn encode_call(&self, batch: &crate::builder::BatchBuilder) -> Result<Vec<Call>> {
-        let token_address = self
-            .token
-            .address_on(batch.chain_id())
-            .ok_or_else(|| crate::EilError::InvalidAddress {
-                chain_id: batch.chain_id(),
-                address: format!("Token {} not deployed", self.token.name),
-            })?;
+        if !self.token.is_native_on(batch.chain_id()) {
+            return encode_token_call(batch, &self.token, "transfer", self.recipient, &self.amount);
+        }
+        match &self.amount {
+            Amount::Fixed(amount) => Ok(vec![native_transfer(self.recipient, *amount)]),
+            Amount::Runtime(_) => Err(crate::EilError::UnsupportedNativeOperation(format!(
+                "runtime amount of {}",
+                self.token.name
+            ))),
+        }
+    }
+}
 
-        // Encode transfer function call
-        let abi = self.token.abi();
-        let functions = abi
-            .function("transfer")
-            .ok_or_else(|| crate::EilError::Generic("transfer function not found".into()))?;
-
-        let function = functions
-            .first()
-            .ok_or_else(|| crate::EilError::Generic("transfer function not found".into()))?;
-
-        // Encode based on whether amount is fixed or runtime
-        let data = match &self.amount {
-            Amount::Fixed(amount) => {
-                use alloy::dyn_abi::DynSolValue;
-                let args = vec![
-                    DynSolValue::Address(self.recipient),
-                    DynSolValue::Uint(*amount, 256),
-                ];
-
-                // Encode function call: selector + encoded args
-                let mut encoded = function.selector().to_vec();
-
-                // Encode args as a tuple
-                let tuple = alloy::dyn_abi::DynSolValue::Tuple(args);
-                let encoded_args = tuple.abi_encode();
-                // Skip the first 32 bytes (offset for dynamic tuple) if needed
-                // For simple types like (address, uint256), we can use it directly
-                encoded.extend_from_slice(&encoded_args);
-                encoded
-            }
-            Amount::Runtime(var) => {
-                // For runtime variables, we'll need special encoding
-                // This will be handled by the runtime vars system
-                return Err(crate::EilError::Generic(
-                    "Runtime variables not yet implemented in encode".into(),
-                ));
-            }
-        };
+/// Send native currency (e.g. ETH)
+#[derive(Debug, Clone)]
+pub struct NativeTransferAction {
+    /// Recipient address
+    pub recipient: Address,
+    /// Amount in wei
+    pub amount: U256,
+}
 
-        Ok(vec![Call {
-            target: token_address,
-            data: data.into(),
-            value: None,
-        }])
+#[async_trait]
+impl Action for NativeTransferAction {
+    async fn encode_call(&self, _batch: &crate::builder::BatchBuilder) -> Result<Vec<Call>> {
+        Ok(vec![native_transfer(self.recipient, self.amount)])
     }
 }
 
@@ -98,51 +95,207 @@ pub struct ApproveAction {
 #[async_trait]
 impl Action for ApproveAction {
     async fn encode_call(&self, batch: &crate::builder::BatchBuilder) -> Result<Vec<Call>> {
-        let token_address = self
-            .token
-            .address_on(batch.chain_id())
-            .ok_or_else(|| crate::EilError::InvalidAddress {
-                chain_id: batch.chain_id(),
-                address: format!("Token {} not deployed", self.token.name),
-            })?;
+        if self.token.is_native_on(batch.chain_id()) {
+            return Err(crate::EilError::UnsupportedNativeOperation(format!(
+                "approve {}",
+                self.token.name
+            )));
+        }
+        encode_token_call(batch, &self.token, "approve", self.spender, &self.value)
+    }
+}
 
-        let abi = self.token.abi();
-        let functions = abi
-            .function("approve")
-            .ok_or_else(|| crate::EilError::Generic("approve function not found".into()))?;
-
-        let function = functions
-            .first()
-            .ok_or_else(|| crate::EilError::Generic("approve function not found".into()))?;
-
-        let data = match &self.value {
-            Amount::Fixed(amount) => {
-                use alloy::dyn_abi::DynSolValue;
-                let args = vec![
-                    DynSolValue::Address(self.spender),
-                    DynSolValue::Uint(*amount, 256),
-                ];
-
-                // Encode function call: selector + encoded args
-                let mut encoded = function.selector().to_vec();
-
-                // Encode args as a tuple
-                let tuple = alloy::dyn_abi::DynSolValue::Tuple(args);
-                let encoded_args = tuple.abi_encode();
-                // Skip the first 32 bytes (offset for dynamic tuple) if needed
-                // For simple types like (address, uint256), we can use it directly
-                encoded.extend_from_slice(&encoded_args);
-                encoded
-            }
-            Amount::Runtime(_var) => {
-                return Err(crate::EilError::Generic(
-                    "Runtime variables not yet implemented in encode".into(),
-                ));
-            }
+/// Approve ERC20 token spending with an EIP-2612 `permit` signed off-chain
+///
+/// The token owner is the account's signer EOA, not the smart account: EIP-2612
+/// verifies the signature with `ecrecover`, so only the signer's own tokens can be
+/// approved. The nonce and DOMAIN_SEPARATOR are read from the token when the batch
+/// is built.
+#[derive(Debug, Clone)]
+pub struct PermitAction {
+    /// Token to approve, must implement EIP-2612
+    pub token: MultichainToken,
+    /// Spender address
+    pub spender: Address,
+    /// Amount to approve
+    pub value: U256,
+    /// Timestamp after which the permit is invalid
+    pub deadline: U256,
+}
+
+#[async_trait]
+impl Action for PermitAction {
+    async fn encode_call(&self, batch: &crate::builder::BatchBuilder) -> Result<Vec<Call>> {
+        let token = permit_token_address(batch, &self.token, "permit")?;
+        let signer = account_signer(batch)?;
+        let owner = signer.address();
+        let (domain_separator, nonce) = read_permit_domain(batch, token, owner).await?;
+
+        let permit = Permit {
+            owner,
+            spender: self.spender,
+            value: self.value,
+            nonce,
+            deadline: self.deadline,
         };
+        let hash = typed_data_hash(domain_separator, permit.eip712_hash_struct());
+        let (v, r, s) = split_signature(&signer.sign_typed_data_hash(&hash.0).await?)?;
 
+        let data = IERC20Permit::permitCall {
+            owner,
+            spender: self.spender,
+            value: self.value,
+            deadline: self.deadline,
+            v,
+            r,
+            s,
+        }
+        .abi_encode();
         Ok(vec![Call {
-            target: token_address,
+            target: token,
+            data: data.into(),
+            value: None,
+        }])
+    }
+}
+
+/// Transfer ERC20 tokens of the account's signer with a Permit2 `permitTransferFrom`
+/// The signer must have approved Permit2 on the token. The account calls Permit2
+/// and is the signed spender.
+#[derive(Debug, Clone)]
+pub struct Permit2TransferAction {
+    /// Token to transfer
+    pub token: MultichainToken,
+    /// Recipient address
+    pub recipient: Address,
+    /// Amount to transfer
+    pub amount: U256,
+    /// Unordered Permit2 nonce, each nonce can be used once
+    pub nonce: U256,
+    /// Timestamp after which the signature is invalid
+    pub deadline: U256,
+}
+
+#[async_trait]
+impl Action for Permit2TransferAction {
+    async fn encode_call(&self, batch: &crate::builder::BatchBuilder) -> Result<Vec<Call>> {
+        let token = permit_token_address(batch, &self.token, "Permit2 transfer")?;
+        let permit2 = batch.network_env().permit2(batch.chain_id())?;
+        let spender = batch.account()?.address_on(batch.chain_id())?;
+        let signer = account_signer(batch)?;
+
+        let message = PermitTransferFrom {
+            permitted: TokenPermissions {
+                token,
+                amount: self.amount,
+            },
+            spender,
+            nonce: self.nonce,
+            deadline: self.deadline,
+        };
+        let hash = typed_data_hash(
+            permit2_domain(batch.chain_id(), permit2),
+            message.eip712_hash_struct(),
+        );
+        let signature = signer.sign_typed_data_hash(&hash.0).await?;
+
+        let data = IPermit2::permitTransferFromCall {
+            permit: IPermit2::PermitTransferFrom {
+                permitted: IPermit2::TokenPermissions {
+                    token,
+                    amount: self.amount,
+                },
+                nonce: self.nonce,
+                deadline: self.deadline,
+            },
+            transferDetails: IPermit2::SignatureTransferDetails {
+                to: self.recipient,
+                requestedAmount: self.amount,
+            },
+            owner: signer.address(),
+            signature,
+        }
+        .abi_encode();
+        Ok(vec![Call {
+            target: permit2,
+            data: data.into(),
+            value: None,
+        }])
+    }
+}
+
+/// Set the Permit2 allowance of a spender on tokens of the account's signer with a
+/// signed Permit2 `permit`
+/// The signer must have approved Permit2 on the token. The nonce is read from Permit2.
+#[derive(Debug, Clone)]
+pub struct Permit2AllowanceAction {
+    /// Token to approve
+    pub token: MultichainToken,
+    /// Spender address
+    pub spender: Address,
+    /// Allowance, at most `uint160` max
+    pub amount: U256,
+    /// Timestamp the allowance expires at
+    pub expiration: u64,
+    /// Timestamp after which the signature is invalid
+    pub sig_deadline: U256,
+}
+
+#[async_trait]
+impl Action for Permit2AllowanceAction {
+    async fn encode_call(&self, batch: &crate::builder::BatchBuilder) -> Result<Vec<Call>> {
+        let token = permit_token_address(batch, &self.token, "Permit2 permit")?;
+        let permit2 = batch.network_env().permit2(batch.chain_id())?;
+        let signer = account_signer(batch)?;
+        let owner = signer.address();
+        let amount = U160::uint_try_from(self.amount).map_err(|_| {
+            crate::EilError::InvalidAmount(format!(
+                "{} exceeds the Permit2 allowance range",
+                self.amount
+            ))
+        })?;
+        let expiration = U48::uint_try_from(self.expiration).map_err(|_| {
+            crate::EilError::Generic(format!(
+                "Permit2 expiration {} out of range",
+                self.expiration
+            ))
+        })?;
+
+        let provider = batch
+            .network_env()
+            .create_provider(batch.chain_id())
+            .await?;
+        let nonce = IPermit2::new(permit2, provider)
+            .allowance(owner, token, self.spender)
+            .call()
+            .await
+            .map_err(|e| crate::EilError::AlloyContract(format!("allowance: {}", e)))?
+            .nonce;
+
+        let permit_single = IPermit2::PermitSingle {
+            details: IPermit2::PermitDetails {
+                token,
+                amount,
+                expiration,
+                nonce,
+            },
+            spender: self.spender,
+            sigDeadline: self.sig_deadline,
+        };
+        let hash = typed_data_hash(
+            permit2_domain(batch.chain_id(), permit2),
+            permit_single.eip712_hash_struct(),
+        );
+        let signature = signer.sign_typed_data_hash(&hash.0).await?;
+
+        let data = IPermit2::permitCall {
+            owner,
+            permitSingle: permit_single,
+            signature,
+        }
+        .abi_encode();
+        Ok(vec![Call {
+            target: permit2,
             data: data.into(),
             value: None,
         }])
@@ -159,33 +312,7 @@ pub struct FunctionCallAction {
 #[async_trait]
 impl Action for FunctionCallAction {
     async fn encode_call(&self, batch: &crate::builder::BatchBuilder) -> Result<Vec<Call>> {
-        let functions = self
-            .call
-            .abi
-            .function(&self.call.function_name)
-            .ok_or_else(|| {
-                crate::EilError::Generic(format!(
-                    "Function {} not found",
-                    self.call.function_name
-                ))
-            })?;
-
-        let function = functions
-            .first()
-            .ok_or_else(|| {
-                crate::EilError::Generic(format!(
-                    "Function {} not found",
-                    self.call.function_name
-                ))
-            })?;
-
-        // Encode function call: selector + encoded args
-        let mut data = function.selector().to_vec();
-
-        // Encode args as a tuple
-        let tuple = alloy::dyn_abi::DynSolValue::Tuple(self.call.args.clone());
-        let encoded_args = tuple.abi_encode();
-        data.extend_from_slice(&encoded_args);
+        let data = crate::runtime_vars::encode_function_call(&self.call)?;
 
         // Validate target address exists on this chain
         if !is_valid_address(self.call.target) {
@@ -201,10 +328,14 @@ impl Action for FunctionCallAction {
 
         Ok(vec![Call {
             target: self.call.target,
-            data: data.into(),
+            data,
             value: self.call.value,
         }])
     }
+
+    fn abi(&self) -> Option<&JsonAbi> {
+        Some(&self.call.abi)
+    }
 }
 
 /// Voucher request action
@@ -245,12 +376,180 @@ impl SetVarAction {
 
 #[async_trait]
 impl Action for SetVarAction {
-    async fn encode_call(&self, _batch: &crate::builder::BatchBuilder) -> Result<Vec<Call>> {
-        // Runtime variable setting requires special encoding through RuntimeVarsHelper
-        // This will be implemented in the runtime variables module
-        Err(crate::EilError::Generic(
-            "SetVarAction encoding not yet implemented".into(),
-        ))
+    async fn encode_call(&self, batch: &crate::builder::BatchBuilder) -> Result<Vec<Call>> {
+        let helper = batch.network_env().runtime_vars_helper(batch.chain_id())?;
+        Ok(vec![crate::runtime_vars::encode_set_var(
+            helper,
+            &self.var_name,
+            &self.call,
+        )?])
+    }
+
+    fn defined_var(&self) -> Option<&str> {
+        Some(&self.var_name)
+    }
+
+    fn abi(&self) -> Option<&JsonAbi> {
+        Some(&self.call.abi)
+    }
+}
+
+/// Plain value transfer with empty calldata
+fn native_transfer(recipient: Address, amount: U256) -> Call {
+    Call {
+        target: recipient,
+        data: Hex::new(),
+        value: Some(amount),
+    }
+}
+
+/// Token address on the batch chain, rejecting native tokens
+fn permit_token_address(
+    batch: &crate::builder::BatchBuilder,
+    token: &MultichainToken,
+    operation: &str,
+) -> Result<Address> {
+    if token.is_native_on(batch.chain_id()) {
+        return Err(crate::EilError::UnsupportedNativeOperation(format!(
+            "{} {}",
+            operation, token.name
+        )));
+    }
+    token
+        .address_on(batch.chain_id())
+        .ok_or_else(|| crate::EilError::InvalidAddress {
+            chain_id: batch.chain_id(),
+            address: format!("Token {} not deployed", token.name),
+        })
+}
+
+/// Signer of the account executing the batch
+fn account_signer(batch: &crate::builder::BatchBuilder) -> Result<&dyn Signer> {
+    batch
+        .account()?
+        .signer()
+        .ok_or(crate::EilError::SignerUnavailable)
+}
+
+/// Read the DOMAIN_SEPARATOR of an EIP-2612 token and the permit nonce of `owner`
+/// The on-chain separator is signed as is, whatever name, version or salt it is built from.
+async fn read_permit_domain(
+    batch: &crate::builder::BatchBuilder,
+    token: Address,
+    owner: Address,
+) -> Result<(B256, U256)> {
+    use std::future::IntoFuture;
+
+    let provider = batch
+        .network_env()
+        .create_provider(batch.chain_id())
+        .await?;
+    let contract = IERC20Permit::new(token, provider);
+    let nonces = contract.nonces(owner);
+    let domain_separator = contract.DOMAIN_SEPARATOR();
+    let (nonce, domain_separator) = futures::join!(
+        nonces.call().into_future(),
+        domain_separator.call().into_future()
+    );
+    let contract_error = |function: &str, e: alloy::contract::Error| {
+        crate::EilError::AlloyContract(format!("{}: {}", function, e))
+    };
+    let nonce = nonce.map_err(|e| contract_error("nonces", e))?._0;
+    let domain_separator = domain_separator
+        .map_err(|e| contract_error("DOMAIN_SEPARATOR", e))?
+        ._0;
+    Ok((domain_separator, nonce))
+}
+
+/// EIP-712 domain separator of Permit2
+fn permit2_domain(chain_id: ChainId, permit2: Address) -> B256 {
+    Eip712Domain::new(
+        Some("Permit2".into()),
+        None,
+        Some(U256::from(chain_id)),
+        Some(permit2),
+        None,
+    )
+    .separator()
+}
+
+/// EIP-712 hash of a message: `keccak256(0x1901 || domainSeparator || hashStruct)`
+fn typed_data_hash(domain_separator: B256, struct_hash: B256) -> B256 {
+    let mut data = [0u8; 66];
+    data[..2].copy_from_slice(&[0x19, 0x01]);
+    data[2..34].copy_from_slice(domain_separator.as_slice());
+    data[34..].copy_from_slice(struct_hash.as_slice());
+    keccak256(data)
+}
+
+/// Split a 65-byte `r || s || v` signature, normalizing `v` to 27/28
+fn split_signature(signature: &[u8]) -> Result<(u8, B256, B256)> {
+    if signature.len() != 65 {
+        return Err(crate::EilError::InvalidSignature(format!(
+            "expected 65 bytes, got {}",
+            signature.len()
+        )));
+    }
+    let v = match signature[64] {
+        v @ (0 | 1) => v + 27,
+        v => v,
+    };
+    Ok((
+        v,
+        B256::from_slice(&signature[..32]),
+        B256::from_slice(&signature[32..64]),
+    ))
+}
+
+/// Encode an ERC20 `function(address, uint256)` call on the batch chain
+/// A runtime amount is spliced in by the RuntimeVarsHelper and must be set earlier in the batch
+fn encode_token_call(
+    batch: &crate::builder::BatchBuilder,
+    token: &MultichainToken,
+    function_name: &str,
+    address_arg: Address,
+    amount: &Amount,
+) -> Result<Vec<Call>> {
+    use alloy::dyn_abi::{DynSolValue, JsonAbiExt};
+
+    let token_address =
+        token
+            .address_on(batch.chain_id())
+            .ok_or_else(|| crate::EilError::InvalidAddress {
+                chain_id: batch.chain_id(),
+                address: format!("Token {} not deployed", token.name),
+            })?;
+
+    let abi = token.abi();
+    let function = abi
+        .function(function_name)
+        .and_then(|functions| functions.first())
+        .ok_or_else(|| crate::EilError::Generic(format!("{} function not found", function_name)))?;
+
+    let (amount_value, var_ref) = crate::runtime_vars::amount_arg(amount, 1);
+    let data = function
+        .abi_encode_input(&[DynSolValue::Address(address_arg), amount_value])
+        .map_err(|e| {
+            crate::EilError::Generic(format!("Failed to encode {}: {}", function_name, e))
+        })?;
+
+    let call = Call {
+        target: token_address,
+        data: data.into(),
+        value: None,
+    };
+
+    match var_ref {
+        None => Ok(vec![call]),
+        Some(var_ref) => {
+            batch.require_var(&var_ref.name)?;
+            let helper = batch.network_env().runtime_vars_helper(batch.chain_id())?;
+            Ok(vec![crate::runtime_vars::encode_call_with_vars(
+                helper,
+                call,
+                &[var_ref],
+            )?])
+        }
     }
 }
 
@@ -258,3 +557,308 @@ impl Action for SetVarAction {
 fn is_valid_address(address: Address) -> bool {
     !address.is_zero()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        account::BaseMultichainSmartAccount,
+        builder::BatchBuilder,
+        test_utils::{call_request, create_test_config, MockBundlerManager, MockRpcServer},
+        EilSdk,
+    };
+    use alloy::{
+        signers::{local::PrivateKeySigner, Signature, SignerSync},
+        sol_types::SolValue,
+    };
+    use std::{collections::HashMap, sync::Arc};
+
+    const TOKEN: Address = Address::repeat_byte(0x44);
+    const PERMIT2: Address = Address::repeat_byte(0x77);
+    const ACCOUNT: Address = Address::repeat_byte(0x33);
+    const SPENDER: Address = Address::repeat_byte(0x55);
+
+    struct LocalSigner(PrivateKeySigner);
+
+    /// Signs UserOperation hashes as EIP-191 messages, like wallet signers
+    #[async_trait]
+    impl Signer for LocalSigner {
+        async fn sign(&self, hash: &[u8; 32]) -> Result<Hex> {
+            let signature = self
+                .0
+                .sign_message_sync(hash)
+                .map_err(|e| crate::EilError::AlloySigner(e.to_string()))?;
+            Ok(Hex::from(signature.as_bytes().to_vec()))
+        }
+
+        async fn sign_typed_data_hash(&self, hash: &[u8; 32]) -> Result<Hex> {
+            let signature = self
+                .0
+                .sign_hash_sync(&B256::from(*hash))
+                .map_err(|e| crate::EilError::AlloySigner(e.to_string()))?;
+            Ok(Hex::from(signature.as_bytes().to_vec()))
+        }
+
+        fn address(&self) -> Address {
+            self.0.address()
+        }
+    }
+
+    fn token_domain() -> Eip712Domain {
+        Eip712Domain::new(
+            Some("USD Coin".into()),
+            Some("2".into()),
+            Some(U256::from(1)),
+            Some(TOKEN),
+            None,
+        )
+    }
+
+    /// Serve an EIP-2612 token at TOKEN with `domain` and Permit2 allowances with nonce 5
+    /// `domain: None` makes `DOMAIN_SEPARATOR()` revert.
+    async fn start_permit_chain(domain: Option<Eip712Domain>) -> MockRpcServer {
+        let domain_separator = domain.map(|domain| domain.separator());
+        MockRpcServer::start(move |method, params| match method {
+            "eth_chainId" => Ok(serde_json::json!("0x1")),
+            "eth_call" => {
+                let (_, data) = call_request(params);
+                let result = match <[u8; 4]>::try_from(&data[..4]).unwrap() {
+                    IERC20Permit::noncesCall::SELECTOR => U256::from(3).abi_encode(),
+                    IERC20Permit::DOMAIN_SEPARATORCall::SELECTOR => match domain_separator {
+                        Some(domain_separator) => domain_separator.abi_encode(),
+                        None => return Err((3, "execution reverted".to_string())),
+                    },
+                    IPermit2::allowanceCall::SELECTOR => {
+                        (U160::ZERO, U48::ZERO, U48::from(5)).abi_encode()
+                    }
+                    _ => return Err((-32000, "unknown function".to_string())),
+                };
+                Ok(serde_json::json!(Hex::from(result)))
+            }
+            _ => Err((-32601, format!("method {} not found", method))),
+        })
+        .await
+    }
+
+    fn create_batch(
+        url: &str,
+        signer: &PrivateKeySigner,
+        permit2: Option<Address>,
+    ) -> BatchBuilder {
+        let mut config = create_test_config(vec![1]);
+        config.chain_infos[0].rpc_url = url.to_string();
+        config.chain_infos[0].permit2 = permit2;
+        let account = BaseMultichainSmartAccount::new(
+            HashMap::from([(1, ACCOUNT)]),
+            Box::new(LocalSigner(signer.clone())),
+            Box::new(MockBundlerManager::new()),
+        );
+        EilSdk::new(config)
+            .create_builder()
+            .use_account(Arc::new(account))
+            .unwrap()
+            .start_batch(1)
+    }
+
+    fn create_token() -> MultichainToken {
+        MultichainToken::new("USDC".to_string(), HashMap::from([(1, TOKEN)]))
+    }
+
+    fn recover(signature: &[u8], hash: B256) -> Address {
+        Signature::try_from(signature)
+            .unwrap()
+            .recover_address_from_prehash(&hash)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_permit_action_signs_eip2612_permit() {
+        let server = start_permit_chain(Some(token_domain())).await;
+        let signer = PrivateKeySigner::random();
+        let batch = create_batch(&server.url, &signer, None);
+
+        let calls = PermitAction {
+            token: create_token(),
+            spender: SPENDER,
+            value: U256::from(100),
+            deadline: U256::from(1_000),
+        }
+        .encode_call(&batch)
+        .await
+        .unwrap();
+
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].target, TOKEN);
+        let call = IERC20Permit::permitCall::abi_decode(&calls[0].data, true).unwrap();
+        assert_eq!(call.owner, signer.address());
+        assert_eq!(call.spender, SPENDER);
+        assert_eq!(call.value, U256::from(100));
+        assert!(call.v == 27 || call.v == 28);
+
+        let permit = Permit {
+            owner: signer.address(),
+            spender: SPENDER,
+            value: U256::from(100),
+            nonce: U256::from(3),
+            deadline: U256::from(1_000),
+        };
+        let mut signature = [call.r.as_slice(), call.s.as_slice()].concat();
+        signature.push(call.v);
+        assert_eq!(
+            recover(&signature, permit.eip712_signing_hash(&token_domain())),
+            signer.address()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_permit_signs_onchain_domain_separator() {
+        let signer = PrivateKeySigner::random();
+        let permit = PermitAction {
+            token: create_token(),
+            spender: SPENDER,
+            value: U256::from(100),
+            deadline: U256::from(1_000),
+        };
+
+        // EIP-712 name differing from the token symbol, and a salted domain
+        let domain = Eip712Domain::new(
+            Some("USD Coin (PoS)".into()),
+            Some("1".into()),
+            None,
+            Some(TOKEN),
+            Some(B256::repeat_byte(0x01)),
+        );
+        let server = start_permit_chain(Some(domain.clone())).await;
+        let batch = create_batch(&server.url, &signer, None);
+        let calls = permit.encode_call(&batch).await.unwrap();
+        let call = IERC20Permit::permitCall::abi_decode(&calls[0].data, true).unwrap();
+        let message = Permit {
+            owner: signer.address(),
+            spender: SPENDER,
+            value: U256::from(100),
+            nonce: U256::from(3),
+            deadline: U256::from(1_000),
+        };
+        let mut signature = [call.r.as_slice(), call.s.as_slice()].concat();
+        signature.push(call.v);
+        assert_eq!(
+            recover(&signature, message.eip712_signing_hash(&domain)),
+            signer.address()
+        );
+
+        let server = start_permit_chain(None).await;
+        let batch = create_batch(&server.url, &signer, None);
+        assert!(matches!(
+            permit.encode_call(&batch).await,
+            Err(crate::EilError::AlloyContract(e)) if e.starts_with("DOMAIN_SEPARATOR")
+        ));
+
+        let native = PermitAction {
+            token: MultichainToken::native("ETH", &[1]),
+            ..permit
+        };
+        assert!(matches!(
+            native.encode_call(&batch).await,
+            Err(crate::EilError::UnsupportedNativeOperation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_permit2_transfer_action() {
+        let signer = PrivateKeySigner::random();
+        let action = Permit2TransferAction {
+            token: create_token(),
+            recipient: SPENDER,
+            amount: U256::from(250),
+            nonce: U256::from(9),
+            deadline: U256::from(1_000),
+        };
+
+        let batch = create_batch("http://127.0.0.1:1", &signer, None);
+        assert!(matches!(
+            action.encode_call(&batch).await,
+            Err(crate::EilError::Permit2NotConfigured(1))
+        ));
+
+        let batch = create_batch("http://127.0.0.1:1", &signer, Some(PERMIT2));
+        let calls = action.encode_call(&batch).await.unwrap();
+
+        assert_eq!(calls[0].target, PERMIT2);
+        let call = IPermit2::permitTransferFromCall::abi_decode(&calls[0].data, true).unwrap();
+        assert_eq!(call.owner, signer.address());
+        assert_eq!(call.permit.permitted.token, TOKEN);
+        assert_eq!(call.permit.nonce, U256::from(9));
+        assert_eq!(call.transferDetails.to, SPENDER);
+        assert_eq!(call.transferDetails.requestedAmount, U256::from(250));
+
+        // The account calling Permit2 is the signed spender
+        let message = PermitTransferFrom {
+            permitted: TokenPermissions {
+                token: TOKEN,
+                amount: U256::from(250),
+            },
+            spender: ACCOUNT,
+            nonce: U256::from(9),
+            deadline: U256::from(1_000),
+        };
+        let domain = Eip712Domain::new(
+            Some("Permit2".into()),
+            None,
+            Some(U256::from(1)),
+            Some(PERMIT2),
+            None,
+        );
+        assert_eq!(
+            recover(&call.signature, message.eip712_signing_hash(&domain)),
+            signer.address()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_permit2_allowance_action() {
+        let server = start_permit_chain(Some(token_domain())).await;
+        let signer = PrivateKeySigner::random();
+        let batch = create_batch(&server.url, &signer, Some(PERMIT2));
+        let action = Permit2AllowanceAction {
+            token: create_token(),
+            spender: SPENDER,
+            amount: U256::from(500),
+            expiration: 2_000,
+            sig_deadline: U256::from(1_000),
+        };
+
+        let calls = action.encode_call(&batch).await.unwrap();
+
+        assert_eq!(calls[0].target, PERMIT2);
+        let call = IPermit2::permitCall::abi_decode(&calls[0].data, true).unwrap();
+        assert_eq!(call.owner, signer.address());
+        assert_eq!(call.permitSingle.spender, SPENDER);
+        assert_eq!(call.permitSingle.details.amount, U160::from(500));
+        assert_eq!(call.permitSingle.details.expiration, U48::from(2_000));
+        assert_eq!(call.permitSingle.details.nonce, U48::from(5));
+
+        let too_large = Permit2AllowanceAction {
+            amount: U256::MAX,
+            ..action
+        };
+        assert!(matches!(
+            too_large.encode_call(&batch).await,
+            Err(crate::EilError::InvalidAmount(_))
+        ));
+    }
+
+    #[test]
+    fn test_split_signature() {
+        let mut signature = vec![0x11; 64];
+        signature.push(1);
+        let (v, r, s) = split_signature(&signature).unwrap();
+        assert_eq!(v, 28);
+        assert_eq!(r, B256::repeat_byte(0x11));
+        assert_eq!(s, B256::repeat_byte(0x11));
+
+        assert!(matches!(
+            split_signature(&[0u8; 64]),
+            Err(crate::EilError::InvalidSignature(_))
+        ));
+    }
+}
@@ -3,12 +3,13 @@ use crate::{
     actions::Action,
     contract_types::*,
     network::NetworkEnvironment,
+    session::EphemeralSigner,
     types::*,
-    voucher::{InternalVoucherInfo, VoucherCoordinator},
+    voucher::VoucherCoordinator,
     Result,
 };
-use alloy::primitives::{keccak256, U256};
-use std::{collections::HashSet, marker::PhantomData, sync::Arc};
+use alloy::primitives::U256;
+use std::{collections::{HashMap, HashSet}, marker::PhantomData, sync::Arc};
 
 /// Type-state for CrossChainBuilder
 pub struct Building;
@@ -20,7 +21,7 @@ pub struct CrossChainBuilder<State = Building> {
     network_env: Arc<NetworkEnvironment>,
     batches: Vec<BatchBuilder>,
     coordinator: VoucherCoordinator,
-    ephemeral_signer: Vec<u8>, // Simplified - would be proper key
+    ephemeral_signer: Arc<EphemeralSigner>,
     account: Option<Arc<dyn MultiChainSmartAccount>>,
     is_built: bool,
     _state: PhantomData<State>,
@@ -29,14 +30,11 @@ pub struct CrossChainBuilder<State = Building> {
 impl CrossChainBuilder<Building> {
     /// Create a new CrossChainBuilder
     pub fn new(network_env: &NetworkEnvironment) -> Self {
-        // Generate ephemeral signer (simplified)
-        let ephemeral_signer = vec![0u8; 32]; // Would use proper key generation
-
         Self {
             network_env: Arc::new(network_env.clone()),
             batches: Vec::new(),
             coordinator: VoucherCoordinator::new(),
-            ephemeral_signer,
+            ephemeral_signer: Arc::new(EphemeralSigner::random()),
             account: None,
             is_built: false,
             _state: PhantomData,
@@ -65,6 +63,13 @@ impl CrossChainBuilder<Building> {
     }
 }
 
+impl<State> CrossChainBuilder<State> {
+    /// Address of this session's ephemeral key
+    pub fn ephemeral_address(&self) -> Address {
+        self.ephemeral_signer.address()
+    }
+}
+
 impl CrossChainBuilder<ReadyToBuild> {
     /// Get the number of batches (for testing)
     /// Note: This is public for testing purposes only
@@ -73,16 +78,54 @@ impl CrossChainBuilder<ReadyToBuild> {
     }
 
     /// Start a new batch on the specified chain
-    pub fn start_batch(mut self, chain_id: ChainId) -> BatchBuilder {
+    pub fn start_batch(self, chain_id: ChainId) -> BatchBuilder {
         let batch_index = self.batches.len();
         BatchBuilder::new(
             chain_id,
             batch_index,
-            Arc::new(self.network_env.as_ref().clone()),
+            self.network_env.clone(),
             self,
         )
     }
 
+    /// Plan how to fund a spend on a destination chain from the account's balances
+    /// See [`crate::planner`].
+    pub async fn plan_funding(
+        &self,
+        request: &crate::planner::FundingRequest,
+        strategy: &dyn crate::planner::FundingStrategy,
+    ) -> Result<crate::planner::FundingPlan> {
+        let account = self
+            .account
+            .as_ref()
+            .ok_or(crate::EilError::AccountNotSet)?;
+        crate::planner::plan_funding(&self.network_env, account.as_ref(), request, strategy).await
+    }
+
+    /// Add a source batch per voucher of `plan` and start the destination batch using them
+    pub fn start_funded_batch(
+        mut self,
+        plan: &crate::planner::FundingPlan,
+    ) -> Result<BatchBuilder> {
+        for voucher in &plan.vouchers {
+            if self.coordinator.get(&voucher.ref_id).is_ok() {
+                return Err(crate::EilError::DuplicateVoucher(voucher.ref_id.clone()));
+            }
+        }
+        for (voucher, request) in plan.vouchers.iter().zip(plan.voucher_requests()) {
+            self = self
+                .start_batch(voucher.source_chain_id)
+                .add_voucher_request(request)
+                .end_batch();
+        }
+
+        let mut batch = self.start_batch(plan.destination_chain_id);
+        for voucher in &plan.vouchers {
+            batch = batch.use_voucher(&voucher.ref_id)?;
+        }
+        Ok(batch)
+    }
+
     /// Build all batches into SingleChainBatch objects
     pub async fn build_single_chain_batches(&mut self) -> Result<Vec<SingleChainBatch>> {
         self.assert_not_built()?;
@@ -90,20 +133,46 @@ impl CrossChainBuilder<ReadyToBuild> {
         // Collect XLPs for each voucher
         self.collect_xlps_per_voucher().await?;
 
-        // Build voucher requests
-        self.build_vouchers().await?;
-
         // Validate all vouchers are consumed
         self.coordinator.validate_all_consumed()?;
 
-        // Build each batch
-        let mut batches = Vec::new();
-        for batch_builder in &self.batches {
-            let single_chain_batch = batch_builder.build_single_chain_batch().await?;
-            batches.push(single_chain_batch);
+        let account = self
+            .account
+            .clone()
+            .ok_or(crate::EilError::AccountNotSet)?;
+
+        // Destination batches are built before their sources: each voucher request prices
+        // its destination UserOperation, and the source UserOperation creates the request
+        let mut batches: Vec<Option<SingleChainBatch>> =
+            self.batches.iter().map(|_| None).collect();
+        while batches.iter().any(Option::is_none) {
+            let ready = (0..self.batches.len())
+                .find(|&index| {
+                    batches[index].is_none()
+                        && self.batches[index].output_vouchers.iter().all(|voucher| {
+                            self.coordinator
+                                .get(&voucher.ref_id)
+                                .ok()
+                                .and_then(|info| info.dest_batch_index)
+                                .is_some_and(|dest| batches[dest].is_some())
+                        })
+                })
+                .ok_or_else(|| {
+                    crate::EilError::Generic("Circular voucher dependency between batches".into())
+                })?;
+
+            let mut voucher_calls = Vec::new();
+            for voucher in self.batches[ready].output_vouchers.clone() {
+                let request = self.build_voucher_request(&voucher, &batches).await?;
+                voucher_calls.extend(crate::voucher::encode_voucher_request_calls(&request));
+            }
+            let single_chain_batch = self.batches[ready]
+                .build_single_chain_batch(account.as_ref(), &self.ephemeral_signer, voucher_calls)
+                .await?;
+            batches[ready] = Some(single_chain_batch);
         }
 
-        Ok(batches)
+        Ok(batches.into_iter().flatten().collect())
     }
 
     /// Build and sign all UserOperations
@@ -131,9 +200,47 @@ impl CrossChainBuilder<ReadyToBuild> {
         self.is_built = true;
 
         Ok(crate::executor::CrossChainExecutor::new(
-            Arc::new(self.network_env.as_ref().clone()),
+            self.network_env.clone(),
+            account.clone(),
             signed_batches,
-        ))
+        )
+        .with_voucher_requests(self.voucher_requests())
+        .with_ephemeral_signer(self.ephemeral_signer.clone()))
+    }
+
+    /// Dry-run every batch before the user signs
+    /// See [`crate::simulation`] for how destination batches are modelled.
+    pub async fn simulate(&mut self) -> Result<Vec<crate::simulation::BatchSimulation>> {
+        let batches = self.build_single_chain_batches().await?;
+        crate::simulation::simulate_batches(&self.network_env, &batches, &self.voucher_requests())
+            .await
+    }
+
+    /// Preview the fee of every voucher request, `elapsed_seconds` after it is created
+    pub fn quote_voucher_fees(
+        &self,
+        elapsed_seconds: u64,
+    ) -> Result<Vec<crate::fees::VoucherFeeQuote>> {
+        let fee_rule = AtomicSwapFeeRule::from_config(&self.network_env.config().fee_config)?;
+        Ok(self
+            .coordinator
+            .all_vouchers()
+            .iter()
+            .map(|v| crate::fees::quote_voucher_fee(&v.voucher, &fee_rule, elapsed_seconds))
+            .collect())
+    }
+
+    /// Built voucher requests by ref ID
+    fn voucher_requests(&self) -> HashMap<String, VoucherRequest> {
+        self.coordinator
+            .all_vouchers()
+            .iter()
+            .filter_map(|v| {
+                v.voucher_request
+                    .clone()
+                    .map(|request| (v.voucher.ref_id.clone(), request))
+            })
+            .collect()
     }
 
     fn assert_not_built(&self) -> Result<()> {
@@ -161,32 +268,57 @@ impl CrossChainBuilder<ReadyToBuild> {
         Ok(())
     }
 
-    async fn get_allowed_xlps(&self, _voucher: &SdkVoucherRequest) -> Result<Vec<Address>> {
-        // Placeholder - would query XLPs from paymaster contract
-        // and filter based on XlpSelectionConfig
-        Ok(vec![])
-    }
+    async fn get_allowed_xlps(&self, voucher: &SdkVoucherRequest) -> Result<Vec<Address>> {
+        let dest_chain = voucher.destination_chain_id;
+        let paymaster = self.network_env.paymaster(dest_chain)?;
+        let config = &self.network_env.config().xlp_selection_config;
+
+        let xlps = crate::voucher::get_solvent_xlps(
+            &self.network_env,
+            dest_chain,
+            paymaster,
+            &voucher.tokens,
+            config.include_balance,
+        )
+        .await?;
 
-    async fn build_vouchers(&mut self) -> Result<()> {
-        // Collect all vouchers to avoid borrow checker issues
-        let vouchers: Vec<SdkVoucherRequest> = self
-            .coordinator
-            .all_vouchers()
-            .iter()
-            .map(|v| v.voucher.clone())
-            .collect();
+        crate::voucher::select_xlps(dest_chain, &xlps, &voucher.tokens, config)
+    }
 
-        for voucher in vouchers {
-            let voucher_request = self.sdk_to_voucher_request(&voucher).await?;
-            self.coordinator
-                .set_voucher_request(&voucher.ref_id, voucher_request)?;
-        }
-        Ok(())
+    /// Build the voucher request of `voucher`, pricing its already built destination batch
+    async fn build_voucher_request(
+        &mut self,
+        voucher: &SdkVoucherRequest,
+        batches: &[Option<SingleChainBatch>],
+    ) -> Result<VoucherRequest> {
+        let dest_batch_index = self.coordinator.get(&voucher.ref_id)?.dest_batch_index;
+        let dest_batch = dest_batch_index
+            .and_then(|index| batches.get(index)?.as_ref())
+            .ok_or_else(|| {
+                crate::EilError::VoucherNotConsumed(
+                    voucher.ref_id.clone(),
+                    voucher.source_chain_id.unwrap_or(0),
+                )
+            })?;
+        let max_user_op_cost = crate::gas::max_user_op_cost(
+            &self.network_env,
+            &dest_batch.user_op,
+            &dest_batch.gas_estimate,
+            &self.network_env.config().gas_config,
+        )
+        .await?;
+        let voucher_request = self
+            .sdk_to_voucher_request(voucher, max_user_op_cost)
+            .await?;
+        self.coordinator
+            .set_voucher_request(&voucher.ref_id, voucher_request.clone())?;
+        Ok(voucher_request)
     }
 
     async fn sdk_to_voucher_request(
         &self,
         sdk_request: &SdkVoucherRequest,
+        max_user_op_cost: U256,
     ) -> Result<VoucherRequest> {
         let account = self
             .account
@@ -217,16 +349,9 @@ impl CrossChainBuilder<ReadyToBuild> {
                         chain_id: source_chain,
                         address: format!("Token {} not deployed", t.token.name),
                     })?;
-                let amount = match &t.amount {
-                    Amount::Fixed(a) => *a,
-                    Amount::Runtime(_) => {
-                        t.min_provider_deposit
-                            .unwrap_or(U256::from(1))
-                    }
-                };
                 Ok(Asset {
                     erc20_token: token_addr,
-                    amount,
+                    amount: t.voucher_amount(),
                 })
             })
             .collect();
@@ -244,36 +369,15 @@ impl CrossChainBuilder<ReadyToBuild> {
                         chain_id: dest_chain,
                         address: format!("Token {} not deployed", t.token.name),
                     })?;
-                let amount = match &t.amount {
-                    Amount::Fixed(a) => *a,
-                    Amount::Runtime(_) => {
-                        t.min_provider_deposit
-                            .unwrap_or(U256::from(1))
-                    }
-                };
                 Ok(Asset {
                     erc20_token: token_addr,
-                    amount,
+                    amount: t.voucher_amount(),
                 })
             })
             .collect();
 
         // Create fee rule from config
-        let fee_config = &self.network_env.config().fee_config;
-        let fee_rule = AtomicSwapFeeRule {
-            start_fee_percent_numerator: crate::utils::fee_percent_to_numerator(
-                fee_config.start_fee_percent,
-            ),
-            max_fee_percent_numerator: crate::utils::fee_percent_to_numerator(
-                fee_config.max_fee_percent,
-            ),
-            fee_increase_per_second: crate::utils::fee_percent_to_numerator(
-                fee_config.fee_increase_per_second,
-            ),
-            unspent_voucher_fee: crate::utils::fee_percent_to_numerator(
-                fee_config.unspent_voucher_fee_percent,
-            ),
-        };
+        let fee_rule = AtomicSwapFeeRule::from_config(&self.network_env.config().fee_config)?;
 
         // Get voucher info to access allowed XLPs
         let voucher_info = self.coordinator.get(&sdk_request.ref_id)?;
@@ -294,7 +398,7 @@ impl CrossChainBuilder<ReadyToBuild> {
                 sender: dest_sender,
                 paymaster: dest_paymaster,
                 assets: dest_assets?,
-                max_user_op_cost: U256::from(10_000_000_000_000_000u64), // 0.01 ETH
+                max_user_op_cost,
                 expires_at: U256::from(
                     crate::utils::now_seconds() + self.network_env.config().expire_time_seconds,
                 ),
@@ -321,8 +425,9 @@ pub struct BatchBuilder {
     input_vouchers: Vec<SdkVoucherRequest>,
     output_vouchers: Vec<SdkVoucherRequest>,
     vars: HashSet<String>,
-    user_op_overrides: Option<UserOperation>,
+    paymaster_override: Option<crate::config::PaymasterData>,
     network_env: Arc<NetworkEnvironment>,
+    account: Option<Arc<dyn MultiChainSmartAccount>>,
     parent_builder: Option<CrossChainBuilder<ReadyToBuild>>,
 }
 
@@ -340,8 +445,9 @@ impl BatchBuilder {
             input_vouchers: Vec::new(),
             output_vouchers: Vec::new(),
             vars: HashSet::new(),
-            user_op_overrides: None,
+            paymaster_override: None,
             network_env,
+            account: parent.account.clone(),
             parent_builder: Some(parent),
         }
     }
@@ -351,6 +457,31 @@ impl BatchBuilder {
         self.chain_id
     }
 
+    /// Get the network environment
+    pub fn network_env(&self) -> &NetworkEnvironment {
+        &self.network_env
+    }
+
+    /// Get the account executing this batch
+    pub fn account(&self) -> Result<&dyn MultiChainSmartAccount> {
+        self.account
+            .as_deref()
+            .ok_or(crate::EilError::AccountNotSet)
+    }
+
+    /// Check whether a runtime variable has been set by an earlier action in this batch
+    pub fn is_var_set(&self, name: &str) -> bool {
+        self.vars.contains(name)
+    }
+
+    /// Fail unless a runtime variable has been set by an earlier action in this batch
+    pub fn require_var(&self, name: &str) -> Result<()> {
+        if !self.is_var_set(name) {
+            return Err(crate::EilError::RuntimeVarNotSet(name.to_string()));
+        }
+        Ok(())
+    }
+
     /// Add an action to this batch
     pub fn add_action(mut self, action: impl Action + 'static) -> Self {
         self.actions.push(Box::new(action));
@@ -381,6 +512,13 @@ impl BatchBuilder {
         Ok(self)
     }
 
+    /// Use a custom paymaster for this batch
+    /// Batches consuming vouchers are always paid by the CrossChainPaymaster.
+    pub fn override_paymaster(mut self, paymaster: crate::config::PaymasterData) -> Self {
+        self.paymaster_override = Some(paymaster);
+        self
+    }
+
     /// End this batch and return to parent builder
     pub fn end_batch(mut self) -> CrossChainBuilder<ReadyToBuild> {
         let mut parent = self.parent_builder.take().unwrap();
@@ -398,50 +536,123 @@ impl BatchBuilder {
     }
 
     /// Build this batch into a SingleChainBatch
-    async fn build_single_chain_batch(&self) -> Result<SingleChainBatch> {
-        let user_op = self.create_user_op().await?;
-        let user_op_hash = compute_user_op_hash(&user_op)?;
+    /// `voucher_calls` create the batch's voucher requests after its actions.
+    async fn build_single_chain_batch(
+        &mut self,
+        account: &dyn MultiChainSmartAccount,
+        ephemeral_signer: &EphemeralSigner,
+        voucher_calls: Vec<Call>,
+    ) -> Result<SingleChainBatch> {
+        if !self.input_vouchers.is_empty() && self.paymaster_override.is_some() {
+            return Err(crate::EilError::CannotOverridePaymaster);
+        }
+
+        let mut user_op = self.create_user_op(account, voucher_calls).await?;
+        if !self.input_vouchers.is_empty() {
+            // Commit to the ephemeral key, which signs the vouchers once they are issued
+            let gas_config = &self.network_env.config().gas_config;
+            user_op.paymaster = Some(self.network_env.paymaster(self.chain_id)?);
+            user_op.paymaster_verification_gas_limit =
+                Some(U256::from(gas_config.paymaster_verification_gas_limit));
+            user_op.paymaster_post_op_gas_limit =
+                Some(U256::from(gas_config.paymaster_post_op_gas_limit));
+            user_op.paymaster_data = Some(ephemeral_signer.commitment());
+        }
+        let mut gas_estimate = crate::gas::estimate_user_op_gas(
+            &self.network_env,
+            &user_op,
+            &self.network_env.config().gas_config,
+        )
+        .await?;
+        gas_estimate.apply_to(&mut user_op);
+        if user_op.paymaster.is_some() {
+            // Priced into max_user_op_cost of the vouchers
+            gas_estimate.paymaster_verification_gas_limit = user_op.paymaster_verification_gas_limit;
+            gas_estimate.paymaster_post_op_gas_limit = user_op.paymaster_post_op_gas_limit;
+        }
+        let user_op_hash = Hex::from(crate::user_op::compute_user_op_hash(&user_op)?.to_vec());
 
         Ok(SingleChainBatch {
             user_op,
             user_op_hash,
             chain_id: self.chain_id,
+            gas_estimate,
             input_voucher_requests: self.input_vouchers.clone(),
             out_voucher_requests: self.output_vouchers.clone(),
+            custom_errors: self.custom_errors(),
         })
     }
 
-    async fn create_user_op(&self) -> Result<UserOperation> {
-        // Build calldata from actions
+    /// Custom errors declared in the ABIs of this batch's actions, deduplicated by selector
+    fn custom_errors(&self) -> Vec<alloy::json_abi::Error> {
+        let mut selectors = HashSet::new();
+        self.actions
+            .iter()
+            .filter_map(|action| action.abi())
+            .flat_map(|abi| abi.errors())
+            .filter(|error| selectors.insert(error.selector()))
+            .cloned()
+            .collect()
+    }
+
+    /// Encode actions in order, tracking the runtime variables each one sets
+    async fn encode_actions(&mut self, actions: &[Box<dyn Action>]) -> Result<Vec<Call>> {
+        self.vars.clear();
         let mut calls = Vec::new();
-        for action in &self.actions {
+        for action in actions {
             calls.extend(action.encode_call(self).await?);
+            if let Some(name) = action.defined_var() {
+                self.vars.insert(name.to_string());
+            }
         }
+        Ok(calls)
+    }
 
-        // Encode calls
-        // let call_data = if calls.is_empty() {
-        //     Hex::new()
-        // } else {
-        //     // Would use account.encode_calls()
-        //     Hex::new()
-        // };
+    async fn create_user_op(
+        &mut self,
+        account: &dyn MultiChainSmartAccount,
+        voucher_calls: Vec<Call>,
+    ) -> Result<UserOperation> {
+        // Build calldata from actions
+        let actions = std::mem::take(&mut self.actions);
+        let calls = self.encode_actions(&actions).await;
+        self.actions = actions;
+        let mut calls = calls?;
+        calls.extend(voucher_calls);
+        let call_data = if self.vars.is_empty() {
+            account.encode_calls(self.chain_id, calls).await?
+        } else {
+            // Run through the helper in the account's context, so calls are made by the account
+            let helper = self.network_env.runtime_vars_helper(self.chain_id)?;
+            let data = crate::runtime_vars::encode_execute_batch(&calls);
+            account
+                .encode_delegate_call(self.chain_id, helper, data)
+                .await?
+        };
+
+        let sender = account.address_on(self.chain_id)?;
+        let nonce = account.get_nonce(self.chain_id).await?;
+        let (factory, factory_data) = account.get_factory_args(self.chain_id).await?;
+
+        let paymaster = self.paymaster_override.clone().unwrap_or_default();
 
         // Create UserOperation
         Ok(UserOperation {
-            sender: Address::ZERO, // Would get from account
-            nonce: U256::from(0),
-            factory: None,
-            factory_data: None,
-            call_data: Hex::new(),
-            call_gas_limit: U256::from(3_000_000),
-            verification_gas_limit: U256::from(500_000),
-            pre_verification_gas: U256::from(100_000),
-            max_fee_per_gas: U256::from(1_000_000_000), // 1 gwei
-            max_priority_fee_per_gas: U256::from(1_000_000_000),
-            paymaster: None,
-            paymaster_verification_gas_limit: None,
-            paymaster_post_op_gas_limit: None,
-            paymaster_data: None,
+            sender,
+            nonce,
+            factory,
+            factory_data,
+            call_data,
+            // Gas limits and fees are estimated in build_single_chain_batch
+            call_gas_limit: U256::ZERO,
+            verification_gas_limit: U256::ZERO,
+            pre_verification_gas: U256::ZERO,
+            max_fee_per_gas: U256::ZERO,
+            max_priority_fee_per_gas: U256::ZERO,
+            paymaster: paymaster.paymaster,
+            paymaster_verification_gas_limit: paymaster.paymaster_verification_gas_limit,
+            paymaster_post_op_gas_limit: paymaster.paymaster_post_op_gas_limit,
+            paymaster_data: paymaster.paymaster_data,
             paymaster_signature: None,
             signature: Hex::new(),
             chain_id: Some(self.chain_id),
@@ -450,9 +661,3 @@ impl BatchBuilder {
     }
 }
 
-/// Compute UserOperation hash
-fn compute_user_op_hash(_user_op: &UserOperation) -> Result<Hex> {
-    // Simplified - would use proper EIP-712 encoding
-    let hash = keccak256(&[0u8; 32]);
-    Ok(Hex::from(hash.to_vec()))
-}
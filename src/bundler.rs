@@ -0,0 +1,603 @@
+//! ERC-4337 bundler JSON-RPC client
+//!
+//! Implements [`BundlerManager`] over HTTP using the bundler URL of each configured
+//! chain, falling back to the chain RPC URL when no bundler URL is set.
+
+use crate::{
+    account::BundlerManager, config::CrossChainConfig, contract_types::UserOperation, types::*,
+    Result,
+};
+use alloy::primitives::{B256, U256};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+/// Gas values returned by `eth_estimateUserOperationGas`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationGasEstimate {
+    /// Gas overhead for pre-verification
+    pub pre_verification_gas: U256,
+    /// Gas limit for the verification phase
+    pub verification_gas_limit: U256,
+    /// Gas limit for the execution phase
+    pub call_gas_limit: U256,
+    /// Paymaster verification gas limit (if a paymaster is used)
+    #[serde(default)]
+    pub paymaster_verification_gas_limit: Option<U256>,
+    /// Paymaster post-operation gas limit (if a paymaster is used)
+    #[serde(default)]
+    pub paymaster_post_op_gas_limit: Option<U256>,
+}
+
+/// Result of `eth_getUserOperationByHash`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationByHash {
+    /// The UserOperation
+    pub user_operation: UserOperation,
+    /// EntryPoint the UserOperation was sent to
+    pub entry_point: Address,
+    /// Block number of inclusion (if included)
+    #[serde(default)]
+    pub block_number: Option<U256>,
+    /// Block hash of inclusion (if included)
+    #[serde(default)]
+    pub block_hash: Option<B256>,
+    /// Transaction hash of inclusion (if included)
+    #[serde(default)]
+    pub transaction_hash: Option<B256>,
+}
+
+/// Transaction receipt embedded in a UserOperation receipt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationTxReceipt {
+    /// Transaction hash
+    pub transaction_hash: B256,
+    /// Block hash
+    #[serde(default)]
+    pub block_hash: Option<B256>,
+    /// Block number
+    #[serde(default)]
+    pub block_number: Option<U256>,
+}
+
+/// Result of `eth_getUserOperationReceipt`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationReceipt {
+    /// UserOperation hash
+    pub user_op_hash: B256,
+    /// EntryPoint the UserOperation was executed by
+    pub entry_point: Address,
+    /// Account sending the operation
+    pub sender: Address,
+    /// Anti-replay nonce
+    pub nonce: U256,
+    /// Paymaster that paid for the operation (if any)
+    #[serde(default)]
+    pub paymaster: Option<Address>,
+    /// Actual gas cost paid
+    pub actual_gas_cost: U256,
+    /// Actual gas used
+    pub actual_gas_used: U256,
+    /// Whether the execution phase succeeded
+    pub success: bool,
+    /// Revert data (if the execution phase reverted)
+    #[serde(default)]
+    pub reason: Option<Hex>,
+    /// Logs emitted by this UserOperation
+    #[serde(default)]
+    pub logs: Vec<alloy::rpc::types::eth::Log>,
+    /// Receipt of the bundle transaction
+    pub receipt: UserOperationTxReceipt,
+}
+
+/// JSON-RPC bundler client for all configured chains
+pub struct JsonRpcBundler {
+    client: reqwest::Client,
+    /// Bundler URL per chain
+    urls: HashMap<ChainId, String>,
+    /// Whether each chain has a bundler, known up front or once probed
+    available: Mutex<HashMap<ChainId, bool>>,
+    next_id: AtomicU64,
+}
+
+impl JsonRpcBundler {
+    /// Create a bundler client from the chain configuration
+    /// Uses `ChainInfo.bundler_url`, falling back to `rpc_url` when unset
+    pub fn new(config: &CrossChainConfig) -> Self {
+        let urls = config
+            .chain_infos
+            .iter()
+            .map(|info| (info.chain_id, info.bundler_url_or_rpc().to_string()))
+            .collect();
+        let available = config
+            .chain_infos
+            .iter()
+            .filter(|info| info.bundler_url.is_some())
+            .map(|info| (info.chain_id, true))
+            .collect();
+
+        Self {
+            client: reqwest::Client::new(),
+            urls,
+            available: Mutex::new(available),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Get the bundler URL for a chain
+    pub fn bundler_url(&self, chain_id: ChainId) -> Result<&str> {
+        self.urls
+            .get(&chain_id)
+            .map(|s| s.as_str())
+            .ok_or(crate::EilError::UnsupportedChain(chain_id))
+    }
+
+    /// Whether a bundler serves the chain
+    ///
+    /// True if a bundler URL is configured. Otherwise the chain RPC URL is probed once
+    /// with `eth_supportedEntryPoints`: any JSON-RPC error or HTTP client error means a
+    /// plain node, as nodes reject unknown methods in different ways. Only transport
+    /// errors are returned.
+    pub async fn is_available(&self, chain_id: ChainId) -> Result<bool> {
+        if let Some(available) = self.available.lock().unwrap().get(&chain_id) {
+            return Ok(*available);
+        }
+
+        let available = match self.supported_entry_points(chain_id).await {
+            Ok(_) => true,
+            Err(e @ crate::EilError::BundlerRequest(_)) => return Err(e),
+            Err(_) => false,
+        };
+        self.available.lock().unwrap().insert(chain_id, available);
+        Ok(available)
+    }
+
+    /// Send a UserOperation (`eth_sendUserOperation`), returning its userOpHash
+    pub async fn send_user_operation_on(
+        &self,
+        chain_id: ChainId,
+        user_op: &UserOperation,
+        entry_point: Address,
+    ) -> Result<Hex> {
+        let params = serde_json::json!([user_op_to_rpc(user_op)?, entry_point]);
+        self.request(chain_id, "eth_sendUserOperation", params)
+            .await
+    }
+
+    /// Estimate UserOperation gas limits (`eth_estimateUserOperationGas`)
+    pub async fn estimate_user_operation_gas(
+        &self,
+        chain_id: ChainId,
+        user_op: &UserOperation,
+        entry_point: Address,
+    ) -> Result<UserOperationGasEstimate> {
+        let params = serde_json::json!([user_op_to_rpc(user_op)?, entry_point]);
+        self.request(chain_id, "eth_estimateUserOperationGas", params)
+            .await
+    }
+
+    /// Get a UserOperation by its hash (`eth_getUserOperationByHash`)
+    pub async fn get_user_operation_by_hash(
+        &self,
+        chain_id: ChainId,
+        user_op_hash: B256,
+    ) -> Result<Option<UserOperationByHash>> {
+        self.request(
+            chain_id,
+            "eth_getUserOperationByHash",
+            serde_json::json!([user_op_hash]),
+        )
+        .await
+    }
+
+    /// Get a UserOperation receipt (`eth_getUserOperationReceipt`)
+    /// Returns `None` while the UserOperation is not yet included
+    pub async fn get_user_operation_receipt(
+        &self,
+        chain_id: ChainId,
+        user_op_hash: B256,
+    ) -> Result<Option<UserOperationReceipt>> {
+        self.request(
+            chain_id,
+            "eth_getUserOperationReceipt",
+            serde_json::json!([user_op_hash]),
+        )
+        .await
+    }
+
+    /// Get the EntryPoints supported by the bundler (`eth_supportedEntryPoints`)
+    pub async fn supported_entry_points(&self, chain_id: ChainId) -> Result<Vec<Address>> {
+        self.request(chain_id, "eth_supportedEntryPoints", serde_json::json!([]))
+            .await
+    }
+
+    async fn request<T: DeserializeOwned>(
+        &self,
+        chain_id: ChainId,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<T> {
+        let url = self.bundler_url(chain_id)?;
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": self.next_id.fetch_add(1, Ordering::Relaxed),
+            "method": method,
+            "params": params,
+        });
+
+        let response = self
+            .client
+            .post(url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| crate::EilError::BundlerRequest(format!("{}: {}", method, e)))?;
+        let status = response.status();
+        let response: JsonRpcResponse = match response.json().await {
+            Ok(response) => response,
+            Err(_) if status.is_client_error() => {
+                return Err(crate::EilError::BundlerHttpStatus {
+                    status: status.as_u16(),
+                    method: method.to_string(),
+                })
+            }
+            Err(e) => {
+                return Err(crate::EilError::BundlerRequest(format!(
+                    "{}: {}",
+                    method, e
+                )))
+            }
+        };
+
+        if let Some(error) = response.error {
+            return Err(bundler_error(error.code, error.message));
+        }
+        Ok(serde_json::from_value(
+            response.result.unwrap_or(serde_json::Value::Null),
+        )?)
+    }
+}
+
+#[async_trait]
+impl BundlerManager for JsonRpcBundler {
+    async fn send_user_operation(
+        &self,
+        user_op: &UserOperation,
+        entry_point: Address,
+    ) -> Result<Hex> {
+        let chain_id = user_op
+            .chain_id
+            .ok_or_else(|| crate::EilError::Generic("UserOperation chain ID not set".into()))?;
+        self.send_user_operation_on(chain_id, user_op, entry_point)
+            .await
+    }
+
+    async fn verify_entry_point(&self, chain_id: ChainId, entry_point: Address) -> Result<()> {
+        let supported = self.supported_entry_points(chain_id).await?;
+        if !supported.contains(&entry_point) {
+            return Err(crate::EilError::UnsupportedEntryPoint {
+                chain_id,
+                entry_point: entry_point.to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse {
+    #[serde(default)]
+    result: Option<serde_json::Value>,
+    #[serde(default)]
+    error: Option<JsonRpcError>,
+}
+
+/// Map an ERC-4337 bundler JSON-RPC error to a typed error
+pub fn bundler_error(code: i64, message: String) -> crate::EilError {
+    match code {
+        -32602 => crate::EilError::BundlerInvalidFields(message),
+        -32500 => crate::EilError::BundlerAccountRejected(message),
+        -32501 => crate::EilError::BundlerPaymasterRejected(message),
+        -32502 => crate::EilError::BundlerOpcodeViolation(message),
+        -32503 => crate::EilError::BundlerOutOfTimeRange(message),
+        -32504 => crate::EilError::BundlerEntityThrottled(message),
+        -32505 => crate::EilError::BundlerInsufficientStake(message),
+        -32507 => crate::EilError::BundlerInvalidSignature(message),
+        code => crate::EilError::BundlerRpc { code, message },
+    }
+}
+
+/// Whether a bundler rejected a UserOperation because it already has it in its mempool
+pub fn is_already_known(error: &crate::EilError) -> bool {
+    let message = match error {
+        crate::EilError::BundlerInvalidFields(message)
+        | crate::EilError::BundlerRpc { message, .. } => message.to_lowercase(),
+        _ => return false,
+    };
+    message.contains("already known") || message.contains("already in mempool")
+}
+
+/// Convert a UserOperation to its ERC-4337 v0.7 JSON-RPC representation
+/// SDK-only fields (chain ID, EntryPoint address, paymaster signature) are dropped
+pub fn user_op_to_rpc(user_op: &UserOperation) -> Result<serde_json::Value> {
+    let mut value = serde_json::to_value(user_op)?;
+    if let Some(fields) = value.as_object_mut() {
+        fields.remove("chainId");
+        fields.remove("entryPointAddress");
+        fields.remove("paymasterSignature");
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{create_test_config, create_test_user_op, MockRpcServer};
+
+    const ENTRY_POINT: &str = "0x0000000071727De22E5E9d8BAf0edAc6f37da032";
+
+    fn create_bundler(url: &str) -> JsonRpcBundler {
+        let mut config = create_test_config(vec![1]);
+        config.chain_infos[0].bundler_url = Some(url.to_string());
+        JsonRpcBundler::new(&config)
+    }
+
+    #[test]
+    fn test_bundler_url_falls_back_to_rpc() {
+        let mut config = create_test_config(vec![1, 10]);
+        config.chain_infos[1].bundler_url = Some("https://bundler.example.com".to_string());
+        let bundler = JsonRpcBundler::new(&config);
+
+        assert_eq!(
+            bundler.bundler_url(1).unwrap(),
+            "https://test-rpc-1.example.com"
+        );
+        assert_eq!(
+            bundler.bundler_url(10).unwrap(),
+            "https://bundler.example.com"
+        );
+        assert!(bundler.bundler_url(999).is_err());
+    }
+
+    #[test]
+    fn test_user_op_to_rpc_drops_sdk_fields() {
+        let value = user_op_to_rpc(&create_test_user_op(1)).unwrap();
+
+        assert!(value.get("chainId").is_none());
+        assert!(value.get("entryPointAddress").is_none());
+        assert_eq!(value["callData"], format!("0x{}", "00".repeat(32)));
+        assert_eq!(value["nonce"], "0x0");
+    }
+
+    #[test]
+    fn test_bundler_error_mapping() {
+        assert!(matches!(
+            bundler_error(-32500, "AA21".into()),
+            crate::EilError::BundlerAccountRejected(_)
+        ));
+        assert!(matches!(
+            bundler_error(-32501, "AA31".into()),
+            crate::EilError::BundlerPaymasterRejected(_)
+        ));
+        assert!(matches!(
+            bundler_error(-32507, "bad sig".into()),
+            crate::EilError::BundlerInvalidSignature(_)
+        ));
+        assert!(matches!(
+            bundler_error(-32000, "other".into()),
+            crate::EilError::BundlerRpc { code: -32000, .. }
+        ));
+    }
+
+    #[test]
+    fn test_is_already_known() {
+        assert!(is_already_known(&bundler_error(
+            -32602,
+            "UserOperation Already Known".into()
+        )));
+        assert!(is_already_known(&bundler_error(
+            -32000,
+            "already in mempool".into()
+        )));
+        assert!(!is_already_known(&bundler_error(
+            -32500,
+            "AA25 invalid nonce".into()
+        )));
+        assert!(!is_already_known(&crate::EilError::BundlerRequest(
+            "already known".into()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_send_user_operation() {
+        let hash = format!("0x{}", "ab".repeat(32));
+        let expected = hash.clone();
+        let server = MockRpcServer::start(move |method, params| {
+            assert_eq!(method, "eth_sendUserOperation");
+            assert_eq!(params[1], ENTRY_POINT.to_lowercase());
+            Ok(serde_json::json!(expected))
+        })
+        .await;
+        let bundler = create_bundler(&server.url);
+
+        let result = bundler
+            .send_user_operation(&create_test_user_op(1), ENTRY_POINT.parse().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(result, hash.parse::<Hex>().unwrap());
+        assert_eq!(server.methods(), vec!["eth_sendUserOperation"]);
+    }
+
+    #[tokio::test]
+    async fn test_send_user_operation_error() {
+        let server = MockRpcServer::start(|_, _| Err((-32500, "AA23 reverted".to_string()))).await;
+        let bundler = create_bundler(&server.url);
+
+        let result = bundler
+            .send_user_operation(&create_test_user_op(1), ENTRY_POINT.parse().unwrap())
+            .await;
+
+        match result {
+            Err(crate::EilError::BundlerAccountRejected(message)) => {
+                assert_eq!(message, "AA23 reverted");
+            }
+            other => panic!("Expected BundlerAccountRejected error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_estimate_user_operation_gas() {
+        let server = MockRpcServer::start(|_, _| {
+            Ok(serde_json::json!({
+                "preVerificationGas": "0xc350",
+                "verificationGasLimit": "0x186a0",
+                "callGasLimit": "0x30d40",
+            }))
+        })
+        .await;
+        let bundler = create_bundler(&server.url);
+
+        let estimate = bundler
+            .estimate_user_operation_gas(1, &create_test_user_op(1), ENTRY_POINT.parse().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(estimate.pre_verification_gas, U256::from(50_000));
+        assert_eq!(estimate.verification_gas_limit, U256::from(100_000));
+        assert_eq!(estimate.call_gas_limit, U256::from(200_000));
+        assert!(estimate.paymaster_verification_gas_limit.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_user_operation_receipt() {
+        let user_op_hash = B256::repeat_byte(0xab);
+        let server = MockRpcServer::start(move |_, params| {
+            if params[0] == serde_json::json!(B256::repeat_byte(0xcd)) {
+                return Ok(serde_json::Value::Null);
+            }
+            Ok(serde_json::json!({
+                "userOpHash": B256::repeat_byte(0xab),
+                "entryPoint": ENTRY_POINT,
+                "sender": "0x2222222222222222222222222222222222222222",
+                "nonce": "0x1",
+                "actualGasCost": "0x5208",
+                "actualGasUsed": "0x5208",
+                "success": true,
+                "logs": [],
+                "receipt": { "transactionHash": B256::repeat_byte(0x01) },
+            }))
+        })
+        .await;
+        let bundler = create_bundler(&server.url);
+
+        let receipt = bundler
+            .get_user_operation_receipt(1, user_op_hash)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(receipt.success);
+        assert_eq!(receipt.receipt.transaction_hash, B256::repeat_byte(0x01));
+
+        let pending = bundler
+            .get_user_operation_receipt(1, B256::repeat_byte(0xcd))
+            .await
+            .unwrap();
+        assert!(pending.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_verify_entry_point() {
+        let server = MockRpcServer::start(|method, _| {
+            assert_eq!(method, "eth_supportedEntryPoints");
+            Ok(serde_json::json!([ENTRY_POINT]))
+        })
+        .await;
+        let bundler = create_bundler(&server.url);
+
+        assert!(bundler
+            .verify_entry_point(1, ENTRY_POINT.parse().unwrap())
+            .await
+            .is_ok());
+
+        let result = bundler.verify_entry_point(1, Address::ZERO).await;
+        assert!(matches!(
+            result,
+            Err(crate::EilError::UnsupportedEntryPoint { chain_id: 1, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_is_available() {
+        // A configured bundler URL is used without probing
+        let server = MockRpcServer::start(|_, _| Ok(serde_json::json!([ENTRY_POINT]))).await;
+        let bundler = create_bundler(&server.url);
+        assert!(bundler.is_available(1).await.unwrap());
+        assert!(server.requests.lock().unwrap().is_empty());
+
+        // Without one, the RPC URL is probed once
+        let node = MockRpcServer::start(|_, _| Err((-32601, "method not found".to_string()))).await;
+        let mut config = create_test_config(vec![1]);
+        config.chain_infos[0].rpc_url = node.url.clone();
+        let bundler = JsonRpcBundler::new(&config);
+        assert!(!bundler.is_available(1).await.unwrap());
+        assert!(!bundler.is_available(1).await.unwrap());
+        assert_eq!(node.requests.lock().unwrap().len(), 1);
+
+        let server = MockRpcServer::start(|_, _| Ok(serde_json::json!([ENTRY_POINT]))).await;
+        config.chain_infos[0].rpc_url = server.url.clone();
+        assert!(JsonRpcBundler::new(&config).is_available(1).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_is_available_on_node_errors() {
+        // Nodes reject the method with different JSON-RPC errors
+        for (code, message) in [
+            (-32600, "invalid request"),
+            (-32000, "method not supported"),
+        ] {
+            let node = MockRpcServer::start(move |_, _| Err((code, message.to_string()))).await;
+            let mut config = create_test_config(vec![1]);
+            config.chain_infos[0].rpc_url = node.url.clone();
+            assert!(!JsonRpcBundler::new(&config).is_available(1).await.unwrap());
+        }
+
+        // Or with an HTTP client error
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = create_test_config(vec![1]);
+        config.chain_infos[0].rpc_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf).await;
+            let _ = stream
+                .write_all(b"HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                .await;
+        });
+        assert!(!JsonRpcBundler::new(&config).is_available(1).await.unwrap());
+
+        // Transport errors are returned
+        config.chain_infos[0].rpc_url = "http://127.0.0.1:1".to_string();
+        assert!(matches!(
+            JsonRpcBundler::new(&config).is_available(1).await,
+            Err(crate::EilError::BundlerRequest(_))
+        ));
+    }
+}
@@ -14,6 +14,42 @@ pub struct ChainInfo {
     pub paymaster: Address,
     /// Optional bundler URL (if different from RPC)
     pub bundler_url: Option<String>,
+    /// RuntimeVarsHelper contract address (required for runtime variables)
+    #[serde(default)]
+    pub runtime_vars_helper: Option<Address>,
+    /// Rollup stack, used to price the L1 data fee of UserOperations
+    #[serde(default)]
+    pub l2_kind: L2Kind,
+    /// Deployed bytecode of `EntryPointSimulations`, overridden at the EntryPoint
+    /// address when simulating batches
+    #[serde(default)]
+    pub entry_point_simulations: Option<Hex>,
+    /// Multicall3 contract address, used to batch balance reads
+    #[serde(default)]
+    pub multicall3: Option<Address>,
+    /// Uniswap Permit2 contract address (required for Permit2 actions)
+    #[serde(default)]
+    pub permit2: Option<Address>,
+}
+
+/// Rollup stack of a chain
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum L2Kind {
+    /// L1 or a chain without a separate data fee
+    #[default]
+    L1,
+    /// OP Stack chain, data fee from the `GasPriceOracle` predeploy
+    OpStack,
+    /// Arbitrum chain, data fee from the `NodeInterface` precompile
+    Arbitrum,
+}
+
+impl ChainInfo {
+    /// Get the bundler URL, falling back to the RPC URL when unset
+    pub fn bundler_url_or_rpc(&self) -> &str {
+        self.bundler_url.as_deref().unwrap_or(&self.rpc_url)
+    }
 }
 
 /// XLP (Cross-chain Liquidity Provider) selection configuration
@@ -122,6 +158,116 @@ fn default_unspent_voucher_fee_percent() -> f64 {
     0.001
 }
 
+/// Gas estimation and fee pricing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasConfig {
+    /// Number of blocks to sample with `eth_feeHistory`
+    #[serde(default = "default_fee_history_blocks")]
+    pub fee_history_blocks: u64,
+
+    /// Priority fee reward percentile (0.0 to 100.0)
+    #[serde(default = "default_reward_percentile")]
+    pub reward_percentile: f64,
+
+    /// Multiplier applied to the next block base fee
+    #[serde(default = "default_base_fee_multiplier")]
+    pub base_fee_multiplier: f64,
+
+    /// Multiplier applied to the sampled priority fee
+    #[serde(default = "default_priority_fee_multiplier")]
+    pub priority_fee_multiplier: f64,
+
+    /// Multiplier applied to estimated gas limits
+    #[serde(default = "default_gas_limit_multiplier")]
+    pub gas_limit_multiplier: f64,
+
+    /// Verification gas limit used when no bundler estimate is available
+    #[serde(default = "default_verification_gas_overhead")]
+    pub verification_gas_overhead: u64,
+
+    /// Extra verification gas when the account is deployed by the UserOperation
+    #[serde(default = "default_deployment_gas_overhead")]
+    pub deployment_gas_overhead: u64,
+
+    /// Pre-verification gas used when no bundler estimate is available
+    #[serde(default = "default_pre_verification_gas_overhead")]
+    pub pre_verification_gas_overhead: u64,
+
+    /// Safety margin applied to the destination `max_user_op_cost` of vouchers
+    #[serde(default = "default_user_op_cost_margin")]
+    pub user_op_cost_margin: f64,
+
+    /// CrossChainPaymaster verification gas limit used when no bundler estimate is available
+    #[serde(default = "default_paymaster_verification_gas_limit")]
+    pub paymaster_verification_gas_limit: u64,
+
+    /// CrossChainPaymaster post-op gas limit used when no bundler estimate is available
+    #[serde(default = "default_paymaster_post_op_gas_limit")]
+    pub paymaster_post_op_gas_limit: u64,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            fee_history_blocks: default_fee_history_blocks(),
+            reward_percentile: default_reward_percentile(),
+            base_fee_multiplier: default_base_fee_multiplier(),
+            priority_fee_multiplier: default_priority_fee_multiplier(),
+            gas_limit_multiplier: default_gas_limit_multiplier(),
+            verification_gas_overhead: default_verification_gas_overhead(),
+            deployment_gas_overhead: default_deployment_gas_overhead(),
+            pre_verification_gas_overhead: default_pre_verification_gas_overhead(),
+            user_op_cost_margin: default_user_op_cost_margin(),
+            paymaster_verification_gas_limit: default_paymaster_verification_gas_limit(),
+            paymaster_post_op_gas_limit: default_paymaster_post_op_gas_limit(),
+        }
+    }
+}
+
+fn default_fee_history_blocks() -> u64 {
+    10
+}
+
+fn default_reward_percentile() -> f64 {
+    50.0
+}
+
+fn default_base_fee_multiplier() -> f64 {
+    2.0
+}
+
+fn default_priority_fee_multiplier() -> f64 {
+    1.2
+}
+
+fn default_gas_limit_multiplier() -> f64 {
+    1.2
+}
+
+fn default_verification_gas_overhead() -> u64 {
+    150_000
+}
+
+fn default_deployment_gas_overhead() -> u64 {
+    300_000
+}
+
+fn default_pre_verification_gas_overhead() -> u64 {
+    60_000
+}
+
+fn default_user_op_cost_margin() -> f64 {
+    1.2
+}
+
+fn default_paymaster_verification_gas_limit() -> u64 {
+    250_000
+}
+
+fn default_paymaster_post_op_gas_limit() -> u64 {
+    50_000
+}
+
 /// Source chain paymaster interface (for chains without vouchers)
 pub trait SourcePaymaster: Send + Sync {
     /// Get paymaster stub data for UserOp
@@ -132,7 +278,7 @@ pub trait SourcePaymaster: Send + Sync {
 }
 
 /// Paymaster data
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct PaymasterData {
     /// Paymaster address
     pub paymaster: Option<Address>,
@@ -155,6 +301,10 @@ pub struct CrossChainConfig {
     #[serde(default = "default_exec_timeout_seconds")]
     pub exec_timeout_seconds: u64,
 
+    /// Maximum number of batches executed at the same time on each chain
+    #[serde(default = "default_max_parallel_batches_per_chain")]
+    pub max_parallel_batches_per_chain: usize,
+
     /// XLP selection configuration
     #[serde(default)]
     pub xlp_selection_config: XlpSelectionConfig,
@@ -163,6 +313,10 @@ pub struct CrossChainConfig {
     #[serde(default)]
     pub fee_config: FeeConfig,
 
+    /// Gas estimation configuration
+    #[serde(default)]
+    pub gas_config: GasConfig,
+
     /// Per-chain configuration
     pub chain_infos: Vec<ChainInfo>,
 
@@ -176,8 +330,10 @@ impl Default for CrossChainConfig {
         Self {
             expire_time_seconds: default_expire_time_seconds(),
             exec_timeout_seconds: default_exec_timeout_seconds(),
+            max_parallel_batches_per_chain: default_max_parallel_batches_per_chain(),
             xlp_selection_config: XlpSelectionConfig::default(),
             fee_config: FeeConfig::default(),
+            gas_config: GasConfig::default(),
             chain_infos: Vec::new(),
             source_paymaster: None,
         }
@@ -192,6 +348,10 @@ fn default_exec_timeout_seconds() -> u64 {
     30
 }
 
+fn default_max_parallel_batches_per_chain() -> usize {
+    1
+}
+
 impl CrossChainConfig {
     /// Create a new configuration with the given chain infos
     pub fn new(chain_infos: Vec<ChainInfo>) -> Self {
@@ -219,6 +379,12 @@ impl CrossChainConfig {
         self
     }
 
+    /// Set gas estimation configuration
+    pub fn with_gas_config(mut self, config: GasConfig) -> Self {
+        self.gas_config = config;
+        self
+    }
+
     /// Set expiration time
     pub fn with_expire_time(mut self, seconds: u64) -> Self {
         self.expire_time_seconds = seconds;
@@ -231,6 +397,12 @@ impl CrossChainConfig {
         self
     }
 
+    /// Set the maximum number of batches executed at the same time on each chain
+    pub fn with_max_parallel_batches_per_chain(mut self, max: usize) -> Self {
+        self.max_parallel_batches_per_chain = max;
+        self
+    }
+
     /// Set source paymaster
     pub fn with_source_paymaster(
         mut self,
@@ -257,6 +429,11 @@ mod tests {
             entry_point: "0x0000000071727De22E5E9d8BAf0edAc6f37da032".parse().unwrap(),
             paymaster: "0x0000000000000000000000000000000000000001".parse().unwrap(),
             bundler_url: None,
+            runtime_vars_helper: None,
+            l2_kind: L2Kind::L1,
+            entry_point_simulations: None,
+            multicall3: None,
+            permit2: None,
         }
     }
 
@@ -264,7 +441,7 @@ mod tests {
     fn test_xlp_selection_config_defaults() {
         let config = XlpSelectionConfig::default();
         assert_eq!(config.deposit_reserve_factor, 1.0);
-        assert_eq!(config.include_balance, false);
+        assert!(!config.include_balance);
         assert_eq!(config.min_xlps, 1);
         assert_eq!(config.max_xlps, 5);
     }
@@ -278,11 +455,43 @@ mod tests {
         assert_eq!(config.unspent_voucher_fee_percent, 0.001);
     }
 
+    #[test]
+    fn test_gas_config_defaults() {
+        let config = GasConfig::default();
+        assert_eq!(config.fee_history_blocks, 10);
+        assert_eq!(config.reward_percentile, 50.0);
+        assert_eq!(config.base_fee_multiplier, 2.0);
+        assert_eq!(config.gas_limit_multiplier, 1.2);
+        assert_eq!(config.paymaster_verification_gas_limit, 250_000);
+        assert_eq!(config.paymaster_post_op_gas_limit, 50_000);
+    }
+
+    #[test]
+    fn test_gas_config_deserialize_partial() {
+        let config: GasConfig =
+            serde_json::from_str(r#"{"reward_percentile": 90.0}"#).unwrap();
+        assert_eq!(config.reward_percentile, 90.0);
+        assert_eq!(config.fee_history_blocks, 10);
+    }
+
+    #[test]
+    fn test_chain_info_l2_kind_deserialize() {
+        let mut json = serde_json::to_value(create_test_chain_info(10)).unwrap();
+        json["l2_kind"] = serde_json::json!("op_stack");
+        let info: ChainInfo = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(info.l2_kind, L2Kind::OpStack);
+
+        json.as_object_mut().unwrap().remove("l2_kind");
+        let info: ChainInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(info.l2_kind, L2Kind::L1);
+    }
+
     #[test]
     fn test_cross_chain_config_defaults() {
         let config = CrossChainConfig::default();
         assert_eq!(config.expire_time_seconds, 60);
         assert_eq!(config.exec_timeout_seconds, 30);
+        assert_eq!(config.max_parallel_batches_per_chain, 1);
         assert_eq!(config.chain_infos.len(), 0);
     }
 
@@ -290,10 +499,12 @@ mod tests {
     fn test_cross_chain_config_builder() {
         let config = CrossChainConfig::new(vec![create_test_chain_info(1)])
             .with_expire_time(120)
-            .with_exec_timeout(60);
+            .with_exec_timeout(60)
+            .with_max_parallel_batches_per_chain(4);
 
         assert_eq!(config.expire_time_seconds, 120);
         assert_eq!(config.exec_timeout_seconds, 60);
+        assert_eq!(config.max_parallel_batches_per_chain, 4);
         assert_eq!(config.chain_infos.len(), 1);
     }
 
@@ -334,7 +545,7 @@ mod tests {
             .with_xlp_config(xlp_config.clone());
 
         assert_eq!(config.xlp_selection_config.deposit_reserve_factor, 1.5);
-        assert_eq!(config.xlp_selection_config.include_balance, true);
+        assert!(config.xlp_selection_config.include_balance);
         assert_eq!(config.xlp_selection_config.min_xlps, 2);
         assert_eq!(config.xlp_selection_config.max_xlps, 10);
     }
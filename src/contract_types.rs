@@ -57,7 +57,7 @@ pub struct UserOperation {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Asset {
-    /// ERC20 token address
+    /// ERC20 token address, or [`crate::multichain::NATIVE_TOKEN`] for the native currency
     pub erc20_token: Address,
     /// Amount
     pub amount: U256,
@@ -155,7 +155,8 @@ pub struct XlpEntry {
 }
 
 /// Single chain batch information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SingleChainBatch {
     /// UserOperation for this batch
     pub user_op: UserOperation,
@@ -163,14 +164,20 @@ pub struct SingleChainBatch {
     pub user_op_hash: Hex,
     /// Chain ID for this batch
     pub chain_id: ChainId,
+    /// Estimated gas limits and fees, already applied to `user_op`
+    pub gas_estimate: crate::gas::GasEstimate,
     /// Input voucher requests (vouchers consumed by this batch)
     pub input_voucher_requests: Vec<SdkVoucherRequest>,
     /// Output voucher requests (vouchers created by this batch)
     pub out_voucher_requests: Vec<SdkVoucherRequest>,
+    /// Custom errors of the called contracts, used to decode reverts
+    #[serde(default)]
+    pub custom_errors: Vec<alloy::json_abi::Error>,
 }
 
 /// SDK-level voucher request (before conversion to contract VoucherRequest)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SdkVoucherRequest {
     /// Reference ID for this voucher
     pub ref_id: String,
@@ -185,7 +192,8 @@ pub struct SdkVoucherRequest {
 }
 
 /// Batch status information during execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct BatchStatusInfo {
     /// Index in the batch array
     pub index: usize,
@@ -201,4 +209,11 @@ pub struct BatchStatusInfo {
     pub tx_hash: Option<Hex>,
     /// Revert reason (if failed)
     pub revert_reason: Option<String>,
+    /// Decoded revert data (if the UserOperation reverted)
+    #[serde(default)]
+    pub revert: Option<crate::revert::DecodedRevert>,
+    /// Latest block before submission, from which EntryPoint logs are searched when no
+    /// bundler is configured
+    #[serde(default)]
+    pub submitted_block: Option<u64>,
 }
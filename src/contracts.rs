@@ -0,0 +1,415 @@
+//! Solidity interfaces of the on-chain contracts used by the SDK
+
+use alloy::primitives::U256;
+
+alloy::sol! {
+    /// Minimal ERC20 interface
+    #[sol(rpc)]
+    interface IERC20 {
+        function balanceOf(address account) external view returns (uint256);
+
+        function decimals() external view returns (uint8);
+
+        function transfer(address to, uint256 amount) external returns (bool);
+
+        function approve(address spender, uint256 amount) external returns (bool);
+    }
+
+    /// EIP-2612 permit extension of ERC20
+    #[sol(rpc)]
+    #[allow(clippy::too_many_arguments)]
+    interface IERC20Permit {
+        function nonces(address owner) external view returns (uint256);
+
+        function DOMAIN_SEPARATOR() external view returns (bytes32);
+
+        function permit(
+            address owner,
+            address spender,
+            uint256 value,
+            uint256 deadline,
+            uint8 v,
+            bytes32 r,
+            bytes32 s
+        ) external;
+    }
+
+    /// EIP-712 message signed for an EIP-2612 `permit`
+    struct Permit {
+        address owner;
+        address spender;
+        uint256 value;
+        uint256 nonce;
+        uint256 deadline;
+    }
+
+    /// EIP-712 message signed for a Permit2 `permitTransferFrom`.
+    /// The spender is signed but not passed, Permit2 takes it from `msg.sender`
+    struct PermitTransferFrom {
+        TokenPermissions permitted;
+        address spender;
+        uint256 nonce;
+        uint256 deadline;
+    }
+
+    struct TokenPermissions {
+        address token;
+        uint256 amount;
+    }
+
+    /// Uniswap Permit2, signature based transfers and allowances
+    #[sol(rpc)]
+    interface IPermit2 {
+        struct TokenPermissions {
+            address token;
+            uint256 amount;
+        }
+
+        struct PermitTransferFrom {
+            TokenPermissions permitted;
+            uint256 nonce;
+            uint256 deadline;
+        }
+
+        struct SignatureTransferDetails {
+            address to;
+            uint256 requestedAmount;
+        }
+
+        struct PermitDetails {
+            address token;
+            uint160 amount;
+            uint48 expiration;
+            uint48 nonce;
+        }
+
+        struct PermitSingle {
+            PermitDetails details;
+            address spender;
+            uint256 sigDeadline;
+        }
+
+        /// Transfer tokens of `owner` to `transferDetails.to` with a one-time signature
+        function permitTransferFrom(
+            PermitTransferFrom memory permit,
+            SignatureTransferDetails calldata transferDetails,
+            address owner,
+            bytes calldata signature
+        ) external;
+
+        /// Set the Permit2 allowance of `permitSingle.spender` with a signature of `owner`
+        function permit(address owner, PermitSingle memory permitSingle, bytes calldata signature) external;
+
+        /// Allowance of `spender` on `token` of `user`, with the next `permit` nonce
+        function allowance(address user, address token, address spender)
+            external
+            view
+            returns (uint160 amount, uint48 expiration, uint48 nonce);
+    }
+
+    /// ERC-4337 EntryPoint v0.7
+    #[sol(rpc)]
+    interface IEntryPoint {
+        /// Emitted after each executed UserOperation
+        event UserOperationEvent(
+            bytes32 indexed userOpHash,
+            address indexed sender,
+            address indexed paymaster,
+            uint256 nonce,
+            bool success,
+            uint256 actualGasCost,
+            uint256 actualGasUsed
+        );
+
+        /// Emitted when the execution phase of a UserOperation reverts
+        event UserOperationRevertReason(
+            bytes32 indexed userOpHash,
+            address indexed sender,
+            uint256 nonce,
+            bytes revertReason
+        );
+
+        /// Validation of a UserOperation failed, with an `AAxx` reason
+        error FailedOp(uint256 opIndex, string reason);
+
+        /// Validation of a UserOperation reverted, with the inner revert data
+        error FailedOpWithRevert(uint256 opIndex, string reason, bytes inner);
+
+        /// Get the next nonce of `sender` for the given nonce key
+        function getNonce(address sender, uint192 key) external view returns (uint256 nonce);
+    }
+
+    /// ERC-4337 `EntryPointSimulations` v0.7, only available through a code override
+    interface IEntryPointSimulations {
+        struct PackedUserOperation {
+            address sender;
+            uint256 nonce;
+            bytes initCode;
+            bytes callData;
+            bytes32 accountGasLimits;
+            uint256 preVerificationGas;
+            bytes32 gasFees;
+            bytes paymasterAndData;
+            bytes signature;
+        }
+
+        struct ExecutionResult {
+            uint256 preOpGas;
+            uint256 paid;
+            uint256 accountValidationData;
+            uint256 paymasterValidationData;
+            bool targetSuccess;
+            bytes targetResult;
+        }
+
+        /// Validate and execute a UserOperation without signature checks reverting
+        function simulateHandleOp(PackedUserOperation calldata op, address target, bytes calldata targetCallData)
+            external
+            returns (ExecutionResult memory);
+    }
+
+    /// eth-infinitism SimpleAccount
+    interface ISimpleAccount {
+        function execute(address dest, uint256 value, bytes calldata func) external;
+
+        function executeBatch(address[] calldata dest, uint256[] calldata value, bytes[] calldata func)
+            external;
+    }
+
+    /// ERC-7579 modular account execution interface
+    interface IERC7579Account {
+        struct Execution {
+            address target;
+            uint256 value;
+            bytes callData;
+        }
+
+        function execute(bytes32 mode, bytes calldata executionCalldata) external payable;
+    }
+
+    /// OP Stack `GasPriceOracle` predeploy
+    #[sol(rpc)]
+    interface IGasPriceOracle {
+        /// L1 data fee of a transaction with the given RLP-encoded payload
+        function getL1Fee(bytes memory data) external view returns (uint256);
+    }
+
+    /// Arbitrum `NodeInterface` precompile
+    #[sol(rpc)]
+    interface INodeInterface {
+        /// L1 component of the gas used by a call, priced at `baseFee`
+        function gasEstimateL1Component(address to, bool contractCreation, bytes calldata data)
+            external
+            payable
+            returns (uint64 gasEstimateForL1, uint256 baseFee, uint256 l1BaseFeeEstimate);
+    }
+
+    /// Multicall3, batching calls into a single `eth_call`
+    #[sol(rpc)]
+    interface IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls)
+            external
+            payable
+            returns (Result[] memory returnData);
+
+        function getEthBalance(address addr) external view returns (uint256 balance);
+    }
+
+    /// EIL CrossChainPaymaster
+    #[sol(rpc)]
+    interface ICrossChainPaymaster {
+        struct XlpEntry {
+            address l1XlpAddress;
+            address l2XlpAddress;
+            uint256 bond;
+        }
+
+        struct Asset {
+            address erc20Token;
+            uint256 amount;
+        }
+
+        struct AtomicSwapFeeRule {
+            uint256 startFeePercentNumerator;
+            uint256 maxFeePercentNumerator;
+            uint256 feeIncreasePerSecond;
+            uint256 unspentVoucherFee;
+        }
+
+        struct SourceSwapComponent {
+            uint256 chainId;
+            address sender;
+            address paymaster;
+            Asset[] assets;
+            AtomicSwapFeeRule feeRule;
+            uint256 senderNonce;
+            address[] allowedXlps;
+        }
+
+        struct DestinationSwapComponent {
+            uint256 chainId;
+            address sender;
+            address paymaster;
+            Asset[] assets;
+            uint256 maxUserOpCost;
+            uint256 expiresAt;
+        }
+
+        struct VoucherRequest {
+            SourceSwapComponent origination;
+            DestinationSwapComponent destination;
+        }
+
+        struct Voucher {
+            VoucherRequest request;
+            bytes signature;
+        }
+
+        /// Ephemeral session authorizing the vouchers of a destination UserOperation
+        struct SessionData {
+            bytes data;
+            bytes ephemeralSignature;
+        }
+
+        /// Emitted by the source chain paymaster for each voucher request of a UserOperation
+        event VoucherRequestCreated(bytes32 indexed requestId, address indexed sender, uint256 destinationChainId);
+
+        /// Emitted when an XLP publishes its signed voucher for a request
+        event VoucherIssued(bytes32 indexed requestId, address indexed xlp, bytes signature);
+
+        /// Lock the source assets of a voucher request, emitting `VoucherRequestCreated`
+        /// ERC-20 assets are pulled from the sender; native assets are sent as value.
+        function createVoucherRequest(VoucherRequest calldata request)
+            external
+            payable
+            returns (bytes32 requestId);
+
+        /// List all registered XLPs
+        function getXlps() external view returns (XlpEntry[] memory xlps);
+
+        /// Get the deposits of an XLP for each of the given tokens
+        function getDeposits(address xlp, address[] calldata tokens)
+            external
+            view
+            returns (uint256[] memory deposits);
+    }
+
+    /// Helper storing static call results as named runtime variables and
+    /// splicing them into later calldata
+    ///
+    /// The account reaches it through DELEGATECALL, so the helper runs in the account's
+    /// context: variables live in the account's transient storage and spliced calls are
+    /// made by the account itself.
+    #[sol(rpc)]
+    interface IRuntimeVarsHelper {
+        struct VarRef {
+            bytes8 name;
+            uint256 offset;
+        }
+
+        struct Call {
+            address target;
+            uint256 value;
+            bytes data;
+        }
+
+        /// Static-call `target` with `data` and store the first returned word as `name`
+        function setVar(bytes8 name, address target, bytes calldata data) external;
+
+        /// Call `target` with `data`, overwriting the 32-byte word at each
+        /// `refs[i].offset` with the value stored as `refs[i].name`
+        function execWithVars(address target, uint256 value, bytes calldata data, VarRef[] calldata refs)
+            external
+            payable;
+
+        /// Run `calls` in order; calls to the helper itself (`setVar`, `execWithVars`)
+        /// are delegated as well, any other call is made with its value
+        function executeBatch(Call[] calldata calls) external payable;
+    }
+}
+
+impl From<&crate::contract_types::Asset> for ICrossChainPaymaster::Asset {
+    fn from(asset: &crate::contract_types::Asset) -> Self {
+        Self {
+            erc20Token: asset.erc20_token,
+            amount: asset.amount,
+        }
+    }
+}
+
+impl From<&crate::contract_types::VoucherRequest> for ICrossChainPaymaster::VoucherRequest {
+    fn from(request: &crate::contract_types::VoucherRequest) -> Self {
+        let origination = &request.origination;
+        let destination = &request.destination;
+        Self {
+            origination: ICrossChainPaymaster::SourceSwapComponent {
+                chainId: U256::from(origination.chain_id),
+                sender: origination.sender,
+                paymaster: origination.paymaster,
+                assets: origination.assets.iter().map(Into::into).collect(),
+                feeRule: ICrossChainPaymaster::AtomicSwapFeeRule {
+                    startFeePercentNumerator: origination.fee_rule.start_fee_percent_numerator,
+                    maxFeePercentNumerator: origination.fee_rule.max_fee_percent_numerator,
+                    feeIncreasePerSecond: origination.fee_rule.fee_increase_per_second,
+                    unspentVoucherFee: origination.fee_rule.unspent_voucher_fee,
+                },
+                senderNonce: origination.sender_nonce,
+                allowedXlps: origination.allowed_xlps.clone(),
+            },
+            destination: ICrossChainPaymaster::DestinationSwapComponent {
+                chainId: U256::from(destination.chain_id),
+                sender: destination.sender,
+                paymaster: destination.paymaster,
+                assets: destination.assets.iter().map(Into::into).collect(),
+                maxUserOpCost: destination.max_user_op_cost,
+                expiresAt: destination.expires_at,
+            },
+        }
+    }
+}
+
+impl From<&crate::contract_types::Voucher> for ICrossChainPaymaster::Voucher {
+    fn from(voucher: &crate::contract_types::Voucher) -> Self {
+        Self {
+            request: (&voucher.request).into(),
+            signature: voucher.signature.clone(),
+        }
+    }
+}
+
+impl From<crate::user_op::PackedUserOperation> for IEntryPointSimulations::PackedUserOperation {
+    fn from(op: crate::user_op::PackedUserOperation) -> Self {
+        Self {
+            sender: op.sender,
+            nonce: op.nonce,
+            initCode: op.initCode,
+            callData: op.callData,
+            accountGasLimits: op.accountGasLimits,
+            preVerificationGas: op.preVerificationGas,
+            gasFees: op.gasFees,
+            paymasterAndData: op.paymasterAndData,
+            signature: op.signature,
+        }
+    }
+}
+
+impl From<ICrossChainPaymaster::XlpEntry> for crate::contract_types::XlpEntry {
+    fn from(entry: ICrossChainPaymaster::XlpEntry) -> Self {
+        Self {
+            l1_xlp_address: entry.l1XlpAddress,
+            l2_xlp_address: entry.l2XlpAddress,
+            bond: entry.bond,
+        }
+    }
+}
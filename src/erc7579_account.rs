@@ -0,0 +1,189 @@
+//! ERC-7579 modular account implementation of [`MultiChainSmartAccount`](crate::account::MultiChainSmartAccount)
+//!
+//! Calls are executed through `execute(bytes32 mode, bytes executionCalldata)`.
+//! The mode word is laid out as `callType (1) | execType (1) | unused (4) |
+//! modeSelector (4) | modePayload (22)`.
+
+use crate::{
+    account::{CallEncoder, SmartAccount},
+    contracts::IERC7579Account,
+    types::*,
+};
+use alloy::{
+    primitives::B256,
+    sol_types::{SolCall, SolValue},
+};
+
+/// Call type for a single execution
+pub const CALLTYPE_SINGLE: u8 = 0x00;
+/// Call type for a batch of executions
+pub const CALLTYPE_BATCH: u8 = 0x01;
+/// Call type for a single DELEGATECALL
+pub const CALLTYPE_DELEGATECALL: u8 = 0xff;
+
+/// ERC-7579 execution type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExecType {
+    /// Revert if any execution fails
+    #[default]
+    Default,
+    /// Continue on failed executions
+    Try,
+}
+
+impl ExecType {
+    fn as_byte(self) -> u8 {
+        match self {
+            ExecType::Default => 0x00,
+            ExecType::Try => 0x01,
+        }
+    }
+}
+
+/// Encode an ERC-7579 mode word with default selector and empty payload
+pub fn encode_mode(call_type: u8, exec_type: ExecType) -> B256 {
+    let mut mode = B256::ZERO;
+    mode[0] = call_type;
+    mode[1] = exec_type.as_byte();
+    mode
+}
+
+/// Encode calls as ERC-7579 `execute` calldata
+/// A single call uses the packed single mode, several calls use the batch mode
+pub fn encode_erc7579_calls(calls: &[Call], exec_type: ExecType) -> Hex {
+    let (mode, execution_calldata) = match calls {
+        [] => return Hex::new(),
+        [call] => {
+            let mut packed = Vec::with_capacity(52 + call.data.len());
+            packed.extend_from_slice(call.target.as_slice());
+            packed.extend_from_slice(&call.value.unwrap_or_default().to_be_bytes::<32>());
+            packed.extend_from_slice(&call.data);
+            (encode_mode(CALLTYPE_SINGLE, exec_type), packed)
+        }
+        _ => {
+            let executions: Vec<_> = calls
+                .iter()
+                .map(|call| IERC7579Account::Execution {
+                    target: call.target,
+                    value: call.value.unwrap_or_default(),
+                    callData: call.data.clone(),
+                })
+                .collect();
+            (
+                encode_mode(CALLTYPE_BATCH, exec_type),
+                executions.abi_encode(),
+            )
+        }
+    };
+
+    IERC7579Account::executeCall {
+        mode,
+        executionCalldata: execution_calldata.into(),
+    }
+    .abi_encode()
+    .into()
+}
+
+/// Encode a DELEGATECALL of `target` as ERC-7579 `execute` calldata
+/// The execution calldata is packed as `target ++ data`, without a value
+pub fn encode_erc7579_delegate_call(target: Address, data: &[u8], exec_type: ExecType) -> Hex {
+    let mut packed = Vec::with_capacity(20 + data.len());
+    packed.extend_from_slice(target.as_slice());
+    packed.extend_from_slice(data);
+    IERC7579Account::executeCall {
+        mode: encode_mode(CALLTYPE_DELEGATECALL, exec_type),
+        executionCalldata: packed.into(),
+    }
+    .abi_encode()
+    .into()
+}
+
+/// ERC-7579 account executing calls through `execute(mode, executionCalldata)`
+pub type Erc7579Account = SmartAccount<Erc7579Encoder>;
+
+/// ERC-7579 call encoding with the execution type of every `execute`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Erc7579Encoder {
+    /// Execution type of the encoded calls
+    pub exec_type: ExecType,
+}
+
+impl CallEncoder for Erc7579Encoder {
+    fn encode_calls(&self, calls: &[Call]) -> Hex {
+        encode_erc7579_calls(calls, self.exec_type)
+    }
+
+    fn encode_delegate_call(&self, target: Address, data: &[u8]) -> Option<Hex> {
+        Some(encode_erc7579_delegate_call(target, data, self.exec_type))
+    }
+}
+
+impl Erc7579Account {
+    /// Set the execution type
+    pub fn with_exec_type(mut self, exec_type: ExecType) -> Self {
+        self.encoder_mut().exec_type = exec_type;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::U256;
+
+    fn create_test_calls(count: u8) -> Vec<Call> {
+        (0..count)
+            .map(|i| Call {
+                target: Address::repeat_byte(0x30 + i),
+                data: Hex::from(vec![i + 1; 4]),
+                value: Some(U256::from(i + 1)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_mode() {
+        let mode = encode_mode(CALLTYPE_BATCH, ExecType::Try);
+        assert_eq!(mode[0], 0x01);
+        assert_eq!(mode[1], 0x01);
+        assert!(mode[2..].iter().all(|b| *b == 0));
+        assert_eq!(encode_mode(CALLTYPE_SINGLE, ExecType::Default), B256::ZERO);
+    }
+
+    #[test]
+    fn test_encode_single_call() {
+        let calls = create_test_calls(1);
+        let data = encode_erc7579_calls(&calls, ExecType::Default);
+
+        let decoded = IERC7579Account::executeCall::abi_decode(&data, true).unwrap();
+        assert_eq!(decoded.mode, B256::ZERO);
+
+        let execution = decoded.executionCalldata;
+        assert_eq!(execution.len(), 20 + 32 + 4);
+        assert_eq!(&execution[..20], calls[0].target.as_slice());
+        assert_eq!(U256::from_be_slice(&execution[20..52]), U256::from(1));
+        assert_eq!(&execution[52..], &calls[0].data[..]);
+    }
+
+    #[test]
+    fn test_encode_batch() {
+        let calls = create_test_calls(2);
+        let data = encode_erc7579_calls(&calls, ExecType::Default);
+
+        let decoded = IERC7579Account::executeCall::abi_decode(&data, true).unwrap();
+        assert_eq!(decoded.mode[0], CALLTYPE_BATCH);
+
+        let executions =
+            Vec::<IERC7579Account::Execution>::abi_decode(&decoded.executionCalldata, true)
+                .unwrap();
+        assert_eq!(executions.len(), 2);
+        assert_eq!(executions[1].target, calls[1].target);
+        assert_eq!(executions[1].value, U256::from(2));
+        assert_eq!(executions[1].callData, calls[1].data);
+    }
+
+    #[test]
+    fn test_encode_empty() {
+        assert!(encode_erc7579_calls(&[], ExecType::Default).is_empty());
+    }
+}
@@ -84,10 +84,90 @@ pub enum EilError {
     #[error("SetVarAction('{0}'): call must not be dynamic")]
     DynamicVariableCall(String),
 
+    /// Runtime variable read before being set
+    #[error("Runtime variable '{0}' used before being set in this batch")]
+    RuntimeVarNotSet(String),
+
+    /// RuntimeVarsHelper not configured
+    #[error("RuntimeVarsHelper not configured on chain {0}")]
+    RuntimeVarsHelperNotConfigured(u64),
+
+    /// Account cannot DELEGATECALL the RuntimeVarsHelper
+    #[error("Account cannot DELEGATECALL on chain {0}, required for runtime variables")]
+    DelegateCallUnsupported(u64),
+
+    /// EntryPointSimulations code not configured
+    #[error("EntryPointSimulations code not configured on chain {0}")]
+    SimulationNotConfigured(u64),
+
+    /// Chain RPC does not support `eth_simulateV1`
+    #[error("RPC of chain {0} does not support eth_simulateV1, required to simulate batches")]
+    SimulationUnsupported(u64),
+
+    /// Fee percentage outside 0.0 to 1.0 or finer than one basis point
+    #[error("Fee percentage {0} is not a whole number of basis points between 0.0 and 1.0")]
+    InvalidFeePercent(f64),
+
+    /// Token balances too low to fund a spend
+    #[error(
+        "Not enough {token} to fund {required} on chain {chain_id}, only {available} reachable"
+    )]
+    InsufficientFunds {
+        token: String,
+        chain_id: u64,
+        required: alloy::primitives::U256,
+        available: alloy::primitives::U256,
+    },
+
+    /// Operation not possible with a native currency
+    #[error("Not supported for native tokens: {0}")]
+    UnsupportedNativeOperation(String),
+
+    /// Token amount not parseable
+    #[error("Invalid token amount {0}")]
+    InvalidAmount(String),
+
+    /// Token decimals neither declared nor fetched
+    #[error(
+        "Decimals of token {0} unknown: declare them with with_decimals() or call fetch_decimals()"
+    )]
+    UnknownDecimals(String),
+
+    /// Token decimals differ across chains
+    #[error("Token {token} has different decimals across chains: {decimals:?}")]
+    InconsistentDecimals {
+        token: String,
+        decimals: Vec<(u64, u8)>,
+    },
+
+    /// Declared token decimals differ from the on-chain ones
+    #[error("Token {token} declared with {declared} decimals, but has {actual} on chain")]
+    DecimalsMismatch {
+        token: String,
+        declared: u8,
+        actual: u8,
+    },
+
+    /// Permit2 not configured
+    #[error("Permit2 not configured on chain {0}")]
+    Permit2NotConfigured(u64),
+
+    /// Account has no signer for off-chain signatures, or its signer cannot sign raw hashes
+    #[error("No signer available for off-chain signatures")]
+    SignerUnavailable,
+
+    /// Signature not in the 65-byte `r || s || v` format
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
     /// Same chain voucher request
     #[error("destinationChainId must be different than current chainId {0}")]
     SameChainVoucher(u64),
 
+    /// No ephemeral key to sign the vouchers of a voucher-consuming batch
+    #[error("No ephemeral signer available to sign vouchers")]
+    EphemeralSignerUnavailable,
+
     /// Cannot override paymaster
     #[error("Cannot override paymaster or paymasterData in a batch that uses vouchers")]
     CannotOverridePaymaster,
@@ -108,6 +188,82 @@ pub enum EilError {
     #[error("Execution timeout after {0} seconds")]
     ExecutionTimeout(u64),
 
+    /// Execution state not found in the store
+    #[error("Execution '{0}' not found in store")]
+    ExecutionNotFound(String),
+
+    /// Execution store failure
+    #[error("Execution store error: {0}")]
+    ExecutionStore(String),
+
+    /// Account or factory validation failed in the EntryPoint (AA1x, AA2x)
+    #[error("Account validation failed ({code}): {reason}")]
+    AccountValidationFailed { code: String, reason: String },
+
+    /// Paymaster validation or postOp failed in the EntryPoint (AA3x, AA5x)
+    #[error("Paymaster validation failed ({code}): {reason}")]
+    PaymasterValidationFailed { code: String, reason: String },
+
+    /// EntryPoint rejected the UserOperation (AA4x, AA9x)
+    #[error("EntryPoint rejected UserOperation ({code}): {reason}")]
+    EntryPointRejected { code: String, reason: String },
+
+    /// A call executed by the account reverted
+    #[error("UserOperation call reverted: {0}")]
+    CallReverted(String),
+
+    /// Bundler rejected the UserOperation fields (-32602)
+    #[error("Bundler rejected UserOperation fields: {0}")]
+    BundlerInvalidFields(String),
+
+    /// UserOperation rejected during account validation (-32500)
+    #[error("UserOperation rejected by account validation: {0}")]
+    BundlerAccountRejected(String),
+
+    /// UserOperation rejected during paymaster validation (-32501)
+    #[error("UserOperation rejected by paymaster validation: {0}")]
+    BundlerPaymasterRejected(String),
+
+    /// UserOperation violates opcode or storage access rules (-32502)
+    #[error("UserOperation violates bundler opcode rules: {0}")]
+    BundlerOpcodeViolation(String),
+
+    /// UserOperation signature expired or not yet valid (-32503)
+    #[error("UserOperation outside its valid time range: {0}")]
+    BundlerOutOfTimeRange(String),
+
+    /// Entity throttled or banned by the bundler (-32504)
+    #[error("UserOperation entity throttled or banned by bundler: {0}")]
+    BundlerEntityThrottled(String),
+
+    /// Entity stake or unstake delay too low (-32505)
+    #[error("UserOperation entity stake too low: {0}")]
+    BundlerInsufficientStake(String),
+
+    /// Invalid UserOperation signature (-32507)
+    #[error("UserOperation signature rejected by bundler: {0}")]
+    BundlerInvalidSignature(String),
+
+    /// Other bundler JSON-RPC error
+    #[error("Bundler JSON-RPC error {code}: {message}")]
+    BundlerRpc { code: i64, message: String },
+
+    /// Bundler request failed at the transport level
+    #[error("Bundler request failed: {0}")]
+    BundlerRequest(String),
+
+    /// Bundler endpoint rejected the request with an HTTP client error
+    #[error("Bundler rejected {method} with HTTP status {status}")]
+    BundlerHttpStatus { status: u16, method: String },
+
+    /// Bundler does not support the EntryPoint
+    #[error("Bundler for chain {chain_id} does not support EntryPoint {entry_point}")]
+    UnsupportedEntryPoint { chain_id: u64, entry_point: String },
+
+    /// RPC endpoint serves a different chain than configured
+    #[error("RPC for chain {expected} returned chain ID {actual}")]
+    ChainIdMismatch { expected: u64, actual: u64 },
+
     /// Alloy provider error
     #[error("Alloy provider error: {0}")]
     AlloyProvider(String),
@@ -1,11 +1,23 @@
 use crate::{
-    contract_types::{BatchStatusInfo, SdkVoucherRequest, SingleChainBatch},
+    account::MultiChainSmartAccount,
+    contract_types::{
+        BatchStatusInfo, SdkVoucherRequest, SingleChainBatch, Voucher, VoucherRequest,
+    },
+    contracts::{ICrossChainPaymaster, IEntryPoint},
     network::NetworkEnvironment,
+    revert::{decode_revert, DecodedRevert, RevertSource},
+    session::EphemeralSigner,
+    store::{ExecutionState, ExecutionStore},
     types::*,
     Result,
 };
-use std::sync::Arc;
-use tokio::time::{sleep, Duration};
+use alloy::{primitives::B256, providers::Provider, rpc::types::Filter, sol_types::SolEvent};
+use futures::{channel::mpsc, future, stream, stream::FuturesUnordered, Stream, StreamExt};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+use tokio::time::{sleep, sleep_until, Duration, Instant};
 
 /// Callback type for execution events
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,12 +32,14 @@ pub enum CallbackType {
     WaitingForVouchers,
     /// A voucher was signed by a provider
     VoucherIssued,
+    /// All batches finished (final item of [`CrossChainExecutor::execute_stream`])
+    Completed,
 }
 
 /// Execution callback data
 #[derive(Debug, Clone)]
 pub struct ExecCallbackData {
-    /// Batch index
+    /// Batch index (0 for `Completed`, which covers every batch)
     pub index: usize,
     /// Callback type
     pub callback_type: CallbackType,
@@ -35,8 +49,12 @@ pub struct ExecCallbackData {
     pub tx_hash: Option<Hex>,
     /// Request IDs (for vouchers)
     pub request_ids: Option<Vec<Hex>>,
+    /// Final status of every batch (for `Completed`)
+    pub batch_statuses: Option<Vec<OperationStatus>>,
     /// Revert reason (if failed)
     pub revert_reason: Option<String>,
+    /// Decoded revert data (if the UserOperation reverted)
+    pub revert: Option<DecodedRevert>,
     /// Input voucher requests
     pub input_voucher_requests: Vec<SdkVoucherRequest>,
     /// Output voucher requests
@@ -46,27 +64,183 @@ pub struct ExecCallbackData {
 /// Execution callback function type
 pub type ExecCallback = Box<dyn Fn(ExecCallbackData) + Send + Sync>;
 
+/// Result of an included UserOperation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserOpOutcome {
+    /// Hash of the bundle transaction
+    pub tx_hash: B256,
+    /// Whether the execution phase succeeded
+    pub success: bool,
+    /// Raw revert data (if the execution phase reverted)
+    pub revert_data: Option<Hex>,
+    /// Block the UserOperation was included in (if known)
+    pub block_number: Option<u64>,
+}
+
+/// Voucher request created by a landed source batch, awaiting its signed voucher
+#[derive(Debug, Clone)]
+struct VoucherWatch {
+    /// Request ID emitted by the source paymaster
+    request_id: B256,
+    /// Voucher reference ID
+    ref_id: String,
+    /// Source chain ID
+    chain_id: ChainId,
+    /// Block the voucher request was created in
+    from_block: u64,
+}
+
 /// Cross-chain executor
 /// Executes signed UserOperations across multiple chains
 pub struct CrossChainExecutor {
     network_env: Arc<NetworkEnvironment>,
+    account: Arc<dyn MultiChainSmartAccount>,
     batches: Vec<SingleChainBatch>,
+    voucher_requests: HashMap<String, VoucherRequest>,
+    ephemeral_signer: Option<Arc<EphemeralSigner>>,
     timeout_seconds: u64,
+    max_parallel_per_chain: usize,
+    poll_interval: Duration,
+    store: Option<(Arc<dyn ExecutionStore>, String)>,
+    resumed: Option<Vec<BatchStatusInfo>>,
 }
 
 impl CrossChainExecutor {
     /// Create a new executor
-    pub fn new(network_env: Arc<NetworkEnvironment>, batches: Vec<SingleChainBatch>) -> Self {
+    pub fn new(
+        network_env: Arc<NetworkEnvironment>,
+        account: Arc<dyn MultiChainSmartAccount>,
+        batches: Vec<SingleChainBatch>,
+    ) -> Self {
         let timeout_seconds = network_env.config().exec_timeout_seconds;
+        let max_parallel_per_chain = network_env.config().max_parallel_batches_per_chain.max(1);
         Self {
             network_env,
+            account,
             batches,
+            voucher_requests: HashMap::new(),
+            ephemeral_signer: None,
             timeout_seconds,
+            max_parallel_per_chain,
+            poll_interval: Duration::from_secs(1),
+            store: None,
+            resumed: None,
+        }
+    }
+
+    /// Resume an execution saved in `store`
+    ///
+    /// Batches that were executing are reconciled with chain data: included ones are
+    /// marked done or failed, `execute` waits for the others without sending them again.
+    ///
+    /// The ephemeral key is never persisted, and pending batches consuming vouchers were
+    /// signed by the user against its commitment. `execute` fails with
+    /// [`crate::EilError::EphemeralSignerUnavailable`] before submitting anything unless
+    /// the original key is passed to [`Self::with_ephemeral_signer`], e.g. from
+    /// [`Self::ephemeral_signer`] of the executor being resumed. If the key is lost, those
+    /// batches cannot be completed: build and sign them again with a new builder.
+    pub async fn resume(
+        network_env: Arc<NetworkEnvironment>,
+        account: Arc<dyn MultiChainSmartAccount>,
+        store: Arc<dyn ExecutionStore>,
+        id: &str,
+    ) -> Result<Self> {
+        let state = store
+            .load(id)
+            .await?
+            .ok_or_else(|| crate::EilError::ExecutionNotFound(id.to_string()))?;
+
+        let batches = state.batches.iter().map(|b| b.batch.clone()).collect();
+        let mut executor = Self::new(network_env, account, batches)
+            .with_voucher_requests(state.voucher_requests)
+            .with_store(store, id);
+
+        let mut statuses = state.batches;
+        for batch in &mut statuses {
+            if batch.status == OperationStatus::Executing {
+                executor.reconcile_executing(batch).await?;
+            }
         }
+        executor.resumed = Some(statuses);
+        Ok(executor)
+    }
+
+    /// Save the execution state to `store` under `id` after every state change
+    pub fn with_store(mut self, store: Arc<dyn ExecutionStore>, id: impl Into<String>) -> Self {
+        self.store = Some((store, id.into()));
+        self
+    }
+
+    /// Set the interval between receipt polls
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Set the voucher requests by reference ID, used to assemble signed vouchers
+    pub fn with_voucher_requests(
+        mut self,
+        voucher_requests: HashMap<String, VoucherRequest>,
+    ) -> Self {
+        self.voucher_requests = voucher_requests;
+        self
+    }
+
+    /// Set the session's ephemeral key, signing the vouchers of destination batches
+    pub fn with_ephemeral_signer(mut self, ephemeral_signer: Arc<EphemeralSigner>) -> Self {
+        self.ephemeral_signer = Some(ephemeral_signer);
+        self
+    }
+
+    /// The session's ephemeral key, if set
+    pub fn ephemeral_signer(&self) -> Option<Arc<EphemeralSigner>> {
+        self.ephemeral_signer.clone()
     }
 
     /// Execute all batches
     pub async fn execute<F>(&self, callback: F) -> Result<()>
+    where
+        F: Fn(ExecCallbackData) + Send + Sync,
+    {
+        self.run(&callback).await.map(|_| ())
+    }
+
+    /// Execute all batches, streaming execution events
+    ///
+    /// The last item is a `Completed` event holding every batch's final status, or
+    /// the error that aborted execution. Dropping the stream cancels execution.
+    pub fn execute_stream(&self) -> impl Stream<Item = Result<ExecCallbackData>> + Send + '_ {
+        let (sender, receiver) = mpsc::unbounded();
+        let execution = async move {
+            let result = self
+                .run(&|data| {
+                    let _ = sender.unbounded_send(Ok(data));
+                })
+                .await;
+            let last = result.map(|statuses| ExecCallbackData {
+                index: 0,
+                callback_type: CallbackType::Completed,
+                user_op_hash: Hex::new(),
+                tx_hash: None,
+                request_ids: None,
+                batch_statuses: Some(statuses.iter().map(|b| b.status).collect()),
+                revert_reason: None,
+                revert: None,
+                input_voucher_requests: Vec::new(),
+                out_voucher_requests: Vec::new(),
+            });
+            let _ = sender.unbounded_send(last);
+        };
+
+        // Events are delivered through the channel; the execution stream yields nothing
+        stream::select(
+            receiver,
+            stream::once(execution).filter_map(|_| future::ready(None)),
+        )
+    }
+
+    /// Run the execution loop, returning the final status of each batch
+    async fn run<F>(&self, callback: &F) -> Result<Vec<BatchStatusInfo>>
     where
         F: Fn(ExecCallbackData) + Send + Sync,
     {
@@ -77,117 +251,1679 @@ impl CrossChainExecutor {
             }
         }
 
-        // Initialize batch status
-        let mut batch_statuses: Vec<BatchStatusInfo> = self
-            .batches
-            .iter()
-            .enumerate()
-            .map(|(index, batch)| BatchStatusInfo {
-                index,
-                batch: batch.clone(),
-                status: OperationStatus::Pending,
-                vouchers: std::collections::HashMap::new(),
-                request_ids: None,
-                tx_hash: None,
-                revert_reason: None,
+        // Initialize batch status, or continue a resumed execution
+        let mut batch_statuses: Vec<BatchStatusInfo> = match &self.resumed {
+            Some(statuses) => statuses.clone(),
+            None => self
+                .batches
+                .iter()
+                .enumerate()
+                .map(|(index, batch)| BatchStatusInfo {
+                    index,
+                    batch: batch.clone(),
+                    status: OperationStatus::Pending,
+                    vouchers: std::collections::HashMap::new(),
+                    request_ids: None,
+                    tx_hash: None,
+                    revert_reason: None,
+                    revert: None,
+                    submitted_block: None,
+                })
+                .collect(),
+        };
+
+        // Vouchers can only be attached by the key the built UserOperations commit to
+        if self.ephemeral_signer.is_none()
+            && batch_statuses.iter().any(|b| {
+                b.status == OperationStatus::Pending && !b.batch.input_voucher_requests.is_empty()
             })
-            .collect();
+        {
+            return Err(crate::EilError::EphemeralSignerUnavailable);
+        }
+
+        let graph = DependencyGraph::new(&self.batches)?;
+        let mut voucher_watches = self.pending_voucher_watches(&batch_statuses).await?;
+        let mut waiting_notified: HashSet<usize> = HashSet::new();
+        let mut running: HashMap<ChainId, usize> = HashMap::new();
+        let mut in_flight = FuturesUnordered::new();
 
-        // Watch for voucher events (would start event listeners in real impl)
-        // self.watch_for_voucher_events(&batch_statuses, &callback).await;
+        let deadline = Instant::now() + Duration::from_secs(self.timeout_seconds);
+        let spawn = |mut batch: BatchStatusInfo, sent: Result<()>| async move {
+            let result = self
+                .execute_single_batch(&mut batch, sent, deadline, callback)
+                .await;
+            (batch, result)
+        };
+
+        // Wait for batches a resumed execution already sent
+        for batch in &mut batch_statuses {
+            if batch.status == OperationStatus::Executing {
+                if batch.submitted_block.is_none() {
+                    batch.submitted_block = self.submission_block(batch.batch.chain_id).await?;
+                }
+                *running.entry(batch.batch.chain_id).or_default() += 1;
+                in_flight.push(spawn(batch.clone(), Ok(())));
+            }
+        }
 
-        // Execution loop
-        let start_time = std::time::Instant::now();
+        // Execution loop, woken by finished batches and voucher polls
         loop {
-            // Check timeout
-            if start_time.elapsed().as_secs() > self.timeout_seconds {
-                return Err(crate::EilError::ExecutionTimeout(self.timeout_seconds));
+            // Start every ready batch, within the per-chain concurrency limit
+            for index in self.find_ready_batches(
+                &mut batch_statuses,
+                &graph,
+                &mut waiting_notified,
+                callback,
+            ) {
+                let chain_id = batch_statuses[index].batch.chain_id;
+                let count = running.entry(chain_id).or_default();
+                if *count >= self.max_parallel_per_chain {
+                    continue;
+                }
+                *count += 1;
+
+                if !batch_statuses[index]
+                    .batch
+                    .input_voucher_requests
+                    .is_empty()
+                {
+                    self.attach_vouchers(&mut batch_statuses[index]).await?;
+                }
+                let batch = &mut batch_statuses[index];
+                batch.submitted_block = self.submission_block(chain_id).await?;
+
+                // Only batches that were sent are saved as executing, so a resumed
+                // execution never sends them twice
+                let sent = self.send_batch(&batch.batch, batch.submitted_block).await;
+                batch.status = match &sent {
+                    Ok(()) => OperationStatus::Executing,
+                    Err(e) => {
+                        batch.revert_reason = Some(e.to_string());
+                        OperationStatus::Failed
+                    }
+                };
+                in_flight.push(spawn(batch.clone(), sent));
             }
 
+            self.save_state(&batch_statuses).await?;
+
             // Check if all done
-            if batch_statuses
-                .iter()
-                .all(|b| b.status == OperationStatus::Done || b.status == OperationStatus::Failed)
+            if in_flight.is_empty()
+                && batch_statuses.iter().all(|b| {
+                    b.status == OperationStatus::Done || b.status == OperationStatus::Failed
+                })
             {
                 break;
             }
 
-            // Find batch ready to execute
-            if let Some(batch_info) = self.find_ready_batch(&batch_statuses).await? {
-                self.execute_single_batch(batch_info, &callback).await?;
-            } else {
-                // Wait for events
-                sleep(Duration::from_millis(100)).await;
+            tokio::select! {
+                Some((batch, result)) = in_flight.next() => {
+                    if let Some(count) = running.get_mut(&batch.batch.chain_id) {
+                        *count -= 1;
+                    }
+                    let index = batch.index;
+                    batch_statuses[index] = batch;
+                    voucher_watches.extend(result?);
+                }
+                // Collect vouchers issued for landed source batches
+                _ = sleep(self.poll_interval), if !voucher_watches.is_empty() => {
+                    self.watch_for_voucher_events(&mut batch_statuses, &mut voucher_watches, callback)
+                        .await?;
+                }
+                _ = sleep_until(deadline) => {
+                    return Err(crate::EilError::ExecutionTimeout(self.timeout_seconds));
+                }
             }
         }
 
-        Ok(())
+        Ok(batch_statuses)
     }
 
-    /// Find a batch that's ready to execute
-    async fn find_ready_batch<'a>(
+    /// Find the indexes of batches that are ready to execute
+    ///
+    /// A batch is ready once all of its source batches are done and its input vouchers
+    /// are collected. Batches whose sources are done but still miss vouchers emit
+    /// `WaitingForVouchers` once; batches with a failed source batch are failed as well.
+    fn find_ready_batches<F>(
         &self,
-        batches: &'a [BatchStatusInfo],
-    ) -> Result<Option<&'a BatchStatusInfo>> {
-        for batch in batches {
-            if batch.status != OperationStatus::Pending {
+        batches: &mut [BatchStatusInfo],
+        graph: &DependencyGraph,
+        waiting_notified: &mut HashSet<usize>,
+        callback: &F,
+    ) -> Vec<usize>
+    where
+        F: Fn(ExecCallbackData) + Send + Sync,
+    {
+        let mut ready = Vec::new();
+        for index in 0..batches.len() {
+            if batches[index].status != OperationStatus::Pending {
+                continue;
+            }
+
+            let sources = graph.dependencies(&batches[index].batch);
+            let failed_source = sources
+                .iter()
+                .find(|(_, source)| batches[*source].status == OperationStatus::Failed);
+            if let Some((ref_id, _)) = failed_source {
+                let reason = format!("Source batch of voucher '{}' failed", ref_id);
+                let batch = &mut batches[index];
+                batch.status = OperationStatus::Failed;
+                batch.revert_reason = Some(reason);
+                callback(callback_data(batch, CallbackType::Failed));
                 continue;
             }
 
-            // Check if waiting for vouchers
-            if self.is_waiting_for_vouchers(batch).await? {
+            if sources
+                .iter()
+                .any(|(_, source)| batches[*source].status != OperationStatus::Done)
+            {
                 continue;
             }
 
-            return Ok(Some(batch));
+            if missing_vouchers(&batches[index]).is_empty() {
+                ready.push(index);
+            } else if waiting_notified.insert(index) {
+                callback(callback_data(
+                    &batches[index],
+                    CallbackType::WaitingForVouchers,
+                ));
+            }
+        }
+        ready
+    }
+
+    /// Poll the source paymasters for `VoucherIssued` events of watched requests
+    /// and hand each signed voucher to the batch consuming it
+    async fn watch_for_voucher_events<F>(
+        &self,
+        batches: &mut [BatchStatusInfo],
+        watches: &mut Vec<VoucherWatch>,
+        callback: &F,
+    ) -> Result<()>
+    where
+        F: Fn(ExecCallbackData) + Send + Sync,
+    {
+        let chain_ids: HashSet<ChainId> = watches.iter().map(|w| w.chain_id).collect();
+        for chain_id in chain_ids {
+            let chain_watches: Vec<&VoucherWatch> =
+                watches.iter().filter(|w| w.chain_id == chain_id).collect();
+            let from_block = chain_watches
+                .iter()
+                .map(|w| w.from_block)
+                .min()
+                .unwrap_or(0);
+            let request_ids: Vec<B256> = chain_watches.iter().map(|w| w.request_id).collect();
+
+            let provider = self.network_env.create_provider(chain_id).await?;
+            let filter = Filter::new()
+                .address(self.network_env.paymaster(chain_id)?)
+                .event_signature(ICrossChainPaymaster::VoucherIssued::SIGNATURE_HASH)
+                .topic1(request_ids)
+                .from_block(from_block);
+            let logs = provider
+                .get_logs(&filter)
+                .await
+                .map_err(|e| crate::EilError::AlloyProvider(e.to_string()))?;
+
+            for log in logs {
+                let Ok(event) = log.log_decode::<ICrossChainPaymaster::VoucherIssued>() else {
+                    continue;
+                };
+                let request_id = event.inner.requestId;
+                let Some(position) = watches.iter().position(|w| w.request_id == request_id) else {
+                    // Already collected from another XLP
+                    continue;
+                };
+                let watch = watches.remove(position);
+
+                let request = self.voucher_requests.get(&watch.ref_id).ok_or_else(|| {
+                    crate::EilError::Generic(format!(
+                        "Voucher request '{}' not built",
+                        watch.ref_id
+                    ))
+                })?;
+                let voucher = Voucher {
+                    request: request.clone(),
+                    signature: event.inner.signature.clone(),
+                };
+
+                for batch in batches.iter_mut() {
+                    if batch
+                        .batch
+                        .input_voucher_requests
+                        .iter()
+                        .any(|v| v.ref_id == watch.ref_id)
+                    {
+                        batch.vouchers.insert(watch.ref_id.clone(), voucher.clone());
+                        let mut data = callback_data(batch, CallbackType::VoucherIssued);
+                        data.request_ids = Some(vec![Hex::from(request_id.to_vec())]);
+                        callback(data);
+                    }
+                }
+            }
         }
-        Ok(None)
+        Ok(())
     }
 
-    /// Check if batch is waiting for vouchers
-    async fn is_waiting_for_vouchers(&self, batch: &BatchStatusInfo) -> Result<bool> {
-        for _voucher_req in &batch.batch.input_voucher_requests {
-            // Would check if voucher is signed
-            // For now, assume all vouchers are ready
+    /// Read the voucher request IDs created by a landed source batch
+    /// IDs are matched to the batch's output vouchers in emission order
+    async fn watch_output_vouchers(
+        &self,
+        batch: &mut BatchStatusInfo,
+        outcome: &UserOpOutcome,
+    ) -> Result<Vec<VoucherWatch>> {
+        let out_vouchers = &batch.batch.out_voucher_requests;
+        if out_vouchers.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let chain_id = batch.batch.chain_id;
+        let block = match outcome.block_number {
+            Some(block) => block,
+            None => self.transaction_block(chain_id, outcome.tx_hash).await?,
+        };
+
+        let provider = self.network_env.create_provider(chain_id).await?;
+        let filter = Filter::new()
+            .address(self.network_env.paymaster(chain_id)?)
+            .event_signature(ICrossChainPaymaster::VoucherRequestCreated::SIGNATURE_HASH)
+            .from_block(block)
+            .to_block(block);
+        let request_ids: Vec<B256> = provider
+            .get_logs(&filter)
+            .await
+            .map_err(|e| crate::EilError::AlloyProvider(e.to_string()))?
+            .into_iter()
+            .filter(|log| log.transaction_hash == Some(outcome.tx_hash))
+            .filter_map(|log| log.topics().get(1).copied())
+            .collect();
+
+        if request_ids.len() != out_vouchers.len() {
+            return Err(crate::EilError::Generic(format!(
+                "Batch {} created {} voucher requests, expected {}",
+                batch.index,
+                request_ids.len(),
+                out_vouchers.len()
+            )));
         }
-        Ok(false)
+
+        batch.request_ids = Some(
+            request_ids
+                .iter()
+                .map(|id| Hex::from(id.to_vec()))
+                .collect(),
+        );
+        Ok(out_vouchers
+            .iter()
+            .zip(request_ids)
+            .map(|(voucher, request_id)| VoucherWatch {
+                request_id,
+                ref_id: voucher.ref_id.clone(),
+                chain_id,
+                from_block: block,
+            })
+            .collect())
     }
 
-    /// Execute a single batch
-    async fn execute_single_batch<F>(&self, batch: &BatchStatusInfo, callback: &F) -> Result<()>
+    /// Wait for a sent batch, moving it to `Done` or `Failed`
+    /// Submission errors (`sent`) and reverts fail the batch; only a timeout aborts execution.
+    /// Returns the voucher requests created by the batch, to watch for signed vouchers.
+    async fn execute_single_batch<F>(
+        &self,
+        batch: &mut BatchStatusInfo,
+        sent: Result<()>,
+        deadline: Instant,
+        callback: &F,
+    ) -> Result<Vec<VoucherWatch>>
     where
         F: Fn(ExecCallbackData) + Send + Sync,
     {
-        // Call callback: Executing
-        callback(ExecCallbackData {
-            index: batch.index,
-            callback_type: CallbackType::Executing,
-            user_op_hash: batch.batch.user_op_hash.clone(),
-            tx_hash: None,
-            request_ids: None,
-            revert_reason: None,
-            input_voucher_requests: batch.batch.input_voucher_requests.clone(),
-            out_voucher_requests: batch.batch.out_voucher_requests.clone(),
-        });
-
-        // Submit UserOp to bundler
-        // In real implementation, would:
-        // 1. Send UserOp to bundler
-        // 2. Wait for inclusion
-        // 3. Watch for events
-
-        // Placeholder: assume success
-        callback(ExecCallbackData {
-            index: batch.index,
-            callback_type: CallbackType::Done,
-            user_op_hash: batch.batch.user_op_hash.clone(),
-            tx_hash: Some(Hex::new()), // Would be actual tx hash
-            request_ids: None,
-            revert_reason: None,
-            input_voucher_requests: batch.batch.input_voucher_requests.clone(),
-            out_voucher_requests: batch.batch.out_voucher_requests.clone(),
-        });
+        batch.status = OperationStatus::Executing;
+        callback(callback_data(batch, CallbackType::Executing));
+
+        let outcome = match sent {
+            Ok(()) => {
+                self.wait_for_outcome(&batch.batch, batch.submitted_block, deadline)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(outcome) => {
+                batch.tx_hash = Some(Hex::from(outcome.tx_hash.to_vec()));
+                if outcome.success {
+                    let watches = self.watch_output_vouchers(batch, &outcome).await?;
+                    batch.status = OperationStatus::Done;
+                    callback(callback_data(batch, CallbackType::Done));
+                    return Ok(watches);
+                } else {
+                    batch.status = OperationStatus::Failed;
+                    set_revert(batch, outcome.revert_data);
+                    callback(callback_data(batch, CallbackType::Failed));
+                }
+            }
+            Err(e @ crate::EilError::ExecutionTimeout(_)) => return Err(e),
+            Err(e) => {
+                batch.status = OperationStatus::Failed;
+                batch.revert_reason = Some(e.to_string());
+                callback(callback_data(batch, CallbackType::Failed));
+            }
+        }
+
+        Ok(Vec::new())
+    }
+
+    /// Block a transaction was included in
+    async fn transaction_block(&self, chain_id: ChainId, tx_hash: B256) -> Result<u64> {
+        let provider = self.network_env.create_provider(chain_id).await?;
+        provider
+            .get_transaction_receipt(tx_hash)
+            .await
+            .map_err(|e| crate::EilError::AlloyProvider(e.to_string()))?
+            .and_then(|receipt| receipt.block_number)
+            .ok_or_else(|| {
+                crate::EilError::Generic(format!("No receipt for transaction {}", tx_hash))
+            })
+    }
+
+    /// Voucher requests of landed batches whose vouchers were not collected yet
+    /// Only non-empty for resumed executions.
+    async fn pending_voucher_watches(
+        &self,
+        batches: &[BatchStatusInfo],
+    ) -> Result<Vec<VoucherWatch>> {
+        let mut watches = Vec::new();
+        for batch in batches {
+            let (Some(request_ids), Some(tx_hash)) = (&batch.request_ids, &batch.tx_hash) else {
+                continue;
+            };
+            if batch.status != OperationStatus::Done {
+                continue;
+            }
+
+            let pending: Vec<(&SdkVoucherRequest, &Hex)> = batch
+                .batch
+                .out_voucher_requests
+                .iter()
+                .zip(request_ids)
+                .filter(|(voucher, _)| {
+                    batches.iter().any(|b| {
+                        b.status == OperationStatus::Pending
+                            && missing_vouchers(b).contains(&voucher.ref_id)
+                    })
+                })
+                .collect();
+            if pending.is_empty() {
+                continue;
+            }
+
+            let chain_id = batch.batch.chain_id;
+            let tx_hash = B256::try_from(tx_hash.as_ref())
+                .map_err(|_| crate::EilError::Generic("Invalid transaction hash".into()))?;
+            let from_block = self.transaction_block(chain_id, tx_hash).await?;
+            for (voucher, request_id) in pending {
+                watches.push(VoucherWatch {
+                    request_id: B256::try_from(request_id.as_ref())
+                        .map_err(|_| crate::EilError::Generic("Invalid request ID".into()))?,
+                    ref_id: voucher.ref_id.clone(),
+                    chain_id,
+                    from_block,
+                });
+            }
+        }
+        Ok(watches)
+    }
+
+    /// Settle a batch left executing by an interrupted execution from chain data
+    /// Batches not included yet stay executing and are waited for until the deadline.
+    async fn reconcile_executing(&self, batch: &mut BatchStatusInfo) -> Result<()> {
+        let chain_id = batch.batch.chain_id;
+        let user_op_hash = B256::try_from(batch.batch.user_op_hash.as_ref())
+            .map_err(|_| crate::EilError::Generic("Invalid userOpHash".into()))?;
+
+        let outcome = if self.network_env.bundler().is_available(chain_id).await? {
+            self.fetch_bundler_outcome(chain_id, user_op_hash).await?
+        } else {
+            let from_block = batch.submitted_block.ok_or_else(|| {
+                crate::EilError::Generic(format!(
+                    "Submission block of batch {} was not saved",
+                    batch.index
+                ))
+            })?;
+            let entry_point = self.network_env.entry_point(chain_id)?;
+            self.fetch_log_outcome(chain_id, entry_point, user_op_hash, from_block)
+                .await?
+        };
+        let Some(outcome) = outcome else {
+            return Ok(());
+        };
+
+        batch.tx_hash = Some(Hex::from(outcome.tx_hash.to_vec()));
+        if outcome.success {
+            self.watch_output_vouchers(batch, &outcome).await?;
+            batch.status = OperationStatus::Done;
+        } else {
+            batch.status = OperationStatus::Failed;
+            set_revert(batch, outcome.revert_data);
+        }
+        Ok(())
+    }
+
+    /// Save the execution state, if a store is configured
+    async fn save_state(&self, batches: &[BatchStatusInfo]) -> Result<()> {
+        let Some((store, id)) = &self.store else {
+            return Ok(());
+        };
+        store
+            .save(&ExecutionState {
+                id: id.clone(),
+                batches: batches.to_vec(),
+                voucher_requests: self.voucher_requests.clone(),
+            })
+            .await
+    }
 
+    /// Sign the collected vouchers with the ephemeral key, attach them to the batch and
+    /// have the account sign the resulting UserOperation
+    async fn attach_vouchers(&self, batch: &mut BatchStatusInfo) -> Result<()> {
+        let vouchers = batch
+            .batch
+            .input_voucher_requests
+            .iter()
+            .map(|request| {
+                batch.vouchers.get(&request.ref_id).cloned().ok_or_else(|| {
+                    crate::EilError::Generic(format!("Voucher '{}' not issued", request.ref_id))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let ephemeral_signer = self
+            .ephemeral_signer
+            .as_ref()
+            .ok_or(crate::EilError::EphemeralSignerUnavailable)?;
+
+        let chain_id = batch.batch.chain_id;
+        crate::voucher::apply_vouchers(
+            &mut batch.batch.user_op,
+            self.network_env.paymaster(chain_id)?,
+            &vouchers,
+            ephemeral_signer,
+        )?;
+        // The userOpHash covers the vouchers, so the final operation is signed again
+        batch.batch.user_op = self
+            .account
+            .sign_user_ops(vec![batch.batch.user_op.clone()])
+            .await?
+            .pop()
+            .ok_or(crate::EilError::UserOpNotSigned)?;
+        batch.batch.user_op_hash =
+            Hex::from(crate::user_op::compute_user_op_hash(&batch.batch.user_op)?.to_vec());
         Ok(())
     }
+
+    /// Latest block of the chain, saved before submitting a batch if no bundler reports
+    /// its receipt
+    async fn submission_block(&self, chain_id: ChainId) -> Result<Option<u64>> {
+        if self.network_env.bundler().is_available(chain_id).await? {
+            return Ok(None);
+        }
+        let provider = self.network_env.create_provider(chain_id).await?;
+        let block = provider
+            .get_block_number()
+            .await
+            .map_err(|e| crate::EilError::AlloyProvider(e.to_string()))?;
+        Ok(Some(block))
+    }
+
+    /// Send a batch's UserOperation
+    ///
+    /// Without `from_block` the receipt is polled from the network bundler, so the operation
+    /// is sent to that same bundler; otherwise the account's bundler sends it and the outcome
+    /// is read from EntryPoint logs. A bundler that already knows the operation has it in
+    /// flight, which counts as sent.
+    async fn send_batch(&self, batch: &SingleChainBatch, from_block: Option<u64>) -> Result<()> {
+        let sent = match from_block {
+            None => {
+                let entry_point = self.network_env.entry_point(batch.chain_id)?;
+                self.network_env
+                    .bundler()
+                    .send_user_operation_on(batch.chain_id, &batch.user_op, entry_point)
+                    .await
+            }
+            Some(_) => {
+                self.account
+                    .send_user_operation(batch.user_op.clone())
+                    .await
+            }
+        };
+        match sent {
+            Ok(_) => Ok(()),
+            Err(e) if crate::bundler::is_already_known(&e) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Wait until a sent batch's UserOperation is included
+    /// The outcome is read from EntryPoint logs from `from_block` if set, or from the bundler.
+    /// Polling errors are retried; the last one is returned if the deadline passes.
+    async fn wait_for_outcome(
+        &self,
+        batch: &SingleChainBatch,
+        from_block: Option<u64>,
+        deadline: Instant,
+    ) -> Result<UserOpOutcome> {
+        let chain_id = batch.chain_id;
+        let entry_point = self.network_env.entry_point(chain_id)?;
+        let user_op_hash = B256::try_from(batch.user_op_hash.as_ref())
+            .map_err(|_| crate::EilError::Generic("Invalid userOpHash".into()))?;
+
+        loop {
+            let outcome = match from_block {
+                None => self.fetch_bundler_outcome(chain_id, user_op_hash).await,
+                Some(from_block) => {
+                    self.fetch_log_outcome(chain_id, entry_point, user_op_hash, from_block)
+                        .await
+                }
+            };
+            let last_error = match outcome {
+                Ok(Some(outcome)) => return Ok(outcome),
+                Ok(None) => None,
+                Err(e) => Some(e),
+            };
+            if Instant::now() >= deadline {
+                return Err(
+                    last_error.unwrap_or(crate::EilError::ExecutionTimeout(self.timeout_seconds))
+                );
+            }
+            sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Get the outcome from the bundler's `eth_getUserOperationReceipt`
+    async fn fetch_bundler_outcome(
+        &self,
+        chain_id: ChainId,
+        user_op_hash: B256,
+    ) -> Result<Option<UserOpOutcome>> {
+        let receipt = self
+            .network_env
+            .bundler()
+            .get_user_operation_receipt(chain_id, user_op_hash)
+            .await?;
+        Ok(receipt.map(|receipt| UserOpOutcome {
+            tx_hash: receipt.receipt.transaction_hash,
+            success: receipt.success,
+            block_number: receipt.receipt.block_number.map(|block| block.to::<u64>()),
+            revert_data: receipt.reason.filter(|_| !receipt.success),
+        }))
+    }
+
+    /// Get the outcome from the EntryPoint `UserOperationEvent` log
+    async fn fetch_log_outcome(
+        &self,
+        chain_id: ChainId,
+        entry_point: Address,
+        user_op_hash: B256,
+        from_block: u64,
+    ) -> Result<Option<UserOpOutcome>> {
+        let provider = self.network_env.create_provider(chain_id).await?;
+        let filter = Filter::new()
+            .address(entry_point)
+            .event_signature(IEntryPoint::UserOperationEvent::SIGNATURE_HASH)
+            .topic1(user_op_hash)
+            .from_block(from_block);
+        let logs = provider
+            .get_logs(&filter)
+            .await
+            .map_err(|e| crate::EilError::AlloyProvider(e.to_string()))?;
+        let Some(log) = logs.into_iter().next() else {
+            return Ok(None);
+        };
+
+        let event = log
+            .log_decode::<IEntryPoint::UserOperationEvent>()
+            .map_err(|e| crate::EilError::AlloyContract(format!("UserOperationEvent: {}", e)))?;
+        let tx_hash = log.transaction_hash.unwrap_or_default();
+        if event.inner.success {
+            return Ok(Some(UserOpOutcome {
+                tx_hash,
+                success: true,
+                revert_data: None,
+                block_number: log.block_number,
+            }));
+        }
+
+        // The revert reason is emitted in a separate event of the same transaction
+        let block = log.block_number.unwrap_or(from_block);
+        let filter = Filter::new()
+            .address(entry_point)
+            .event_signature(IEntryPoint::UserOperationRevertReason::SIGNATURE_HASH)
+            .topic1(user_op_hash)
+            .from_block(block)
+            .to_block(block);
+        let revert_data = provider
+            .get_logs(&filter)
+            .await
+            .map_err(|e| crate::EilError::AlloyProvider(e.to_string()))?
+            .into_iter()
+            .find_map(|log| {
+                log.log_decode::<IEntryPoint::UserOperationRevertReason>()
+                    .ok()
+                    .map(|event| event.inner.revertReason.clone())
+            });
+
+        Ok(Some(UserOpOutcome {
+            tx_hash,
+            success: false,
+            revert_data,
+            block_number: Some(block),
+        }))
+    }
+}
+
+/// Build callback data for a batch's current state
+fn callback_data(batch: &BatchStatusInfo, callback_type: CallbackType) -> ExecCallbackData {
+    ExecCallbackData {
+        index: batch.index,
+        callback_type,
+        user_op_hash: batch.batch.user_op_hash.clone(),
+        tx_hash: batch.tx_hash.clone(),
+        request_ids: batch.request_ids.clone(),
+        batch_statuses: None,
+        revert_reason: batch.revert_reason.clone(),
+        revert: batch.revert.clone(),
+        input_voucher_requests: batch.batch.input_voucher_requests.clone(),
+        out_voucher_requests: batch.batch.out_voucher_requests.clone(),
+    }
+}
+
+/// Voucher dependencies between batches
+struct DependencyGraph {
+    /// Index of the batch creating each voucher
+    sources: HashMap<String, usize>,
+}
+
+impl DependencyGraph {
+    /// Build the graph, failing on vouchers without a source batch and on cycles
+    fn new(batches: &[SingleChainBatch]) -> Result<Self> {
+        let sources: HashMap<String, usize> = batches
+            .iter()
+            .enumerate()
+            .flat_map(|(index, batch)| {
+                batch
+                    .out_voucher_requests
+                    .iter()
+                    .map(move |v| (v.ref_id.clone(), index))
+            })
+            .collect();
+        for batch in batches {
+            for voucher in &batch.input_voucher_requests {
+                if !sources.contains_key(&voucher.ref_id) {
+                    return Err(crate::EilError::VoucherNotFound(voucher.ref_id.clone()));
+                }
+            }
+        }
+
+        let graph = Self { sources };
+
+        // Kahn's algorithm: every batch must be reachable from batches without inputs
+        let mut remaining: Vec<usize> = batches
+            .iter()
+            .map(|batch| graph.dependencies(batch).len())
+            .collect();
+        let mut queue: Vec<usize> = (0..batches.len()).filter(|&i| remaining[i] == 0).collect();
+        let mut visited = 0;
+        while let Some(source) = queue.pop() {
+            visited += 1;
+            for (index, batch) in batches.iter().enumerate() {
+                for (_, dependency) in graph.dependencies(batch) {
+                    if dependency == source {
+                        remaining[index] -= 1;
+                        if remaining[index] == 0 {
+                            queue.push(index);
+                        }
+                    }
+                }
+            }
+        }
+        if visited != batches.len() {
+            return Err(crate::EilError::Generic(
+                "Circular voucher dependency between batches".into(),
+            ));
+        }
+
+        Ok(graph)
+    }
+
+    /// Source batch index of each input voucher of a batch
+    fn dependencies<'a>(&self, batch: &'a SingleChainBatch) -> Vec<(&'a str, usize)> {
+        batch
+            .input_voucher_requests
+            .iter()
+            .filter_map(|v| {
+                self.sources
+                    .get(&v.ref_id)
+                    .map(|&source| (v.ref_id.as_str(), source))
+            })
+            .collect()
+    }
+}
+
+/// Reference IDs of the input vouchers a batch has not received yet
+fn missing_vouchers(batch: &BatchStatusInfo) -> Vec<String> {
+    batch
+        .batch
+        .input_voucher_requests
+        .iter()
+        .filter(|v| !batch.vouchers.contains_key(&v.ref_id))
+        .map(|v| v.ref_id.clone())
+        .collect()
+}
+
+/// Record the decoded execution revert of a failed batch
+fn set_revert(batch: &mut BatchStatusInfo, data: Option<Hex>) {
+    batch.revert =
+        data.map(|data| decode_revert(&data, RevertSource::Call, &batch.batch.custom_errors));
+    batch.revert_reason = batch.revert.as_ref().map(ToString::to_string);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::InMemoryExecutionStore;
+    use crate::test_utils::{
+        create_test_batch, create_test_config, create_test_voucher_request, MockAccount,
+        MockRpcServer,
+    };
+    use alloy::{
+        primitives::{keccak256, U256},
+        sol_types::{SolCall, SolError, SolValue},
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    const ENTRY_POINT: &str = "0x0000000071727De22E5E9d8BAf0edAc6f37da032";
+
+    fn create_executor(
+        config: &crate::config::CrossChainConfig,
+        account: Arc<MockAccount>,
+        batches: Vec<SingleChainBatch>,
+    ) -> CrossChainExecutor {
+        CrossChainExecutor::new(Arc::new(NetworkEnvironment::new(config)), account, batches)
+            .with_poll_interval(Duration::from_millis(10))
+    }
+
+    fn receipt_json(user_op_hash: &Hex, success: bool, reason: Option<Hex>) -> serde_json::Value {
+        serde_json::json!({
+            "userOpHash": user_op_hash,
+            "entryPoint": ENTRY_POINT,
+            "sender": "0x2222222222222222222222222222222222222222",
+            "nonce": "0x0",
+            "actualGasCost": "0x1",
+            "actualGasUsed": "0x1",
+            "success": success,
+            "reason": reason,
+            "logs": [],
+            "receipt": { "transactionHash": B256::repeat_byte(0x77), "blockNumber": "0x11" },
+        })
+    }
+
+    fn log_json(address: Address, topics: Vec<B256>, data: Vec<u8>) -> serde_json::Value {
+        serde_json::json!({
+            "address": address,
+            "topics": topics,
+            "data": Hex::from(data),
+            "blockHash": B256::repeat_byte(0x66),
+            "blockNumber": "0x11",
+            "transactionHash": B256::repeat_byte(0x77),
+            "transactionIndex": "0x0",
+            "logIndex": "0x0",
+            "removed": false,
+        })
+    }
+
+    /// `VoucherRequestCreated` logs of the requests created by a SimpleAccount UserOperation
+    fn voucher_request_logs(
+        user_op: &crate::contract_types::UserOperation,
+        paymaster: Address,
+    ) -> serde_json::Value {
+        use crate::contracts::ISimpleAccount;
+
+        let (targets, calls) =
+            match ISimpleAccount::executeBatchCall::abi_decode(&user_op.call_data, true) {
+                Ok(batch) => (batch.dest, batch.func),
+                Err(_) => {
+                    let call =
+                        ISimpleAccount::executeCall::abi_decode(&user_op.call_data, true).unwrap();
+                    (vec![call.dest], vec![call.func])
+                }
+            };
+        let logs: Vec<_> = targets
+            .into_iter()
+            .zip(calls)
+            .filter(|(target, _)| *target == paymaster)
+            .filter_map(|(_, data)| {
+                ICrossChainPaymaster::createVoucherRequestCall::abi_decode(&data, true).ok()
+            })
+            .map(|call| {
+                log_json(
+                    paymaster,
+                    vec![
+                        ICrossChainPaymaster::VoucherRequestCreated::SIGNATURE_HASH,
+                        keccak256(call.request.abi_encode()),
+                        user_op.sender.into_word(),
+                    ],
+                    call.request.destination.chainId.abi_encode(),
+                )
+            })
+            .collect();
+        serde_json::json!(logs)
+    }
+
+    /// Source batch on chain 10 creating voucher "v1", consumed by a batch on chain 20
+    fn create_voucher_batches() -> Vec<SingleChainBatch> {
+        let voucher = SdkVoucherRequest {
+            ref_id: "v1".to_string(),
+            source_chain_id: Some(10),
+            destination_chain_id: 20,
+            tokens: vec![],
+            target: None,
+        };
+        let mut source = create_test_batch(10);
+        source.user_op.call_data = crate::simple_account::encode_simple_account_calls(
+            &crate::voucher::encode_voucher_request_calls(&create_test_voucher_request()),
+        );
+        source.out_voucher_requests = vec![voucher.clone()];
+        let mut destination = create_test_batch(20);
+        destination.input_voucher_requests = vec![voucher];
+        vec![source, destination]
+    }
+
+    /// Bundler accepting sent UserOperations and answering other requests with `handler`
+    async fn start_bundler<F>(handler: F) -> MockRpcServer
+    where
+        F: Fn(&str, &serde_json::Value) -> std::result::Result<serde_json::Value, (i64, String)>
+            + Send
+            + Sync
+            + 'static,
+    {
+        MockRpcServer::start(move |method, params| match method {
+            "eth_sendUserOperation" => Ok(serde_json::json!(B256::ZERO)),
+            _ => handler(method, params),
+        })
+        .await
+    }
+
+    /// UserOperations sent to a mock bundler
+    fn sent_user_ops(
+        requests: &Mutex<Vec<(String, serde_json::Value)>>,
+    ) -> Vec<crate::contract_types::UserOperation> {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(method, _)| method == "eth_sendUserOperation")
+            .map(|(_, params)| serde_json::from_value(params[0].clone()).unwrap())
+            .collect()
+    }
+
+    fn collect_callbacks() -> (
+        Arc<Mutex<Vec<ExecCallbackData>>>,
+        impl Fn(ExecCallbackData) + Send + Sync,
+    ) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        (events, move |data| recorded.lock().unwrap().push(data))
+    }
+
+    #[tokio::test]
+    async fn test_execute_polls_bundler_receipt() {
+        let batch = create_test_batch(10);
+        let user_op_hash = batch.user_op_hash.clone();
+        let polls = Arc::new(AtomicUsize::new(0));
+        let bundler_polls = polls.clone();
+        let bundler = start_bundler(move |method, _| match method {
+            "eth_getUserOperationReceipt" => {
+                // Not included on the first poll
+                if bundler_polls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    Ok(serde_json::Value::Null)
+                } else {
+                    Ok(receipt_json(&user_op_hash, true, None))
+                }
+            }
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].bundler_url = Some(bundler.url.clone());
+        let account = Arc::new(MockAccount::new());
+        let executor = create_executor(&config, account.clone(), vec![batch]);
+
+        let (events, callback) = collect_callbacks();
+        executor.execute(callback).await.unwrap();
+
+        let events = events.lock().unwrap();
+        let types: Vec<_> = events.iter().map(|e| e.callback_type).collect();
+        assert_eq!(types, vec![CallbackType::Executing, CallbackType::Done]);
+        assert_eq!(
+            events[1].tx_hash,
+            Some(Hex::from(B256::repeat_byte(0x77).to_vec()))
+        );
+        assert_eq!(polls.load(Ordering::SeqCst), 2);
+        // Sent to the bundler the receipt is polled from
+        assert_eq!(sent_user_ops(&bundler.requests).len(), 1);
+        assert!(account.bundler.get_submitted_ops().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_retries_failed_receipt_poll() {
+        let batch = create_test_batch(10);
+        let user_op_hash = batch.user_op_hash.clone();
+        let polls = Arc::new(AtomicUsize::new(0));
+        let bundler_polls = polls.clone();
+        let bundler = start_bundler(move |_, _| {
+            // The bundler is briefly unavailable after the operation is sent
+            if bundler_polls.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err((-32000, "internal error".to_string()))
+            } else {
+                Ok(receipt_json(&user_op_hash, true, None))
+            }
+        })
+        .await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].bundler_url = Some(bundler.url.clone());
+        let account = Arc::new(MockAccount::new());
+        let executor = create_executor(&config, account.clone(), vec![batch]);
+
+        let (events, callback) = collect_callbacks();
+        executor.execute(callback).await.unwrap();
+
+        let events = events.lock().unwrap();
+        let types: Vec<_> = events.iter().map(|e| e.callback_type).collect();
+        assert_eq!(types, vec![CallbackType::Executing, CallbackType::Done]);
+        assert_eq!(polls.load(Ordering::SeqCst), 2);
+        assert_eq!(sent_user_ops(&bundler.requests).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_reverted_receipt_fails_batch() {
+        let batch = create_test_batch(10);
+        let user_op_hash = batch.user_op_hash.clone();
+        let reason = Hex::from(alloy::sol_types::Revert::from("nope").abi_encode());
+        let bundler =
+            start_bundler(move |_, _| Ok(receipt_json(&user_op_hash, false, Some(reason.clone()))))
+                .await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].bundler_url = Some(bundler.url.clone());
+        let executor = create_executor(&config, Arc::new(MockAccount::new()), vec![batch]);
+
+        let (events, callback) = collect_callbacks();
+        executor.execute(callback).await.unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events[1].callback_type, CallbackType::Failed);
+        assert_eq!(events[1].revert_reason.as_deref(), Some("revert: nope"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_decodes_custom_error() {
+        let abi: alloy::json_abi::JsonAbi = serde_json::from_str(
+            r#"[{"type":"error","name":"Expired","inputs":[{"name":"at","type":"uint256"}]}]"#,
+        )
+        .unwrap();
+        let mut batch = create_test_batch(10);
+        batch.custom_errors = abi.errors().cloned().collect();
+        let mut reason = batch.custom_errors[0].selector().to_vec();
+        reason.extend(U256::from(7).abi_encode());
+        let user_op_hash = batch.user_op_hash.clone();
+        let bundler = start_bundler(move |_, _| {
+            Ok(receipt_json(
+                &user_op_hash,
+                false,
+                Some(Hex::from(reason.clone())),
+            ))
+        })
+        .await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].bundler_url = Some(bundler.url.clone());
+        let executor = create_executor(&config, Arc::new(MockAccount::new()), vec![batch]);
+
+        let (events, callback) = collect_callbacks();
+        executor.execute(callback).await.unwrap();
+
+        let events = events.lock().unwrap();
+        let revert = events[1].revert.as_ref().unwrap();
+        assert_eq!(revert.source, RevertSource::Call);
+        assert_eq!(revert.reason, "Expired(7)");
+        assert_eq!(events[1].revert_reason.as_deref(), Some("Expired(7)"));
+    }
+
+    fn executing_state(batch: SingleChainBatch) -> ExecutionState {
+        ExecutionState {
+            id: "exec-1".to_string(),
+            batches: vec![BatchStatusInfo {
+                index: 0,
+                batch,
+                status: OperationStatus::Executing,
+                vouchers: HashMap::new(),
+                request_ids: None,
+                tx_hash: None,
+                revert_reason: None,
+                revert: None,
+                submitted_block: None,
+            }],
+            voucher_requests: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_saves_state_to_store() {
+        let batch = create_test_batch(10);
+        let user_op_hash = batch.user_op_hash.clone();
+        let bundler = start_bundler(move |_, _| Ok(receipt_json(&user_op_hash, true, None))).await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].bundler_url = Some(bundler.url.clone());
+        let store = Arc::new(InMemoryExecutionStore::new());
+        let executor = create_executor(&config, Arc::new(MockAccount::new()), vec![batch])
+            .with_store(store.clone(), "exec-1");
+
+        executor.execute(|_| {}).await.unwrap();
+
+        let state = store.load("exec-1").await.unwrap().unwrap();
+        assert_eq!(state.batches[0].status, OperationStatus::Done);
+        assert_eq!(
+            state.batches[0].tx_hash,
+            Some(Hex::from(B256::repeat_byte(0x77).to_vec()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume_reconciles_included_batch() {
+        let batch = create_test_batch(10);
+        let user_op_hash = batch.user_op_hash.clone();
+        let bundler = start_bundler(move |_, _| Ok(receipt_json(&user_op_hash, true, None))).await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].bundler_url = Some(bundler.url.clone());
+        let store = Arc::new(InMemoryExecutionStore::new());
+        store.save(&executing_state(batch)).await.unwrap();
+        let account = Arc::new(MockAccount::new());
+
+        let executor = CrossChainExecutor::resume(
+            Arc::new(NetworkEnvironment::new(&config)),
+            account.clone(),
+            store.clone(),
+            "exec-1",
+        )
+        .await
+        .unwrap();
+        let (events, callback) = collect_callbacks();
+        executor.execute(callback).await.unwrap();
+
+        assert!(events.lock().unwrap().is_empty());
+        assert!(sent_user_ops(&bundler.requests).is_empty());
+        let state = store.load("exec-1").await.unwrap().unwrap();
+        assert_eq!(state.batches[0].status, OperationStatus::Done);
+    }
+
+    #[tokio::test]
+    async fn test_resume_waits_for_sent_batch() {
+        let batch = create_test_batch(10);
+        let user_op_hash = batch.user_op_hash.clone();
+        let polls = Arc::new(AtomicUsize::new(0));
+        let bundler_polls = polls.clone();
+        let bundler = MockRpcServer::start(move |method, _| match method {
+            "eth_getUserOperationReceipt" => {
+                // Still in the mempool on the first polls
+                if bundler_polls.fetch_add(1, Ordering::SeqCst) < 3 {
+                    Ok(serde_json::Value::Null)
+                } else {
+                    Ok(receipt_json(&user_op_hash, true, None))
+                }
+            }
+            _ => Ok(serde_json::json!([ENTRY_POINT])),
+        })
+        .await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].bundler_url = Some(bundler.url.clone());
+        let store = Arc::new(InMemoryExecutionStore::new());
+        store.save(&executing_state(batch)).await.unwrap();
+        let account = Arc::new(MockAccount::new());
+
+        let executor = CrossChainExecutor::resume(
+            Arc::new(NetworkEnvironment::new(&config)),
+            account.clone(),
+            store.clone(),
+            "exec-1",
+        )
+        .await
+        .unwrap()
+        .with_poll_interval(Duration::from_millis(10));
+        let (events, callback) = collect_callbacks();
+        executor.execute(callback).await.unwrap();
+
+        let types: Vec<_> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|e| e.callback_type)
+            .collect();
+        assert_eq!(types, vec![CallbackType::Executing, CallbackType::Done]);
+        assert!(sent_user_ops(&bundler.requests).is_empty());
+        let state = store.load("exec-1").await.unwrap().unwrap();
+        assert_eq!(state.batches[0].status, OperationStatus::Done);
+    }
+
+    #[tokio::test]
+    async fn test_execute_treats_already_known_as_sent() {
+        let batch = create_test_batch(10);
+        let user_op_hash = batch.user_op_hash.clone();
+        let bundler = MockRpcServer::start(move |method, _| match method {
+            "eth_sendUserOperation" => Err((-32602, "UserOperation already known".to_string())),
+            _ => Ok(receipt_json(&user_op_hash, true, None)),
+        })
+        .await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].bundler_url = Some(bundler.url.clone());
+        let executor = create_executor(&config, Arc::new(MockAccount::new()), vec![batch]);
+
+        let (events, callback) = collect_callbacks();
+        executor.execute(callback).await.unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events[1].callback_type, CallbackType::Done);
+    }
+
+    #[tokio::test]
+    async fn test_execute_saves_failed_send_as_failed() {
+        let bundler =
+            MockRpcServer::start(|_, _| Err((-32507, "invalid signature".to_string()))).await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].bundler_url = Some(bundler.url.clone());
+        let store = Arc::new(InMemoryExecutionStore::new());
+        let executor = create_executor(
+            &config,
+            Arc::new(MockAccount::new()),
+            vec![create_test_batch(10)],
+        )
+        .with_store(store.clone(), "exec-1");
+
+        let (events, callback) = collect_callbacks();
+        executor.execute(callback).await.unwrap();
+
+        assert_eq!(
+            events.lock().unwrap()[1].callback_type,
+            CallbackType::Failed
+        );
+        let state = store.load("exec-1").await.unwrap().unwrap();
+        assert_eq!(state.batches[0].status, OperationStatus::Failed);
+        assert!(state.batches[0].revert_reason.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_resume_searches_logs_from_submitted_block() {
+        let batch = create_test_batch(10);
+        let user_op_hash = B256::try_from(batch.user_op_hash.as_ref()).unwrap();
+        let rpc = MockRpcServer::start(move |method, params| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            "eth_getLogs" => {
+                assert_eq!(params[0]["fromBlock"], "0x10");
+                let topic0: B256 = params[0]["topics"][0].as_str().unwrap().parse().unwrap();
+                Ok(serde_json::json!([log_json(
+                    ENTRY_POINT.parse().unwrap(),
+                    vec![topic0, user_op_hash, B256::ZERO, B256::ZERO],
+                    (U256::ZERO, true, U256::from(1), U256::from(1)).abi_encode_params(),
+                )]))
+            }
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].rpc_url = rpc.url.clone();
+        let mut state = executing_state(batch);
+        state.batches[0].submitted_block = Some(0x10);
+        let store = Arc::new(InMemoryExecutionStore::new());
+        store.save(&state).await.unwrap();
+        let account = Arc::new(MockAccount::new());
+
+        let executor = CrossChainExecutor::resume(
+            Arc::new(NetworkEnvironment::new(&config)),
+            account.clone(),
+            store.clone(),
+            "exec-1",
+        )
+        .await
+        .unwrap();
+        executor.execute(|_| {}).await.unwrap();
+
+        assert!(account.bundler.get_submitted_ops().is_empty());
+        let state = store.load("exec-1").await.unwrap().unwrap();
+        assert_eq!(state.batches[0].status, OperationStatus::Done);
+    }
+
+    #[tokio::test]
+    async fn test_execute_requires_ephemeral_signer_for_vouchers() {
+        let config = create_test_config(vec![10, 20]);
+        let account = Arc::new(MockAccount::new());
+        let executor = create_executor(&config, account.clone(), create_voucher_batches());
+
+        let result = executor.execute(|_| {}).await;
+
+        assert!(matches!(
+            result,
+            Err(crate::EilError::EphemeralSignerUnavailable)
+        ));
+        assert!(account.bundler.get_submitted_ops().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resume_unknown_execution() {
+        let config = create_test_config(vec![10]);
+        let result = CrossChainExecutor::resume(
+            Arc::new(NetworkEnvironment::new(&config)),
+            Arc::new(MockAccount::new()),
+            Arc::new(InMemoryExecutionStore::new()),
+            "missing",
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(crate::EilError::ExecutionNotFound(id)) if id == "missing"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_execute_reads_entry_point_logs() {
+        let batch = create_test_batch(10);
+        let user_op_hash = B256::try_from(batch.user_op_hash.as_ref()).unwrap();
+        let rpc = MockRpcServer::start(move |method, params| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            "eth_blockNumber" => Ok(serde_json::json!("0x10")),
+            "eth_getLogs" => {
+                let topic0: B256 = params[0]["topics"][0].as_str().unwrap().parse().unwrap();
+                let (topics, data) = if topic0 == IEntryPoint::UserOperationEvent::SIGNATURE_HASH {
+                    (
+                        vec![topic0, user_op_hash, B256::ZERO, B256::ZERO],
+                        (U256::ZERO, false, U256::from(1), U256::from(1)).abi_encode_params(),
+                    )
+                } else {
+                    (
+                        vec![topic0, user_op_hash, B256::ZERO],
+                        (
+                            U256::ZERO,
+                            Hex::from(alloy::sol_types::Revert::from("nope").abi_encode()),
+                        )
+                            .abi_encode_params(),
+                    )
+                };
+                Ok(serde_json::json!([{
+                    "address": ENTRY_POINT,
+                    "topics": topics,
+                    "data": Hex::from(data),
+                    "blockHash": B256::repeat_byte(0x66),
+                    "blockNumber": "0x11",
+                    "transactionHash": B256::repeat_byte(0x77),
+                    "transactionIndex": "0x0",
+                    "logIndex": "0x0",
+                    "removed": false,
+                }]))
+            }
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].rpc_url = rpc.url.clone();
+        let executor = create_executor(&config, Arc::new(MockAccount::new()), vec![batch]);
+
+        let (events, callback) = collect_callbacks();
+        executor.execute(callback).await.unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events[1].callback_type, CallbackType::Failed);
+        assert_eq!(events[1].revert_reason.as_deref(), Some("revert: nope"));
+        assert_eq!(
+            events[1].tx_hash,
+            Some(Hex::from(B256::repeat_byte(0x77).to_vec()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_waits_for_issued_voucher() {
+        let ephemeral_signer = Arc::new(EphemeralSigner::random());
+        let mut batches = create_voucher_batches();
+        // Destination as built: committed to the ephemeral key
+        let destination = &mut batches[1];
+        destination.user_op.paymaster = Some(Address::repeat_byte(0x33));
+        destination.user_op.paymaster_data = Some(ephemeral_signer.commitment());
+        destination.user_op_hash = Hex::from(
+            crate::user_op::compute_user_op_hash(&destination.user_op)
+                .unwrap()
+                .to_vec(),
+        );
+        let request_id = keccak256(
+            ICrossChainPaymaster::VoucherRequest::from(&create_test_voucher_request()).abi_encode(),
+        );
+        let hashes: Vec<Hex> = batches.iter().map(|b| b.user_op_hash.clone()).collect();
+        let bundler = start_bundler(move |_, params| {
+            let hash: Hex = serde_json::from_value(params[0].clone()).unwrap();
+            Ok(receipt_json(&hash, true, None))
+        })
+        .await;
+        let sent = bundler.requests.clone();
+        let issued_polls = Arc::new(AtomicUsize::new(0));
+        let rpc_polls = issued_polls.clone();
+        let source_rpc = MockRpcServer::start(move |method, params| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            "eth_getLogs" => {
+                let topic0: B256 = params[0]["topics"][0].as_str().unwrap().parse().unwrap();
+                let paymaster = Address::repeat_byte(0x33);
+                if topic0 == ICrossChainPaymaster::VoucherRequestCreated::SIGNATURE_HASH {
+                    // Created by the submitted source UserOperation
+                    Ok(voucher_request_logs(&sent_user_ops(&sent)[0], paymaster))
+                } else if rpc_polls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    // Not issued on the first poll
+                    Ok(serde_json::json!([]))
+                } else {
+                    Ok(serde_json::json!([log_json(
+                        paymaster,
+                        vec![topic0, request_id, B256::ZERO],
+                        (Hex::from(vec![0xcd; 65]),).abi_encode_params(),
+                    )]))
+                }
+            }
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let mut config = create_test_config(vec![10, 20]);
+        config.chain_infos[0].rpc_url = source_rpc.url.clone();
+        for info in &mut config.chain_infos {
+            info.paymaster = Address::repeat_byte(0x33);
+            info.bundler_url = Some(bundler.url.clone());
+        }
+        let executor = create_executor(&config, Arc::new(MockAccount::new()), batches)
+            .with_voucher_requests(HashMap::from([(
+                "v1".to_string(),
+                create_test_voucher_request(),
+            )]))
+            .with_ephemeral_signer(ephemeral_signer.clone());
+
+        let (events, callback) = collect_callbacks();
+        executor.execute(callback).await.unwrap();
+
+        let events = events.lock().unwrap();
+        let types: Vec<_> = events.iter().map(|e| (e.index, e.callback_type)).collect();
+        assert_eq!(
+            types,
+            vec![
+                (0, CallbackType::Executing),
+                (0, CallbackType::Done),
+                (1, CallbackType::WaitingForVouchers),
+                (1, CallbackType::VoucherIssued),
+                (1, CallbackType::Executing),
+                (1, CallbackType::Done),
+            ]
+        );
+        let request_ids = Some(vec![Hex::from(request_id.to_vec())]);
+        assert_eq!(events[1].request_ids, request_ids);
+        assert_eq!(events[3].request_ids, request_ids);
+        assert_eq!(events[1].user_op_hash, hashes[0]);
+
+        // The destination UserOperation carries the voucher after the commitment in its
+        // paymasterData and is submitted under the hash of the final, signed operation
+        let mut submitted = sent_user_ops(&bundler.requests);
+        let destination = &mut submitted[1];
+        assert_eq!(destination.paymaster, Some(Address::repeat_byte(0x33)));
+        let paymaster_data = destination.paymaster_data.as_ref().unwrap();
+        assert_eq!(
+            &paymaster_data[..32],
+            ephemeral_signer.commitment().as_ref()
+        );
+        let (vouchers, session) = <(
+            Vec<ICrossChainPaymaster::Voucher>,
+            ICrossChainPaymaster::SessionData,
+        )>::abi_decode_params(&paymaster_data[32..], true)
+        .unwrap();
+        assert_eq!(vouchers[0].signature, Hex::from(vec![0xcd; 65]));
+        let (ephemeral, _, sender, nonce) =
+            <(Address, U256, Address, U256)>::abi_decode_params(&session.data, true).unwrap();
+        assert_eq!(ephemeral, ephemeral_signer.address());
+        assert_eq!(sender, destination.sender);
+        assert_eq!(nonce, destination.nonce);
+        assert!(!destination.signature.is_empty());
+        // Restore the SDK-only fields dropped from the JSON-RPC representation
+        destination.chain_id = Some(20);
+        destination.entry_point_address = Some(ENTRY_POINT.parse().unwrap());
+        let final_hash = Hex::from(
+            crate::user_op::compute_user_op_hash(destination)
+                .unwrap()
+                .to_vec(),
+        );
+        assert_ne!(final_hash, hashes[1]);
+        assert_eq!(events[4].user_op_hash, final_hash);
+    }
+
+    #[tokio::test]
+    async fn test_execute_fails_destination_of_failed_source() {
+        let batches = create_voucher_batches();
+        let source_hash = batches[0].user_op_hash.clone();
+        let bundler = start_bundler(move |_, params| {
+            let hash: Hex = serde_json::from_value(params[0].clone()).unwrap();
+            Ok(receipt_json(&hash, hash != source_hash, None))
+        })
+        .await;
+        let mut config = create_test_config(vec![10, 20]);
+        for info in &mut config.chain_infos {
+            info.bundler_url = Some(bundler.url.clone());
+        }
+        let executor = create_executor(&config, Arc::new(MockAccount::new()), batches)
+            .with_ephemeral_signer(Arc::new(EphemeralSigner::random()));
+
+        let (events, callback) = collect_callbacks();
+        executor.execute(callback).await.unwrap();
+
+        let events = events.lock().unwrap();
+        let types: Vec<_> = events.iter().map(|e| (e.index, e.callback_type)).collect();
+        assert_eq!(
+            types,
+            vec![
+                (0, CallbackType::Executing),
+                (0, CallbackType::Failed),
+                (1, CallbackType::Failed),
+            ]
+        );
+        assert_eq!(
+            events[2].revert_reason.as_deref(),
+            Some("Source batch of voucher 'v1' failed")
+        );
+    }
+
+    /// Bundler answering receipts only once `expected` UserOperations were submitted
+    async fn start_batching_bundler(expected: usize) -> MockRpcServer {
+        let submitted = AtomicUsize::new(0);
+        MockRpcServer::start(move |method, params| {
+            if method == "eth_sendUserOperation" {
+                submitted.fetch_add(1, Ordering::SeqCst);
+                return Ok(serde_json::json!(B256::ZERO));
+            }
+            if submitted.load(Ordering::SeqCst) < expected {
+                return Ok(serde_json::Value::Null);
+            }
+            let hash: Hex = serde_json::from_value(params[0].clone()).unwrap();
+            Ok(receipt_json(&hash, true, None))
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_execute_independent_batches_in_parallel() {
+        let account = Arc::new(MockAccount::new());
+        let bundler = start_batching_bundler(2).await;
+        let mut config = create_test_config(vec![10, 20]);
+        for info in &mut config.chain_infos {
+            info.bundler_url = Some(bundler.url.clone());
+        }
+        let batches = vec![create_test_batch(10), create_test_batch(20)];
+        let executor = create_executor(&config, account.clone(), batches);
+
+        let (events, callback) = collect_callbacks();
+        executor.execute(callback).await.unwrap();
+
+        let events = events.lock().unwrap();
+        let types: Vec<_> = events.iter().map(|e| e.callback_type).collect();
+        assert_eq!(
+            types[..2],
+            [CallbackType::Executing, CallbackType::Executing]
+        );
+        assert_eq!(types[2..], [CallbackType::Done, CallbackType::Done]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_limits_batches_per_chain() {
+        let account = Arc::new(MockAccount::new());
+        let bundler = start_batching_bundler(1).await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].bundler_url = Some(bundler.url.clone());
+        let batches = vec![create_test_batch(10), create_test_batch(10)];
+        let executor = create_executor(&config, account.clone(), batches);
+
+        let (events, callback) = collect_callbacks();
+        executor.execute(callback).await.unwrap();
+
+        let events = events.lock().unwrap();
+        let types: Vec<_> = events.iter().map(|e| (e.index, e.callback_type)).collect();
+        assert_eq!(
+            types,
+            vec![
+                (0, CallbackType::Executing),
+                (0, CallbackType::Done),
+                (1, CallbackType::Executing),
+                (1, CallbackType::Done),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dependency_graph_rejects_cycles() {
+        let mut batches = create_voucher_batches();
+        let back = SdkVoucherRequest {
+            ref_id: "v2".to_string(),
+            source_chain_id: Some(20),
+            destination_chain_id: 10,
+            tokens: vec![],
+            target: None,
+        };
+        batches[1].out_voucher_requests = vec![back.clone()];
+        batches[0].input_voucher_requests = vec![back];
+
+        assert!(DependencyGraph::new(&batches).is_err());
+    }
+
+    #[test]
+    fn test_dependency_graph_requires_voucher_source() {
+        let mut batches = create_voucher_batches();
+        batches[0].out_voucher_requests.clear();
+
+        assert!(matches!(
+            DependencyGraph::new(&batches),
+            Err(crate::EilError::VoucherNotFound(ref_id)) if ref_id == "v1"
+        ));
+        let graph = DependencyGraph::new(&create_voucher_batches()).unwrap();
+        assert_eq!(
+            graph.dependencies(&create_voucher_batches()[1]),
+            vec![("v1", 0)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_stream_ends_with_summary() {
+        let batches = create_voucher_batches();
+        let source_hash = batches[0].user_op_hash.clone();
+        let bundler = start_bundler(move |_, params| {
+            let hash: Hex = serde_json::from_value(params[0].clone()).unwrap();
+            Ok(receipt_json(&hash, hash != source_hash, None))
+        })
+        .await;
+        let mut config = create_test_config(vec![10, 20]);
+        for info in &mut config.chain_infos {
+            info.bundler_url = Some(bundler.url.clone());
+        }
+        let executor = create_executor(&config, Arc::new(MockAccount::new()), batches)
+            .with_ephemeral_signer(Arc::new(EphemeralSigner::random()));
+
+        let events: Vec<_> = executor.execute_stream().collect().await;
+
+        let types: Vec<_> = events
+            .iter()
+            .map(|e| e.as_ref().unwrap().callback_type)
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                CallbackType::Executing,
+                CallbackType::Failed,
+                CallbackType::Failed,
+                CallbackType::Completed,
+            ]
+        );
+        let summary = events.last().unwrap().as_ref().unwrap();
+        assert_eq!(
+            summary.batch_statuses,
+            Some(vec![OperationStatus::Failed, OperationStatus::Failed])
+        );
+        assert_eq!(summary.index, 0);
+    }
+
+    #[tokio::test]
+    async fn test_execute_stream_yields_error() {
+        let mut batch = create_test_batch(10);
+        batch.user_op.signature = Hex::new();
+        let config = create_test_config(vec![10]);
+        let executor = create_executor(&config, Arc::new(MockAccount::new()), vec![batch]);
+
+        let events: Vec<_> = executor.execute_stream().collect().await;
+
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], Err(crate::EilError::UserOpNotSigned)));
+    }
+
+    #[tokio::test]
+    async fn test_execute_stream_cancelled_on_drop() {
+        // The UserOperation is never included
+        let bundler = start_bundler(|_, _| Ok(serde_json::Value::Null)).await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].bundler_url = Some(bundler.url.clone());
+        let executor = create_executor(
+            &config,
+            Arc::new(MockAccount::new()),
+            vec![create_test_batch(10)],
+        );
+
+        let mut events = Box::pin(executor.execute_stream());
+        let first = events.next().await.unwrap().unwrap();
+        assert_eq!(first.callback_type, CallbackType::Executing);
+        // Drive execution while it polls for the receipt, then drop the stream
+        let next = tokio::time::timeout(Duration::from_millis(50), events.next()).await;
+        assert!(next.is_err());
+        assert!(!bundler.methods().is_empty());
+        drop(events);
+
+        // Allow an in-flight request to land before counting
+        sleep(Duration::from_millis(20)).await;
+        let polls = bundler.methods().len();
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(bundler.methods().len(), polls);
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_unsigned() {
+        let mut batch = create_test_batch(10);
+        batch.user_op.signature = Hex::new();
+        let config = create_test_config(vec![10]);
+        let executor = create_executor(&config, Arc::new(MockAccount::new()), vec![batch]);
+
+        assert!(matches!(
+            executor.execute(|_| {}).await,
+            Err(crate::EilError::UserOpNotSigned)
+        ));
+    }
 }
@@ -0,0 +1,244 @@
+//! Voucher fee preview
+//!
+//! Vouchers are priced by a Dutch auction: the XLP fee starts at
+//! `start_fee_percent_numerator` when the voucher request is created and grows by
+//! `fee_increase_per_second` until it reaches `max_fee_percent_numerator`. Numerators
+//! are out of [`FEE_DENOMINATOR`].
+
+use crate::{
+    config::FeeConfig,
+    contract_types::{AtomicSwapFeeRule, SdkVoucherRequest},
+    types::*,
+    utils::fee_percent_to_numerator,
+    Result,
+};
+use alloy::primitives::U256;
+use serde::{Deserialize, Serialize};
+
+/// Denominator of the fee percentage numerators
+pub const FEE_DENOMINATOR: u64 = 10_000;
+
+impl AtomicSwapFeeRule {
+    /// Build the fee rule from a [`FeeConfig`], rejecting out-of-range percentages
+    pub fn from_config(config: &FeeConfig) -> Result<Self> {
+        Ok(Self {
+            start_fee_percent_numerator: fee_percent_to_numerator(config.start_fee_percent)?,
+            max_fee_percent_numerator: fee_percent_to_numerator(config.max_fee_percent)?,
+            fee_increase_per_second: fee_percent_to_numerator(config.fee_increase_per_second)?,
+            unspent_voucher_fee: fee_percent_to_numerator(config.unspent_voucher_fee_percent)?,
+        })
+    }
+
+    /// Fee numerator `elapsed_seconds` after the voucher request was created
+    pub fn fee_numerator_at(&self, elapsed_seconds: u64) -> U256 {
+        let increase = self
+            .fee_increase_per_second
+            .saturating_mul(U256::from(elapsed_seconds));
+        self.start_fee_percent_numerator
+            .saturating_add(increase)
+            .min(self.max_fee_percent_numerator)
+    }
+
+    /// Fee on `amount` if the voucher is issued `elapsed_seconds` after its request
+    pub fn fee_at(&self, amount: U256, elapsed_seconds: u64) -> U256 {
+        apply_numerator(amount, self.fee_numerator_at(elapsed_seconds))
+    }
+
+    /// Highest fee on `amount`, once the auction reached its maximum
+    pub fn max_fee(&self, amount: U256) -> U256 {
+        apply_numerator(amount, self.max_fee_percent_numerator)
+    }
+
+    /// Fee on `amount` charged when the voucher is issued but never spent
+    pub fn unspent_fee(&self, amount: U256) -> U256 {
+        apply_numerator(amount, self.unspent_voucher_fee)
+    }
+
+    /// Seconds until the fee reaches its maximum
+    /// `None` if the fee never increases up to the maximum.
+    pub fn seconds_to_max_fee(&self) -> Option<u64> {
+        let remaining = self
+            .max_fee_percent_numerator
+            .saturating_sub(self.start_fee_percent_numerator);
+        if remaining.is_zero() {
+            return Some(0);
+        }
+        if self.fee_increase_per_second.is_zero() {
+            return None;
+        }
+        remaining
+            .div_ceil(self.fee_increase_per_second)
+            .try_into()
+            .ok()
+    }
+}
+
+fn apply_numerator(amount: U256, numerator: U256) -> U256 {
+    amount.saturating_mul(numerator) / U256::from(FEE_DENOMINATOR)
+}
+
+/// Fee preview of one token of a voucher
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenFeeQuote {
+    /// Token name
+    pub token: String,
+    /// Token address on the source chain
+    pub token_address: Option<Address>,
+    /// Amount locked in the voucher
+    pub amount: U256,
+    /// Fee if the voucher is issued at the quoted time
+    pub expected_fee: U256,
+    /// Fee once the auction reached its maximum
+    pub max_fee: U256,
+    /// Fee charged if the voucher is issued but never spent
+    pub unspent_fee: U256,
+}
+
+/// Fee preview of a voucher request
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoucherFeeQuote {
+    /// Voucher reference ID
+    pub ref_id: String,
+    /// Chain the voucher is requested on
+    pub source_chain_id: Option<ChainId>,
+    /// Chain the voucher is spent on
+    pub destination_chain_id: ChainId,
+    /// Seconds after the voucher request the expected fees are quoted at
+    pub elapsed_seconds: u64,
+    /// Seconds until the fee reaches its maximum, `None` if it never does
+    pub seconds_to_max_fee: Option<u64>,
+    /// Fees per token
+    pub tokens: Vec<TokenFeeQuote>,
+}
+
+/// Quote the fees of a voucher request, `elapsed_seconds` after it is created
+pub fn quote_voucher_fee(
+    voucher: &SdkVoucherRequest,
+    rule: &AtomicSwapFeeRule,
+    elapsed_seconds: u64,
+) -> VoucherFeeQuote {
+    let tokens = voucher
+        .tokens
+        .iter()
+        .map(|token| {
+            let amount = token.voucher_amount();
+            TokenFeeQuote {
+                token: token.token.name.clone(),
+                token_address: voucher
+                    .source_chain_id
+                    .and_then(|chain_id| token.token.address_on(chain_id)),
+                amount,
+                expected_fee: rule.fee_at(amount, elapsed_seconds),
+                max_fee: rule.max_fee(amount),
+                unspent_fee: rule.unspent_fee(amount),
+            }
+        })
+        .collect();
+
+    VoucherFeeQuote {
+        ref_id: voucher.ref_id.clone(),
+        source_chain_id: voucher.source_chain_id,
+        destination_chain_id: voucher.destination_chain_id,
+        elapsed_seconds,
+        seconds_to_max_fee: rule.seconds_to_max_fee(),
+        tokens,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::multichain::MultichainToken;
+    use std::collections::HashMap;
+
+    fn create_rule() -> AtomicSwapFeeRule {
+        // 0.1% rising by 0.01% per second up to 5%, 0.1% if unspent
+        AtomicSwapFeeRule::from_config(&FeeConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_fee_rule_from_config() {
+        let rule = create_rule();
+        assert_eq!(rule.start_fee_percent_numerator, U256::from(10));
+        assert_eq!(rule.max_fee_percent_numerator, U256::from(500));
+        assert_eq!(rule.fee_increase_per_second, U256::from(1));
+        assert_eq!(rule.unspent_voucher_fee, U256::from(10));
+
+        let config = FeeConfig {
+            max_fee_percent: 1.5,
+            ..FeeConfig::default()
+        };
+        assert!(matches!(
+            AtomicSwapFeeRule::from_config(&config),
+            Err(crate::EilError::InvalidFeePercent(_))
+        ));
+        assert!(fee_percent_to_numerator(-0.1).is_err());
+        assert!(fee_percent_to_numerator(f64::NAN).is_err());
+        // Float error is tolerated, not truncated
+        assert_eq!(fee_percent_to_numerator(0.0029).unwrap(), U256::from(29));
+        // Finer than one basis point
+        assert!(matches!(
+            fee_percent_to_numerator(0.00015),
+            Err(crate::EilError::InvalidFeePercent(_))
+        ));
+    }
+
+    #[test]
+    fn test_fee_increases_up_to_max() {
+        let rule = create_rule();
+        let amount = U256::from(1_000_000);
+
+        assert_eq!(rule.fee_at(amount, 0), U256::from(1_000));
+        assert_eq!(rule.fee_at(amount, 90), U256::from(10_000));
+        assert_eq!(rule.fee_at(amount, 490), U256::from(50_000));
+        assert_eq!(rule.fee_at(amount, u64::MAX), U256::from(50_000));
+        assert_eq!(rule.max_fee(amount), U256::from(50_000));
+        assert_eq!(rule.unspent_fee(amount), U256::from(1_000));
+    }
+
+    #[test]
+    fn test_seconds_to_max_fee() {
+        let mut rule = create_rule();
+        assert_eq!(rule.seconds_to_max_fee(), Some(490));
+
+        rule.fee_increase_per_second = U256::from(3);
+        assert_eq!(rule.seconds_to_max_fee(), Some(164));
+
+        rule.fee_increase_per_second = U256::ZERO;
+        assert_eq!(rule.seconds_to_max_fee(), None);
+
+        rule.start_fee_percent_numerator = rule.max_fee_percent_numerator;
+        assert_eq!(rule.seconds_to_max_fee(), Some(0));
+    }
+
+    #[test]
+    fn test_quote_voucher_fee() {
+        let token = MultichainToken::new(
+            "USDC".to_string(),
+            HashMap::from([(10, Address::repeat_byte(0x44))]),
+        );
+        let voucher = SdkVoucherRequest {
+            ref_id: "v1".to_string(),
+            source_chain_id: Some(10),
+            destination_chain_id: 20,
+            tokens: vec![TokenAmount {
+                token,
+                amount: Amount::Fixed(U256::from(2_000_000)),
+                min_provider_deposit: None,
+            }],
+            target: None,
+        };
+
+        let quote = quote_voucher_fee(&voucher, &create_rule(), 40);
+
+        assert_eq!(quote.ref_id, "v1");
+        assert_eq!(quote.seconds_to_max_fee, Some(490));
+        let fee = &quote.tokens[0];
+        assert_eq!(fee.token_address, Some(Address::repeat_byte(0x44)));
+        assert_eq!(fee.expected_fee, U256::from(10_000));
+        assert_eq!(fee.max_fee, U256::from(100_000));
+        assert_eq!(fee.unspent_fee, U256::from(2_000));
+    }
+}
@@ -0,0 +1,557 @@
+//! UserOperation gas estimation and EIP-1559 fee pricing
+//!
+//! Gas limits come from the bundler's `eth_estimateUserOperationGas` when a bundler
+//! serves the chain, otherwise from `eth_estimateGas` on the account
+//! call plus the fixed overheads of [`GasConfig`]. Fees come from `eth_feeHistory`.
+//! On rollups, the L1 data fee is priced according to the chain's [`L2Kind`].
+
+use crate::{
+    config::{GasConfig, L2Kind},
+    contract_types::UserOperation,
+    contracts::{IGasPriceOracle, INodeInterface},
+    network::NetworkEnvironment,
+    types::*,
+    utils::scale_ceil,
+    Result,
+};
+use alloy::{
+    eips::BlockNumberOrTag,
+    primitives::{address, U256},
+    providers::Provider,
+    rpc::types::{FeeHistory, TransactionRequest},
+    sol_types::SolValue,
+};
+use serde::{Deserialize, Serialize};
+
+/// OP Stack `GasPriceOracle` predeploy address
+pub const OP_GAS_PRICE_ORACLE: Address = address!("420000000000000000000000000000000000000F");
+
+/// Arbitrum `NodeInterface` precompile address
+pub const ARBITRUM_NODE_INTERFACE: Address = address!("00000000000000000000000000000000000000C8");
+
+/// Signature used while estimating gas, before the UserOperation is signed
+pub const DUMMY_SIGNATURE: [u8; 65] = {
+    let mut signature = [0xff; 65];
+    signature[64] = 0x1c;
+    signature
+};
+
+/// Source of the gas limits in a [`GasEstimate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GasEstimateSource {
+    /// Bundler `eth_estimateUserOperationGas`
+    Bundler,
+    /// Node `eth_estimateGas` plus fixed overheads
+    Rpc,
+}
+
+/// EIP-1559 fees per gas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeEstimate {
+    /// Max fee per gas
+    pub max_fee_per_gas: U256,
+    /// Max priority fee per gas
+    pub max_priority_fee_per_gas: U256,
+}
+
+/// Estimated gas limits and fees of a UserOperation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasEstimate {
+    /// Gas limit for the execution phase
+    pub call_gas_limit: U256,
+    /// Gas limit for the verification phase
+    pub verification_gas_limit: U256,
+    /// Gas overhead for pre-verification
+    pub pre_verification_gas: U256,
+    /// Paymaster verification gas limit (if estimated by the bundler)
+    pub paymaster_verification_gas_limit: Option<U256>,
+    /// Paymaster post-op gas limit (if estimated by the bundler)
+    pub paymaster_post_op_gas_limit: Option<U256>,
+    /// Fees per gas
+    pub fees: FeeEstimate,
+    /// Where the gas limits came from
+    pub source: GasEstimateSource,
+}
+
+impl GasEstimate {
+    /// Set the estimated gas limits and fees on a UserOperation
+    /// Paymaster limits are only overwritten when estimated
+    pub fn apply_to(&self, user_op: &mut UserOperation) {
+        user_op.call_gas_limit = self.call_gas_limit;
+        user_op.verification_gas_limit = self.verification_gas_limit;
+        user_op.pre_verification_gas = self.pre_verification_gas;
+        user_op.max_fee_per_gas = self.fees.max_fee_per_gas;
+        user_op.max_priority_fee_per_gas = self.fees.max_priority_fee_per_gas;
+        if self.paymaster_verification_gas_limit.is_some() {
+            user_op.paymaster_verification_gas_limit = self.paymaster_verification_gas_limit;
+        }
+        if self.paymaster_post_op_gas_limit.is_some() {
+            user_op.paymaster_post_op_gas_limit = self.paymaster_post_op_gas_limit;
+        }
+    }
+
+    /// Total gas the UserOperation may consume
+    pub fn total_gas(&self) -> U256 {
+        self.call_gas_limit
+            + self.verification_gas_limit
+            + self.pre_verification_gas
+            + self.paymaster_verification_gas_limit.unwrap_or_default()
+            + self.paymaster_post_op_gas_limit.unwrap_or_default()
+    }
+
+    /// Maximum cost of the UserOperation in wei
+    pub fn max_cost(&self) -> U256 {
+        self.total_gas() * self.fees.max_fee_per_gas
+    }
+}
+
+/// Compute fees from an `eth_feeHistory` response
+///
+/// The priority fee is the mean of the sampled percentile rewards; the max fee is
+/// the next block base fee plus the priority fee, each scaled by its multiplier.
+pub fn fees_from_history(history: &FeeHistory, config: &GasConfig) -> Result<FeeEstimate> {
+    let base_fee = history.next_block_base_fee().ok_or_else(|| {
+        crate::EilError::AlloyProvider("eth_feeHistory returned no base fee".into())
+    })?;
+
+    let rewards: Vec<u128> = history
+        .reward
+        .iter()
+        .flatten()
+        .filter_map(|block| block.first().copied())
+        .collect();
+    let priority_fee = if rewards.is_empty() {
+        0
+    } else {
+        rewards.iter().sum::<u128>() / rewards.len() as u128
+    };
+
+    let max_priority_fee_per_gas =
+        scale_ceil(U256::from(priority_fee), config.priority_fee_multiplier);
+    let max_fee_per_gas =
+        scale_ceil(U256::from(base_fee), config.base_fee_multiplier) + max_priority_fee_per_gas;
+
+    Ok(FeeEstimate {
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+    })
+}
+
+/// Fetch fee history for a chain and compute fees
+pub async fn estimate_fees(
+    network_env: &NetworkEnvironment,
+    chain_id: ChainId,
+    config: &GasConfig,
+) -> Result<FeeEstimate> {
+    let provider = network_env.create_provider(chain_id).await?;
+    let history = provider
+        .get_fee_history(
+            config.fee_history_blocks,
+            BlockNumberOrTag::Latest,
+            &[config.reward_percentile],
+        )
+        .await
+        .map_err(|e| crate::EilError::AlloyProvider(format!("eth_feeHistory: {}", e)))?;
+    fees_from_history(&history, config)
+}
+
+/// Estimate gas limits and fees for a UserOperation
+///
+/// `user_op.chain_id` and `user_op.entry_point_address` must be set. An empty
+/// signature is replaced by [`DUMMY_SIGNATURE`] for the estimation.
+pub async fn estimate_user_op_gas(
+    network_env: &NetworkEnvironment,
+    user_op: &UserOperation,
+    config: &GasConfig,
+) -> Result<GasEstimate> {
+    let chain_id = user_op
+        .chain_id
+        .ok_or_else(|| crate::EilError::Generic("UserOperation chain ID not set".into()))?;
+    let entry_point = user_op
+        .entry_point_address
+        .ok_or_else(|| crate::EilError::Generic("EntryPoint address not set".into()))?;
+    let fees = estimate_fees(network_env, chain_id, config).await?;
+
+    if network_env.bundler().is_available(chain_id).await? {
+        let mut user_op = user_op.clone();
+        user_op.max_fee_per_gas = fees.max_fee_per_gas;
+        user_op.max_priority_fee_per_gas = fees.max_priority_fee_per_gas;
+        if user_op.signature.is_empty() {
+            user_op.signature = Hex::from(DUMMY_SIGNATURE);
+        }
+
+        let estimate = network_env
+            .bundler()
+            .estimate_user_operation_gas(chain_id, &user_op, entry_point)
+            .await?;
+        return Ok(GasEstimate {
+            call_gas_limit: scale_ceil(estimate.call_gas_limit, config.gas_limit_multiplier),
+            verification_gas_limit: scale_ceil(
+                estimate.verification_gas_limit,
+                config.gas_limit_multiplier,
+            ),
+            pre_verification_gas: estimate.pre_verification_gas,
+            paymaster_verification_gas_limit: estimate.paymaster_verification_gas_limit,
+            paymaster_post_op_gas_limit: estimate.paymaster_post_op_gas_limit,
+            fees,
+            source: GasEstimateSource::Bundler,
+        });
+    }
+
+    let provider = network_env.create_provider(chain_id).await?;
+    let request = TransactionRequest::default()
+        .from(entry_point)
+        .to(user_op.sender)
+        .input(user_op.call_data.clone().into());
+    let call_gas = provider
+        .estimate_gas(&request)
+        .await
+        .map_err(|e| crate::EilError::AlloyProvider(format!("eth_estimateGas: {}", e)))?;
+
+    let mut verification_gas = config.verification_gas_overhead;
+    if user_op.factory.is_some() {
+        verification_gas += config.deployment_gas_overhead;
+    }
+
+    Ok(GasEstimate {
+        call_gas_limit: scale_ceil(U256::from(call_gas), config.gas_limit_multiplier),
+        verification_gas_limit: U256::from(verification_gas),
+        pre_verification_gas: U256::from(config.pre_verification_gas_overhead),
+        paymaster_verification_gas_limit: None,
+        paymaster_post_op_gas_limit: None,
+        fees,
+        source: GasEstimateSource::Rpc,
+    })
+}
+
+/// Estimate the L1 data fee of submitting a UserOperation on a rollup
+///
+/// The fee is priced on the ABI-encoded packed UserOperation, with
+/// [`DUMMY_SIGNATURE`] standing in for an empty signature. Returns zero on L1.
+pub async fn estimate_l1_data_fee(
+    network_env: &NetworkEnvironment,
+    user_op: &UserOperation,
+) -> Result<U256> {
+    let chain_id = user_op
+        .chain_id
+        .ok_or_else(|| crate::EilError::Generic("UserOperation chain ID not set".into()))?;
+    let l2_kind = network_env
+        .config()
+        .chain_info(chain_id)
+        .ok_or(crate::EilError::UnsupportedChain(chain_id))?
+        .l2_kind;
+    if l2_kind == L2Kind::L1 {
+        return Ok(U256::ZERO);
+    }
+
+    let mut user_op = user_op.clone();
+    if user_op.signature.is_empty() {
+        user_op.signature = Hex::from(DUMMY_SIGNATURE);
+    }
+    let data = Hex::from(crate::user_op::pack_user_op(&user_op)?.abi_encode());
+    let provider = network_env.create_provider(chain_id).await?;
+
+    match l2_kind {
+        L2Kind::L1 => Ok(U256::ZERO),
+        L2Kind::OpStack => IGasPriceOracle::new(OP_GAS_PRICE_ORACLE, provider)
+            .getL1Fee(data)
+            .call()
+            .await
+            .map(|fee| fee._0)
+            .map_err(|e| crate::EilError::AlloyContract(format!("getL1Fee: {}", e))),
+        L2Kind::Arbitrum => {
+            let to = user_op
+                .entry_point_address
+                .ok_or_else(|| crate::EilError::Generic("EntryPoint address not set".into()))?;
+            let component = INodeInterface::new(ARBITRUM_NODE_INTERFACE, provider)
+                .gasEstimateL1Component(to, false, data)
+                .call()
+                .await
+                .map_err(|e| {
+                    crate::EilError::AlloyContract(format!("gasEstimateL1Component: {}", e))
+                })?;
+            Ok(U256::from(component.gasEstimateForL1) * component.baseFee)
+        }
+    }
+}
+
+/// Maximum cost of a UserOperation including its L1 data fee
+/// `(max gas cost + L1 data fee) * user_op_cost_margin`, rounded up
+pub async fn max_user_op_cost(
+    network_env: &NetworkEnvironment,
+    user_op: &UserOperation,
+    estimate: &GasEstimate,
+    config: &GasConfig,
+) -> Result<U256> {
+    let l1_data_fee = estimate_l1_data_fee(network_env, user_op).await?;
+    Ok(scale_ceil(
+        estimate.max_cost() + l1_data_fee,
+        config.user_op_cost_margin,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{create_test_config, create_test_user_op, MockRpcServer};
+
+    const GWEI: u128 = 1_000_000_000;
+
+    fn fee_history_json() -> serde_json::Value {
+        serde_json::json!({
+            "oldestBlock": "0x10",
+            "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00", "0x77359400"],
+            "gasUsedRatio": [0.5, 0.5],
+            "reward": [["0x3b9aca00"], ["0x77359400"]],
+        })
+    }
+
+    #[test]
+    fn test_fees_from_history() {
+        let history: FeeHistory = serde_json::from_value(fee_history_json()).unwrap();
+        let config = GasConfig {
+            base_fee_multiplier: 2.0,
+            priority_fee_multiplier: 1.0,
+            ..Default::default()
+        };
+
+        let fees = fees_from_history(&history, &config).unwrap();
+        // Mean reward of 1 and 2 gwei
+        assert_eq!(fees.max_priority_fee_per_gas, U256::from(3 * GWEI / 2));
+        // 2 * next base fee (2 gwei) + priority fee
+        assert_eq!(fees.max_fee_per_gas, U256::from(4 * GWEI + 3 * GWEI / 2));
+    }
+
+    #[test]
+    fn test_fees_from_history_without_rewards() {
+        let history = FeeHistory {
+            base_fee_per_gas: vec![GWEI],
+            ..Default::default()
+        };
+
+        let fees = fees_from_history(&history, &GasConfig::default()).unwrap();
+        assert_eq!(fees.max_priority_fee_per_gas, U256::ZERO);
+        assert_eq!(fees.max_fee_per_gas, U256::from(2 * GWEI));
+    }
+
+    #[test]
+    fn test_fees_from_history_missing_base_fee() {
+        assert!(fees_from_history(&FeeHistory::default(), &GasConfig::default()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_estimate_user_op_gas_rpc_fallback() {
+        let server = MockRpcServer::start(|method, _| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            "eth_feeHistory" => Ok(fee_history_json()),
+            "eth_estimateGas" => Ok(serde_json::json!("0x186a0")),
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].rpc_url = server.url.clone();
+        let network_env = NetworkEnvironment::new(&config);
+
+        let mut user_op = create_test_user_op(10);
+        user_op.factory = Some(Address::repeat_byte(0xfa));
+        let estimate = estimate_user_op_gas(&network_env, &user_op, &GasConfig::default())
+            .await
+            .unwrap();
+
+        assert_eq!(estimate.source, GasEstimateSource::Rpc);
+        assert_eq!(estimate.call_gas_limit, U256::from(120_000));
+        assert_eq!(estimate.verification_gas_limit, U256::from(450_000));
+        assert_eq!(estimate.pre_verification_gas, U256::from(60_000));
+        assert!(!server
+            .methods()
+            .contains(&"eth_estimateUserOperationGas".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_user_op_gas_bundler() {
+        let rpc = MockRpcServer::start(|method, _| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            "eth_feeHistory" => Ok(fee_history_json()),
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let bundler = MockRpcServer::start(|method, _| match method {
+            "eth_estimateUserOperationGas" => Ok(serde_json::json!({
+                "preVerificationGas": "0xc350",
+                "verificationGasLimit": "0x186a0",
+                "callGasLimit": "0x30d40",
+                "paymasterVerificationGasLimit": "0x7530",
+            })),
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].rpc_url = rpc.url.clone();
+        config.chain_infos[0].bundler_url = Some(bundler.url.clone());
+        let network_env = NetworkEnvironment::new(&config);
+
+        let gas_config = GasConfig {
+            gas_limit_multiplier: 1.0,
+            ..Default::default()
+        };
+        let estimate = estimate_user_op_gas(&network_env, &create_test_user_op(10), &gas_config)
+            .await
+            .unwrap();
+
+        assert_eq!(estimate.source, GasEstimateSource::Bundler);
+        assert_eq!(estimate.call_gas_limit, U256::from(200_000));
+        assert_eq!(estimate.verification_gas_limit, U256::from(100_000));
+        assert_eq!(estimate.pre_verification_gas, U256::from(50_000));
+        assert_eq!(
+            estimate.paymaster_verification_gas_limit,
+            Some(U256::from(30_000))
+        );
+
+        // The bundler receives the priced UserOperation with a dummy signature
+        let requests = bundler.requests.lock().unwrap();
+        let sent = &requests[0].1[0];
+        assert_eq!(
+            sent["signature"],
+            serde_json::json!(Hex::from(DUMMY_SIGNATURE))
+        );
+        assert_eq!(
+            sent["maxFeePerGas"],
+            serde_json::json!(estimate.fees.max_fee_per_gas)
+        );
+    }
+
+    #[test]
+    fn test_gas_estimate_apply_and_cost() {
+        let estimate = GasEstimate {
+            call_gas_limit: U256::from(100),
+            verification_gas_limit: U256::from(200),
+            pre_verification_gas: U256::from(50),
+            paymaster_verification_gas_limit: None,
+            paymaster_post_op_gas_limit: Some(U256::from(10)),
+            fees: FeeEstimate {
+                max_fee_per_gas: U256::from(3),
+                max_priority_fee_per_gas: U256::from(1),
+            },
+            source: GasEstimateSource::Rpc,
+        };
+        let mut user_op = create_test_user_op(10);
+        user_op.paymaster_verification_gas_limit = Some(U256::from(7));
+        estimate.apply_to(&mut user_op);
+
+        assert_eq!(user_op.call_gas_limit, U256::from(100));
+        assert_eq!(user_op.max_fee_per_gas, U256::from(3));
+        assert_eq!(
+            user_op.paymaster_verification_gas_limit,
+            Some(U256::from(7))
+        );
+        assert_eq!(user_op.paymaster_post_op_gas_limit, Some(U256::from(10)));
+        assert_eq!(estimate.max_cost(), U256::from(360 * 3));
+    }
+
+    fn create_l2_env(url: &str, l2_kind: L2Kind) -> NetworkEnvironment {
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].rpc_url = url.to_string();
+        config.chain_infos[0].l2_kind = l2_kind;
+        NetworkEnvironment::new(&config)
+    }
+
+    fn create_test_estimate() -> GasEstimate {
+        GasEstimate {
+            call_gas_limit: U256::from(100_000),
+            verification_gas_limit: U256::from(100_000),
+            pre_verification_gas: U256::from(50_000),
+            paymaster_verification_gas_limit: None,
+            paymaster_post_op_gas_limit: None,
+            fees: FeeEstimate {
+                max_fee_per_gas: U256::from(GWEI),
+                max_priority_fee_per_gas: U256::from(GWEI),
+            },
+            source: GasEstimateSource::Rpc,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_l1_data_fee_on_l1_is_zero() {
+        let network_env = NetworkEnvironment::new(&create_test_config(vec![10]));
+        let fee = estimate_l1_data_fee(&network_env, &create_test_user_op(10))
+            .await
+            .unwrap();
+        assert_eq!(fee, U256::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_l1_data_fee_op_stack() {
+        let server = MockRpcServer::start(|method, _| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            "eth_call" => Ok(serde_json::json!(Hex::from(U256::from(5_000).abi_encode()))),
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let network_env = create_l2_env(&server.url, L2Kind::OpStack);
+
+        let fee = estimate_l1_data_fee(&network_env, &create_test_user_op(10))
+            .await
+            .unwrap();
+        assert_eq!(fee, U256::from(5_000));
+
+        let requests = server.requests.lock().unwrap();
+        let (_, params) = requests.iter().find(|(m, _)| m == "eth_call").unwrap();
+        let (to, input) = crate::test_utils::call_request(params);
+        assert_eq!(to, OP_GAS_PRICE_ORACLE);
+        let data =
+            <IGasPriceOracle::getL1FeeCall as alloy::sol_types::SolCall>::abi_decode(&input, true)
+                .unwrap()
+                .data;
+        let packed = crate::user_op::PackedUserOperation::abi_decode(&data, true).unwrap();
+        assert_eq!(packed.signature, Hex::from(DUMMY_SIGNATURE));
+    }
+
+    #[tokio::test]
+    async fn test_l1_data_fee_arbitrum() {
+        let server = MockRpcServer::start(|method, _| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            "eth_call" => Ok(serde_json::json!(Hex::from(
+                (300u64, U256::from(10), U256::from(7)).abi_encode_params()
+            ))),
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let network_env = create_l2_env(&server.url, L2Kind::Arbitrum);
+
+        let fee = estimate_l1_data_fee(&network_env, &create_test_user_op(10))
+            .await
+            .unwrap();
+        assert_eq!(fee, U256::from(3_000));
+    }
+
+    #[tokio::test]
+    async fn test_max_user_op_cost() {
+        let server = MockRpcServer::start(|method, _| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            "eth_call" => Ok(serde_json::json!(Hex::from(
+                U256::from(GWEI * 50_000).abi_encode()
+            ))),
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let network_env = create_l2_env(&server.url, L2Kind::OpStack);
+        let gas_config = GasConfig {
+            user_op_cost_margin: 1.5,
+            ..Default::default()
+        };
+
+        let cost = max_user_op_cost(
+            &network_env,
+            &create_test_user_op(10),
+            &create_test_estimate(),
+            &gas_config,
+        )
+        .await
+        .unwrap();
+        // (250k gas + 50k gas worth of data fee) at 1 gwei, plus 50%
+        assert_eq!(cost, U256::from(GWEI * 450_000));
+    }
+}
@@ -38,12 +38,26 @@
 pub mod types;
 pub mod config;
 pub mod contract_types;
+pub mod contracts;
 pub mod multichain;
 pub mod actions;
+pub mod runtime_vars;
 pub mod voucher;
 pub mod builder;
 pub mod executor;
 pub mod account;
+pub mod simple_account;
+pub mod erc7579_account;
+pub mod bundler;
+pub mod gas;
+pub mod fees;
+pub mod user_op;
+pub mod merkle;
+pub mod planner;
+pub mod revert;
+pub mod session;
+pub mod simulation;
+pub mod store;
 pub mod network;
 pub mod utils;
 
@@ -83,6 +97,11 @@ impl EilSdk {
         multichain::MultichainToken::new(name.into(), deployments)
     }
 
+    /// Get the configuration
+    pub fn config(&self) -> &config::CrossChainConfig {
+        &self.config
+    }
+
     /// Get the network environment
     pub fn network_env(&self) -> &network::NetworkEnvironment {
         &self.network_env
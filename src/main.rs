@@ -20,7 +20,9 @@ async fn main() -> eil::Result<()> {
     let sdk = EilSdk::new(config);
 
     // 3. Create USDC token (deployed on multiple chains)
-    let usdc = sdk.create_token("USDC", create_usdc_deployments());
+    let usdc = sdk
+        .create_token("USDC", create_usdc_deployments())
+        .with_decimals(6);
 
     println!("✓ SDK initialized");
     println!("✓ USDC token configured on {} chains\n", usdc.deployments.len());
@@ -44,7 +46,7 @@ async fn main() -> eil::Result<()> {
                 destination_chain_id: chain_ids::ARBITRUM,
                 tokens: vec![TokenAmount {
                     token: usdc.clone(),
-                    amount: Amount::Fixed(U256::from(90_000000)), // 90 USDC (6 decimals)
+                    amount: Amount::Fixed(usdc.parse_amount("90 USDC")?),
                     min_provider_deposit: None,
                 }],
                 target: None, // Use same address on destination
@@ -56,7 +58,7 @@ async fn main() -> eil::Result<()> {
             .add_action(ApproveAction {
                 token: usdc.clone(),
                 spender: nft_marketplace_address(),
-                value: Amount::Fixed(U256::from(90_000000)),
+                value: Amount::Fixed(usdc.parse_amount("90 USDC")?),
             })
             .add_action(FunctionCallAction {
                 call: create_purchase_nft_call(123),
@@ -124,6 +126,11 @@ fn setup_config() -> CrossChainConfig {
                 .parse()
                 .unwrap(),
             bundler_url: None,
+            runtime_vars_helper: None,
+            l2_kind: L2Kind::OpStack,
+            entry_point_simulations: None,
+            multicall3: None,
+            permit2: None,
         },
         ChainInfo {
             chain_id: chain_ids::ARBITRUM,
@@ -135,6 +142,11 @@ fn setup_config() -> CrossChainConfig {
                 .parse()
                 .unwrap(),
             bundler_url: None,
+            runtime_vars_helper: None,
+            l2_kind: L2Kind::Arbitrum,
+            entry_point_simulations: None,
+            multicall3: None,
+            permit2: None,
         },
     ])
     .with_expire_time(60)
@@ -165,6 +177,7 @@ fn create_usdc_deployments() -> AddressPerChain {
 }
 
 /// NFT marketplace address (example)
+#[allow(dead_code)]
 fn nft_marketplace_address() -> Address {
     "0x0000000000000000000000000000000000000001"
         .parse()
@@ -172,6 +185,7 @@ fn nft_marketplace_address() -> Address {
 }
 
 /// Create purchase NFT function call (example)
+#[allow(dead_code)]
 fn create_purchase_nft_call(token_id: u64) -> FunctionCall {
     use alloy::json_abi::JsonAbi;
 
@@ -0,0 +1,235 @@
+//! Merkle tree over UserOperation hashes for single-signature multi-chain signing
+//!
+//! The account signs only the Merkle root once, and each UserOperation carries the
+//! root signature together with a proof that its userOpHash is a leaf of the tree.
+//! Pairs are hashed in sorted order, matching OpenZeppelin's `MerkleProof` library.
+
+use crate::{types::*, Result};
+use alloy::{
+    primitives::{keccak256, B256},
+    sol_types::SolValue,
+};
+
+/// Merkle tree built from a list of leaves
+#[derive(Debug, Clone)]
+pub struct MerkleTree {
+    /// Tree layers, from the leaves (index 0) up to the root
+    layers: Vec<Vec<B256>>,
+}
+
+impl MerkleTree {
+    /// Build a Merkle tree from the given leaves
+    /// A node without a sibling is promoted unchanged to the next layer
+    pub fn new(leaves: Vec<B256>) -> Result<Self> {
+        if leaves.is_empty() {
+            return Err(crate::EilError::Generic(
+                "Cannot build a Merkle tree without leaves".into(),
+            ));
+        }
+
+        let mut layers = vec![leaves];
+        while layers[layers.len() - 1].len() > 1 {
+            let next = layers[layers.len() - 1]
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_pair(*left, *right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            layers.push(next);
+        }
+
+        Ok(Self { layers })
+    }
+
+    /// Get the Merkle root
+    pub fn root(&self) -> B256 {
+        self.layers.last().unwrap()[0]
+    }
+
+    /// Get the leaves of the tree
+    pub fn leaves(&self) -> &[B256] {
+        &self.layers[0]
+    }
+
+    /// Get the proof for the leaf at the given index
+    pub fn proof(&self, index: usize) -> Result<Vec<B256>> {
+        if index >= self.leaves().len() {
+            return Err(crate::EilError::Generic(format!(
+                "Merkle leaf index {} out of bounds",
+                index
+            )));
+        }
+
+        let mut proof = Vec::new();
+        let mut index = index;
+        for layer in &self.layers[..self.layers.len() - 1] {
+            let sibling = index ^ 1;
+            if sibling < layer.len() {
+                proof.push(layer[sibling]);
+            }
+            index /= 2;
+        }
+        Ok(proof)
+    }
+}
+
+/// Hash a pair of nodes in sorted order
+pub fn hash_pair(a: B256, b: B256) -> B256 {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut data = [0u8; 64];
+    data[..32].copy_from_slice(first.as_slice());
+    data[32..].copy_from_slice(second.as_slice());
+    keccak256(data)
+}
+
+/// Compute the root implied by a leaf and its proof
+pub fn process_proof(leaf: B256, proof: &[B256]) -> B256 {
+    proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(node, *sibling))
+}
+
+/// Verify that a leaf belongs to the tree with the given root
+pub fn verify_proof(leaf: B256, proof: &[B256], root: B256) -> bool {
+    process_proof(leaf, proof) == root
+}
+
+/// Encode a UserOperation signature carrying the root signature and a Merkle proof
+/// Layout: `abi.encode(bytes32 merkleRoot, bytes32[] proof, bytes rootSignature)`
+pub fn encode_signature(root: B256, proof: &[B256], root_signature: &Hex) -> Hex {
+    (root, proof.to_vec(), root_signature.clone())
+        .abi_encode_params()
+        .into()
+}
+
+/// Decode a UserOperation signature produced by [`encode_signature`]
+/// Returns the Merkle root, the proof and the root signature
+pub fn decode_signature(signature: &Hex) -> Result<(B256, Vec<B256>, Hex)> {
+    <(B256, Vec<B256>, Hex)>::abi_decode_params(signature, true)
+        .map_err(|e| crate::EilError::Generic(format!("Invalid Merkle signature encoding: {}", e)))
+}
+
+/// Verify that a UserOperation signature commits to the given userOpHash
+/// Returns the Merkle root and the root signature on success
+pub fn verify_signature(user_op_hash: B256, signature: &Hex) -> Result<(B256, Hex)> {
+    let (root, proof, root_signature) = decode_signature(signature)?;
+    if !verify_proof(user_op_hash, &proof, root) {
+        return Err(crate::EilError::Generic(format!(
+            "Merkle proof for userOpHash {} does not match root {}",
+            user_op_hash, root
+        )));
+    }
+    Ok((root, root_signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: u8) -> Vec<B256> {
+        (0..count).map(|i| keccak256([i])).collect()
+    }
+
+    #[test]
+    fn test_merkle_tree_empty() {
+        assert!(MerkleTree::new(vec![]).is_err());
+    }
+
+    #[test]
+    fn test_merkle_tree_single_leaf() {
+        let leaves = leaves(1);
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+
+        assert_eq!(tree.root(), leaves[0]);
+        assert!(tree.proof(0).unwrap().is_empty());
+        assert!(verify_proof(leaves[0], &[], tree.root()));
+    }
+
+    #[test]
+    fn test_merkle_tree_two_leaves() {
+        let leaves = leaves(2);
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+
+        assert_eq!(tree.root(), hash_pair(leaves[0], leaves[1]));
+        assert_eq!(tree.proof(0).unwrap(), vec![leaves[1]]);
+        assert_eq!(tree.proof(1).unwrap(), vec![leaves[0]]);
+    }
+
+    #[test]
+    fn test_merkle_tree_proofs_verify() {
+        for count in 1..=9 {
+            let leaves = leaves(count);
+            let tree = MerkleTree::new(leaves.clone()).unwrap();
+
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(i).unwrap();
+                assert!(
+                    verify_proof(*leaf, &proof, tree.root()),
+                    "leaf {} of {}",
+                    i,
+                    count
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_merkle_tree_rejects_wrong_leaf() {
+        let leaves = leaves(4);
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let proof = tree.proof(0).unwrap();
+
+        assert!(!verify_proof(leaves[1], &proof, tree.root()));
+        assert!(!verify_proof(keccak256([0xff]), &proof, tree.root()));
+    }
+
+    #[test]
+    fn test_merkle_tree_proof_out_of_bounds() {
+        let tree = MerkleTree::new(leaves(3)).unwrap();
+        assert!(tree.proof(3).is_err());
+    }
+
+    #[test]
+    fn test_hash_pair_is_commutative() {
+        let leaves = leaves(2);
+        assert_eq!(
+            hash_pair(leaves[0], leaves[1]),
+            hash_pair(leaves[1], leaves[0])
+        );
+    }
+
+    #[test]
+    fn test_signature_roundtrip() {
+        let leaves = leaves(3);
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let root_signature = Hex::from(vec![0xab; 65]);
+
+        for (i, leaf) in leaves.iter().enumerate() {
+            let signature = encode_signature(tree.root(), &tree.proof(i).unwrap(), &root_signature);
+            let (root, decoded_signature) = verify_signature(*leaf, &signature).unwrap();
+
+            assert_eq!(root, tree.root());
+            assert_eq!(decoded_signature, root_signature);
+        }
+    }
+
+    #[test]
+    fn test_verify_signature_wrong_hash() {
+        let leaves = leaves(2);
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let signature = encode_signature(
+            tree.root(),
+            &tree.proof(0).unwrap(),
+            &Hex::from(vec![0xab; 65]),
+        );
+
+        assert!(verify_signature(leaves[1], &signature).is_err());
+    }
+
+    #[test]
+    fn test_decode_signature_invalid() {
+        assert!(decode_signature(&Hex::from(vec![0xab; 65])).is_err());
+    }
+}
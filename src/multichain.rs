@@ -1,6 +1,17 @@
-use crate::types::*;
+use crate::{
+    contracts::{IMulticall3, IERC20},
+    network::NetworkEnvironment,
+    types::*,
+    utils::{format_units, parse_units},
+    Result,
+};
+use alloy::eips::BlockId;
 use alloy::json_abi::JsonAbi;
 use alloy::primitives::U256;
+use alloy::providers::Provider;
+use alloy::sol_types::SolCall;
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 pub use crate::types::AddressPerChain;
@@ -37,19 +48,49 @@ impl MultiChainEntity for MultichainContract {
     }
 }
 
+/// Token address standing for a chain's native currency, as in voucher assets
+pub const NATIVE_TOKEN: Address = Address::ZERO;
+
 /// ERC20 token abstraction across multiple chains
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MultichainToken {
     /// Token name/symbol
     pub name: String,
     /// Deployment addresses per chain
     pub deployments: AddressPerChain,
+    /// Token decimals, the same on every chain
+    #[serde(default)]
+    pub decimals: Option<u8>,
 }
 
 impl MultichainToken {
     /// Create a new multichain token
     pub fn new(name: String, deployments: AddressPerChain) -> Self {
-        Self { name, deployments }
+        Self {
+            name,
+            deployments,
+            decimals: None,
+        }
+    }
+
+    /// Native currency (e.g. ETH) of the given chains, with 18 decimals
+    pub fn native(name: impl Into<String>, chain_ids: &[ChainId]) -> Self {
+        let deployments = chain_ids
+            .iter()
+            .map(|chain_id| (*chain_id, NATIVE_TOKEN))
+            .collect();
+        Self::new(name.into(), deployments).with_decimals(18)
+    }
+
+    /// Check if the token is the native currency of a chain
+    pub fn is_native_on(&self, chain_id: ChainId) -> bool {
+        self.address_on(chain_id) == Some(NATIVE_TOKEN)
+    }
+
+    /// Declare the token decimals
+    pub fn with_decimals(mut self, decimals: u8) -> Self {
+        self.decimals = Some(decimals);
+        self
     }
 
     /// Get the token address on a specific chain
@@ -67,6 +108,231 @@ impl MultichainToken {
         // Standard ERC20 ABI
         serde_json::from_str(ERC20_ABI).expect("Failed to parse ERC20 ABI")
     }
+
+    /// Fetch `decimals()` on every configured chain the token is deployed on
+    ///
+    /// Fails if the decimals differ across chains, since the same raw amount would then
+    /// mean different values, or if they differ from the declared decimals.
+    pub async fn fetch_decimals(&mut self, network_env: &NetworkEnvironment) -> Result<u8> {
+        let mut chains: Vec<(ChainId, Address)> = network_env
+            .chain_ids()
+            .into_iter()
+            .filter_map(|chain_id| self.address_on(chain_id).map(|token| (chain_id, token)))
+            .collect();
+        chains.sort_by_key(|(chain_id, _)| *chain_id);
+
+        let decimals = try_join_all(chains.into_iter().map(|(chain_id, token)| async move {
+            if token == NATIVE_TOKEN {
+                return Ok((chain_id, 18));
+            }
+            let provider = network_env.create_provider(chain_id).await?;
+            IERC20::new(token, &provider)
+                .decimals()
+                .call()
+                .await
+                .map(|r| (chain_id, r._0))
+                .map_err(|e| crate::EilError::AlloyContract(format!("decimals: {}", e)))
+        }))
+        .await?;
+
+        let Some(&(_, actual)) = decimals.first() else {
+            return self
+                .decimals
+                .ok_or_else(|| crate::EilError::UnknownDecimals(self.name.clone()));
+        };
+        if decimals.iter().any(|(_, d)| *d != actual) {
+            return Err(crate::EilError::InconsistentDecimals {
+                token: self.name.clone(),
+                decimals,
+            });
+        }
+        if let Some(declared) = self.decimals.filter(|d| *d != actual) {
+            return Err(crate::EilError::DecimalsMismatch {
+                token: self.name.clone(),
+                declared,
+                actual,
+            });
+        }
+        self.decimals = Some(actual);
+        Ok(actual)
+    }
+
+    /// Parse an amount such as `"90.5"` or `"90.5 USDC"` into base units
+    ///
+    /// The token name suffix is matched case-insensitively and may contain spaces, e.g.
+    /// `"90 usdc"` or `"90 USD Coin"`. Amounts with more fractional digits than the token
+    /// decimals are rejected.
+    pub fn parse_amount(&self, amount: &str) -> Result<U256> {
+        let decimals = self.known_decimals()?;
+        let amount = amount.trim();
+        let value = amount
+            .len()
+            .checked_sub(self.name.len())
+            .filter(|&at| {
+                amount
+                    .get(at..)
+                    .is_some_and(|symbol| symbol.eq_ignore_ascii_case(&self.name))
+            })
+            .map(|at| &amount[..at])
+            .filter(|value| value.ends_with(char::is_whitespace));
+        let value = match value {
+            Some(value) => value.trim_end(),
+            None if amount.contains(char::is_whitespace) => {
+                return Err(crate::EilError::InvalidAmount(format!(
+                    "'{}': not an amount of {}",
+                    amount, self.name
+                )))
+            }
+            None => amount,
+        };
+        parse_units(value, decimals)
+    }
+
+    /// Format base units as a readable amount, e.g. `"90.5 USDC"`
+    pub fn format_amount(&self, amount: U256) -> Result<String> {
+        Ok(format!(
+            "{} {}",
+            format_units(amount, self.known_decimals()?),
+            self.name
+        ))
+    }
+
+    fn known_decimals(&self) -> Result<u8> {
+        self.decimals
+            .ok_or_else(|| crate::EilError::UnknownDecimals(self.name.clone()))
+    }
+
+    /// Balance of `account` on every chain the token and the account are both on
+    pub async fn total_balance_of<E: MultiChainEntity + ?Sized>(
+        &self,
+        network_env: &NetworkEnvironment,
+        account: &E,
+    ) -> Result<TotalBalanceOfResult> {
+        self.total_balance_of_at(network_env, account, &HashMap::new())
+            .await
+    }
+
+    /// Balance of `account` on every chain, read at the block given for that chain
+    ///
+    /// Chains missing from `blocks` are read at the latest block. All chains are
+    /// queried concurrently.
+    pub async fn total_balance_of_at<E: MultiChainEntity + ?Sized>(
+        &self,
+        network_env: &NetworkEnvironment,
+        account: &E,
+        blocks: &HashMap<ChainId, BlockNumber>,
+    ) -> Result<TotalBalanceOfResult> {
+        let mut queries: Vec<(ChainId, Address, Address)> = self
+            .deployments
+            .iter()
+            .filter_map(|(&chain_id, &token)| {
+                account
+                    .address_on(chain_id)
+                    .map(|owner| (chain_id, token, owner))
+            })
+            .collect();
+        queries.sort_by_key(|(chain_id, _, _)| *chain_id);
+
+        let per_chain_balance = try_join_all(queries.into_iter().map(
+            |(chain_id, token, owner)| async move {
+                let block = blocks
+                    .get(&chain_id)
+                    .copied()
+                    .unwrap_or(BlockNumber::Latest);
+                let balances = balances_of(network_env, chain_id, &[(token, owner)], block).await?;
+                Ok::<_, crate::EilError>((chain_id, balances[0]))
+            },
+        ))
+        .await?;
+
+        let total_balance = per_chain_balance
+            .iter()
+            .fold(U256::ZERO, |total, (_, balance)| {
+                total.saturating_add(*balance)
+            });
+
+        Ok(TotalBalanceOfResult {
+            per_chain_balance,
+            total_balance,
+        })
+    }
+}
+
+/// Read `balanceOf(owner)` for each `(token, owner)` pair on a chain at `block`
+///
+/// The reads are batched into a single Multicall3 `aggregate3` call when the chain
+/// has `multicall3` configured, and sent as separate calls otherwise. A
+/// [`NATIVE_TOKEN`] query reads the owner's native balance.
+pub async fn balances_of(
+    network_env: &NetworkEnvironment,
+    chain_id: ChainId,
+    queries: &[(Address, Address)],
+    block: BlockNumber,
+) -> Result<Vec<U256>> {
+    let provider = network_env.create_provider(chain_id).await?;
+    let block = BlockId::Number(block);
+
+    let Some(multicall3) = network_env.multicall3(chain_id) else {
+        return try_join_all(queries.iter().map(|&(token, owner)| {
+            let provider = &provider;
+            async move {
+                if token == NATIVE_TOKEN {
+                    return provider
+                        .get_balance(owner)
+                        .block_id(block)
+                        .await
+                        .map_err(|e| {
+                            crate::EilError::AlloyProvider(format!("eth_getBalance: {}", e))
+                        });
+                }
+                IERC20::new(token, provider)
+                    .balanceOf(owner)
+                    .block(block)
+                    .call()
+                    .await
+                    .map(|r| r._0)
+                    .map_err(|e| crate::EilError::AlloyContract(format!("balanceOf: {}", e)))
+            }
+        }))
+        .await;
+    };
+
+    let calls = queries
+        .iter()
+        .map(|&(token, owner)| {
+            if token == NATIVE_TOKEN {
+                IMulticall3::Call3 {
+                    target: multicall3,
+                    allowFailure: false,
+                    callData: IMulticall3::getEthBalanceCall { addr: owner }
+                        .abi_encode()
+                        .into(),
+                }
+            } else {
+                IMulticall3::Call3 {
+                    target: token,
+                    allowFailure: false,
+                    callData: IERC20::balanceOfCall { account: owner }.abi_encode().into(),
+                }
+            }
+        })
+        .collect();
+    let results = IMulticall3::new(multicall3, &provider)
+        .aggregate3(calls)
+        .block(block)
+        .call()
+        .await
+        .map_err(|e| crate::EilError::AlloyContract(format!("aggregate3: {}", e)))?
+        .returnData;
+
+    results
+        .iter()
+        .map(|result| {
+            IERC20::balanceOfCall::abi_decode_returns(&result.returnData, true)
+                .map(|r| r._0)
+                .map_err(|e| crate::EilError::AlloyContract(format!("balanceOf: {}", e)))
+        })
+        .collect()
 }
 
 impl MultiChainEntity for MultichainToken {
@@ -142,7 +408,8 @@ pub struct TotalBalanceOfResult {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
+    use crate::test_utils::{call_request, create_test_config, MockRpcServer};
+    use alloy::sol_types::SolValue;
 
     fn create_test_deployments() -> AddressPerChain {
         let mut deployments = HashMap::new();
@@ -238,4 +505,266 @@ mod tests {
         assert!(entity.address_on(1).is_some());
         assert!(entity.address_on(999).is_none());
     }
+
+    const MULTICALL3: Address = Address::repeat_byte(0xca);
+
+    /// Mock node of `chain_id` answering every token call and native balance with
+    /// `value`, with Multicall3 at [`MULTICALL3`]
+    async fn start_token_chain(chain_id: ChainId, value: u64) -> MockRpcServer {
+        MockRpcServer::start(move |method, params| match method {
+            "eth_chainId" => Ok(serde_json::json!(format!("0x{:x}", chain_id))),
+            "eth_getBalance" => Ok(serde_json::json!(format!("0x{:x}", value))),
+            "eth_call" => {
+                let (to, data) = call_request(params);
+                let value = U256::from(value);
+                if to != MULTICALL3 {
+                    return Ok(serde_json::json!(Hex::from(value.abi_encode())));
+                }
+                let call = IMulticall3::aggregate3Call::abi_decode(&data, true).unwrap();
+                let results: Vec<IMulticall3::Result> = call
+                    .calls
+                    .iter()
+                    .map(|_| IMulticall3::Result {
+                        success: true,
+                        returnData: value.abi_encode().into(),
+                    })
+                    .collect();
+                Ok(serde_json::json!(Hex::from(results.abi_encode())))
+            }
+            _ => Err((-32601, format!("method {} not found", method))),
+        })
+        .await
+    }
+
+    fn create_token_env(servers: &[(ChainId, &MockRpcServer)]) -> NetworkEnvironment {
+        let mut config = create_test_config(servers.iter().map(|(id, _)| *id).collect());
+        for (info, (_, server)) in config.chain_infos.iter_mut().zip(servers) {
+            info.rpc_url = server.url.clone();
+        }
+        config.chain_infos[0].multicall3 = Some(MULTICALL3);
+        NetworkEnvironment::new(&config)
+    }
+
+    fn eth_calls(server: &MockRpcServer) -> Vec<serde_json::Value> {
+        server
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(method, _)| method == "eth_call")
+            .map(|(_, params)| params.clone())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_total_balance_of() {
+        let chain1 = start_token_chain(1, 100).await;
+        let chain10 = start_token_chain(10, 250).await;
+        let env = create_token_env(&[(1, &chain1), (10, &chain10)]);
+        let mut deployments = create_test_deployments();
+        deployments.insert(42, Address::repeat_byte(0x42));
+        let token = MultichainToken::new("USDC".to_string(), deployments);
+        let account = MultichainContract::new(
+            JsonAbi::new(),
+            HashMap::from([
+                (1, Address::repeat_byte(0x11)),
+                (10, Address::repeat_byte(0x11)),
+            ]),
+        );
+
+        let result = token.total_balance_of(&env, &account).await.unwrap();
+
+        assert_eq!(
+            result.per_chain_balance,
+            vec![(1, U256::from(100)), (10, U256::from(250))]
+        );
+        assert_eq!(result.total_balance, U256::from(350));
+        // Chain 1 reads through Multicall3, chain 10 calls the token directly
+        let (to, _) = call_request(&eth_calls(&chain1)[0]);
+        assert_eq!(to, MULTICALL3);
+        let (to, data) = call_request(&eth_calls(&chain10)[0]);
+        assert_eq!(to, token.address_on(10).unwrap());
+        let call = IERC20::balanceOfCall::abi_decode(&data, true).unwrap();
+        assert_eq!(call.account, Address::repeat_byte(0x11));
+    }
+
+    #[tokio::test]
+    async fn test_total_balance_of_at_block() {
+        let chain1 = start_token_chain(1, 100).await;
+        let chain10 = start_token_chain(10, 250).await;
+        let env = create_token_env(&[(1, &chain1), (10, &chain10)]);
+        let token = MultichainToken::new("USDC".to_string(), create_test_deployments());
+        let account = MultichainContract::new(
+            JsonAbi::new(),
+            HashMap::from([(10, Address::repeat_byte(0x11))]),
+        );
+        let blocks = HashMap::from([(10, BlockNumber::Number(0x20))]);
+
+        let result = token
+            .total_balance_of_at(&env, &account, &blocks)
+            .await
+            .unwrap();
+
+        assert_eq!(result.per_chain_balance, vec![(10, U256::from(250))]);
+        assert_eq!(result.total_balance, U256::from(250));
+        assert!(eth_calls(&chain1).is_empty());
+        assert_eq!(eth_calls(&chain10)[0][1], "0x20");
+    }
+
+    #[test]
+    fn test_parse_and_format_amount() {
+        let token =
+            MultichainToken::new("USDC".to_string(), create_test_deployments()).with_decimals(6);
+
+        assert_eq!(token.parse_amount("90").unwrap(), U256::from(90_000_000));
+        assert_eq!(
+            token.parse_amount("90.5 USDC").unwrap(),
+            U256::from(90_500_000)
+        );
+        assert_eq!(token.parse_amount("0.000001").unwrap(), U256::from(1));
+        assert_eq!(
+            token.parse_amount("1.500000").unwrap(),
+            U256::from(1_500_000)
+        );
+        assert_eq!(
+            token.format_amount(U256::from(90_500_000)).unwrap(),
+            "90.5 USDC"
+        );
+        assert_eq!(token.format_amount(U256::from(1)).unwrap(), "0.000001 USDC");
+        assert_eq!(token.format_amount(U256::ZERO).unwrap(), "0 USDC");
+        assert_eq!(format_units(U256::from(42), 0), "42");
+    }
+
+    #[test]
+    fn test_parse_amount_symbol_suffix() {
+        let token =
+            MultichainToken::new("USDC".to_string(), create_test_deployments()).with_decimals(6);
+        assert_eq!(
+            token.parse_amount("90 usdc").unwrap(),
+            U256::from(90_000_000)
+        );
+        assert_eq!(
+            token.parse_amount(" 90.5\tUsdc ").unwrap(),
+            U256::from(90_500_000)
+        );
+        assert!(token.parse_amount("90usdc").is_err());
+
+        let token = MultichainToken::new("USD Coin".to_string(), create_test_deployments())
+            .with_decimals(6);
+        assert_eq!(
+            token.parse_amount("90.5 USD Coin").unwrap(),
+            U256::from(90_500_000)
+        );
+        assert_eq!(
+            token.parse_amount("90 usd coin").unwrap(),
+            U256::from(90_000_000)
+        );
+        assert!(matches!(
+            token.parse_amount("90 Coin"),
+            Err(crate::EilError::InvalidAmount(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_amount_rejects_invalid() {
+        let token = MultichainToken::new("USDC".to_string(), create_test_deployments());
+        assert!(matches!(
+            token.parse_amount("1"),
+            Err(crate::EilError::UnknownDecimals(_))
+        ));
+
+        let token = token.with_decimals(6);
+        for amount in [
+            "0.0000001",
+            "1.2345678 USDC",
+            "90.5 DAI",
+            "-1",
+            "1e6",
+            ".",
+            "",
+        ] {
+            assert!(
+                matches!(
+                    token.parse_amount(amount),
+                    Err(crate::EilError::InvalidAmount(_))
+                ),
+                "{}",
+                amount
+            );
+        }
+        assert!(parse_units(&"9".repeat(80), 0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_decimals() {
+        let chain1 = start_token_chain(1, 6).await;
+        let chain10 = start_token_chain(10, 6).await;
+        let env = create_token_env(&[(1, &chain1), (10, &chain10)]);
+        let mut token = MultichainToken::new("USDC".to_string(), create_test_deployments());
+
+        assert_eq!(token.fetch_decimals(&env).await.unwrap(), 6);
+        assert_eq!(token.decimals, Some(6));
+
+        let mut token = token.with_decimals(18);
+        assert!(matches!(
+            token.fetch_decimals(&env).await,
+            Err(crate::EilError::DecimalsMismatch {
+                declared: 18,
+                actual: 6,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_decimals_differ_across_chains() {
+        let chain1 = start_token_chain(1, 6).await;
+        let chain10 = start_token_chain(10, 18).await;
+        let env = create_token_env(&[(1, &chain1), (10, &chain10)]);
+        let mut token = MultichainToken::new("USDC".to_string(), create_test_deployments());
+
+        let err = token.fetch_decimals(&env).await.unwrap_err();
+        assert!(matches!(
+            err,
+            crate::EilError::InconsistentDecimals { ref decimals, .. }
+                if *decimals == vec![(1, 6), (10, 18)]
+        ));
+        assert_eq!(token.decimals, None);
+    }
+
+    #[tokio::test]
+    async fn test_native_token() {
+        let chain1 = start_token_chain(1, 100).await;
+        let chain10 = start_token_chain(10, 250).await;
+        let env = create_token_env(&[(1, &chain1), (10, &chain10)]);
+        let mut eth = MultichainToken::native("ETH", &[1, 10]);
+        let account = MultichainContract::new(
+            JsonAbi::new(),
+            HashMap::from([
+                (1, Address::repeat_byte(0x11)),
+                (10, Address::repeat_byte(0x11)),
+            ]),
+        );
+
+        assert!(eth.is_native_on(1));
+        assert!(!eth.is_native_on(42161));
+        assert_eq!(
+            eth.parse_amount("0.5 ETH").unwrap(),
+            U256::from(5u64 * 10u64.pow(17))
+        );
+        assert_eq!(eth.fetch_decimals(&env).await.unwrap(), 18);
+
+        let result = eth.total_balance_of(&env, &account).await.unwrap();
+
+        assert_eq!(result.total_balance, U256::from(350));
+        // Chain 1 reads through Multicall3 getEthBalance, chain 10 with eth_getBalance
+        let (_, data) = call_request(&eth_calls(&chain1)[0]);
+        let call = IMulticall3::aggregate3Call::abi_decode(&data, true).unwrap();
+        assert_eq!(call.calls[0].target, MULTICALL3);
+        assert!(call.calls[0]
+            .callData
+            .starts_with(&IMulticall3::getEthBalanceCall::SELECTOR));
+        assert!(eth_calls(&chain10).is_empty());
+        assert!(chain10.methods().contains(&"eth_getBalance".to_string()));
+    }
 }
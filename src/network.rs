@@ -1,12 +1,29 @@
-use crate::{config::CrossChainConfig, types::*, Result};
-use std::collections::HashMap;
+use crate::{bundler::JsonRpcBundler, config::CrossChainConfig, types::*, Result};
+use alloy::{
+    providers::{Provider, RootProvider},
+    rpc::client::RpcClient,
+    transports::http::Http,
+};
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::OnceCell;
 
-/// Simplified provider type - just store RPC URLs for now
-/// Full provider implementation requires runtime async initialization
+/// HTTP provider used to read chain state
+pub type EilProvider = RootProvider<Http<reqwest::Client>>;
+
+/// Network environment holding per-chain RPC configuration and providers
+///
+/// Providers are created lazily and cached; clones of the environment share the
+/// same cache, so a provider is created (and its chain ID checked) once per chain.
 #[derive(Clone)]
 pub struct NetworkEnvironment {
     /// RPC URLs per chain
     rpc_urls: HashMap<ChainId, String>,
+    /// Cached providers per chain
+    providers: Arc<HashMap<ChainId, OnceCell<EilProvider>>>,
+    /// HTTP client shared by all providers
+    http_client: reqwest::Client,
+    /// Bundler client shared by all clones
+    bundler: Arc<JsonRpcBundler>,
     /// Configuration reference
     config: CrossChainConfig,
 }
@@ -15,13 +32,18 @@ impl NetworkEnvironment {
     /// Create a new network environment from configuration
     pub fn new(config: &CrossChainConfig) -> Self {
         let mut rpc_urls = HashMap::new();
+        let mut providers = HashMap::new();
 
         for chain_info in &config.chain_infos {
             rpc_urls.insert(chain_info.chain_id, chain_info.rpc_url.clone());
+            providers.insert(chain_info.chain_id, OnceCell::new());
         }
 
         Self {
             rpc_urls,
+            providers: Arc::new(providers),
+            http_client: reqwest::Client::new(),
+            bundler: Arc::new(JsonRpcBundler::new(config)),
             config: config.clone(),
         }
     }
@@ -31,19 +53,48 @@ impl NetworkEnvironment {
         self.rpc_urls
             .get(&chain_id)
             .map(|s| s.as_str())
-            .ok_or_else(|| crate::EilError::UnsupportedChain(chain_id))
+            .ok_or(crate::EilError::UnsupportedChain(chain_id))
     }
 
-    /// Create a provider for a specific chain (async operation)
-    /// Note: This is a placeholder. Full provider implementation requires proper async initialization
-    pub async fn create_provider(
-        &self,
-        _chain_id: ChainId,
-    ) -> Result<()> {
-        // TODO: Implement proper provider creation
-        // For now, this is a placeholder as provider creation in alloy
-        // requires specific setup that varies by version
-        Ok(())
+    /// Get the provider for a specific chain, creating it on first use
+    ///
+    /// On creation, `eth_chainId` is checked against the configured chain ID.
+    pub async fn create_provider(&self, chain_id: ChainId) -> Result<EilProvider> {
+        let cell = self
+            .providers
+            .get(&chain_id)
+            .ok_or(crate::EilError::UnsupportedChain(chain_id))?;
+
+        cell.get_or_try_init(|| async {
+            let url = self.rpc_url(chain_id)?.parse().map_err(|e| {
+                crate::EilError::AlloyProvider(format!(
+                    "Invalid RPC URL for chain {}: {}",
+                    chain_id, e
+                ))
+            })?;
+            let transport = Http::with_client(self.http_client.clone(), url);
+            let provider = RootProvider::new(RpcClient::new(transport, false));
+
+            let actual = provider
+                .get_chain_id()
+                .await
+                .map_err(|e| crate::EilError::AlloyProvider(e.to_string()))?;
+            if actual != chain_id {
+                return Err(crate::EilError::ChainIdMismatch {
+                    expected: chain_id,
+                    actual,
+                });
+            }
+
+            Ok(provider)
+        })
+        .await
+        .cloned()
+    }
+
+    /// Get the bundler client
+    pub fn bundler(&self) -> &JsonRpcBundler {
+        &self.bundler
     }
 
     /// Get all chain IDs
@@ -61,7 +112,32 @@ impl NetworkEnvironment {
         self.config
             .chain_info(chain_id)
             .map(|info| info.entry_point)
-            .ok_or_else(|| crate::EilError::UnsupportedChain(chain_id))
+            .ok_or(crate::EilError::UnsupportedChain(chain_id))
+    }
+
+    /// Get RuntimeVarsHelper address for a chain
+    pub fn runtime_vars_helper(&self, chain_id: ChainId) -> Result<Address> {
+        self.config
+            .chain_info(chain_id)
+            .ok_or(crate::EilError::UnsupportedChain(chain_id))?
+            .runtime_vars_helper
+            .ok_or(crate::EilError::RuntimeVarsHelperNotConfigured(chain_id))
+    }
+
+    /// Get the Multicall3 address of a chain, if configured
+    pub fn multicall3(&self, chain_id: ChainId) -> Option<Address> {
+        self.config
+            .chain_info(chain_id)
+            .and_then(|info| info.multicall3)
+    }
+
+    /// Get the Permit2 address of a chain
+    pub fn permit2(&self, chain_id: ChainId) -> Result<Address> {
+        self.config
+            .chain_info(chain_id)
+            .ok_or(crate::EilError::UnsupportedChain(chain_id))?
+            .permit2
+            .ok_or(crate::EilError::Permit2NotConfigured(chain_id))
     }
 
     /// Get Paymaster address for a chain
@@ -69,6 +145,67 @@ impl NetworkEnvironment {
         self.config
             .chain_info(chain_id)
             .map(|info| info.paymaster)
-            .ok_or_else(|| crate::EilError::UnsupportedChain(chain_id))
+            .ok_or(crate::EilError::UnsupportedChain(chain_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{create_test_config, MockRpcServer};
+
+    fn create_test_env(url: &str, chain_id: ChainId) -> NetworkEnvironment {
+        let mut config = create_test_config(vec![chain_id]);
+        config.chain_infos[0].rpc_url = url.to_string();
+        NetworkEnvironment::new(&config)
+    }
+
+    #[tokio::test]
+    async fn test_create_provider_checks_chain_id() {
+        let server = MockRpcServer::start(|method, _| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            "eth_blockNumber" => Ok(serde_json::json!("0x64")),
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let env = create_test_env(&server.url, 10);
+
+        let provider = env.create_provider(10).await.unwrap();
+        assert_eq!(provider.get_block_number().await.unwrap(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_create_provider_chain_id_mismatch() {
+        let server = MockRpcServer::start(|_, _| Ok(serde_json::json!("0x1"))).await;
+        let env = create_test_env(&server.url, 10);
+
+        match env.create_provider(10).await {
+            Err(crate::EilError::ChainIdMismatch { expected, actual }) => {
+                assert_eq!(expected, 10);
+                assert_eq!(actual, 1);
+            }
+            other => panic!("Expected ChainIdMismatch error, got {:?}", other.err()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_provider_cached_across_clones() {
+        let server = MockRpcServer::start(|_, _| Ok(serde_json::json!("0xa"))).await;
+        let env = create_test_env(&server.url, 10);
+        let cloned = Arc::new(env.clone());
+
+        env.create_provider(10).await.unwrap();
+        cloned.create_provider(10).await.unwrap();
+
+        assert_eq!(server.methods(), vec!["eth_chainId"]);
+    }
+
+    #[tokio::test]
+    async fn test_create_provider_unsupported_chain() {
+        let env = NetworkEnvironment::new(&create_test_config(vec![1]));
+        assert!(matches!(
+            env.create_provider(999).await,
+            Err(crate::EilError::UnsupportedChain(999))
+        ));
     }
 }
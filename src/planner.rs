@@ -0,0 +1,678 @@
+//! Funding route planner
+//!
+//! Plans how to get `amount` of a token onto a destination chain. The account's balance
+//! on the destination chain is spent first; the rest is pulled from other chains through
+//! one voucher per source chain. A source chain can fund its balance less the voucher's
+//! maximum fee, and all vouchers together are capped by the amount enough destination
+//! XLPs can cover. The [`FundingStrategy`] decides which source chains are drawn from
+//! first.
+
+use crate::{
+    account::MultiChainSmartAccount,
+    contract_types::{
+        Asset, AtomicSwapFeeRule, DestinationSwapComponent, SdkVoucherRequest, SourceSwapComponent,
+        UserOperation, VoucherRequest,
+    },
+    fees::FEE_DENOMINATOR,
+    multichain::MultichainToken,
+    network::NetworkEnvironment,
+    types::*,
+    utils::unscale_floor,
+    Result,
+};
+use alloy::primitives::U256;
+use futures::future::try_join_all;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+/// Spend to fund: `amount` of `token` on `destination_chain_id`
+#[derive(Debug, Clone)]
+pub struct FundingRequest {
+    /// Token to spend
+    pub token: MultichainToken,
+    /// Amount needed on the destination chain
+    pub amount: U256,
+    /// Chain the amount is spent on
+    pub destination_chain_id: ChainId,
+}
+
+/// Chain the account can pull funds from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FundingSource {
+    /// Source chain
+    pub chain_id: ChainId,
+    /// Account balance on the chain
+    pub balance: U256,
+    /// Largest voucher amount the chain can fund
+    pub capacity: U256,
+    /// Estimated cost in wei of the source batch creating the voucher
+    pub estimated_cost: U256,
+}
+
+/// Order in which source chains are drawn from
+pub trait FundingStrategy: Send + Sync {
+    /// Sort `sources`, most preferred first
+    fn rank(&self, sources: &mut [FundingSource]);
+}
+
+/// Draw from the chains holding the most first, using as few vouchers as possible
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FewestChains;
+
+impl FundingStrategy for FewestChains {
+    fn rank(&self, sources: &mut [FundingSource]) {
+        sources.sort_by_key(|source| (Reverse(source.capacity), source.chain_id));
+    }
+}
+
+/// Draw from the chains with the cheapest source batch first
+///
+/// Voucher fees are the same share of the amount whatever the source chain, so routes
+/// differ in cost by their source batches: gas, plus the L1 data fee on rollups.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LowestFee;
+
+impl FundingStrategy for LowestFee {
+    fn rank(&self, sources: &mut [FundingSource]) {
+        sources.sort_by_key(|source| {
+            (
+                source.estimated_cost,
+                Reverse(source.capacity),
+                source.chain_id,
+            )
+        });
+    }
+}
+
+/// Draw from the listed chains in order, then from the others as [`FewestChains`]
+#[derive(Debug, Clone, Default)]
+pub struct PreferChains(pub Vec<ChainId>);
+
+impl FundingStrategy for PreferChains {
+    fn rank(&self, sources: &mut [FundingSource]) {
+        FewestChains.rank(sources);
+        sources.sort_by_key(|source| {
+            self.0
+                .iter()
+                .position(|chain_id| *chain_id == source.chain_id)
+                .unwrap_or(usize::MAX)
+        });
+    }
+}
+
+/// Voucher pulling funds from a source chain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedVoucher {
+    /// Voucher reference ID
+    pub ref_id: String,
+    /// Chain the voucher is requested on
+    pub source_chain_id: ChainId,
+    /// Amount delivered on the destination chain
+    pub amount: U256,
+    /// Highest fee the voucher can cost on top of `amount`
+    pub max_fee: U256,
+}
+
+/// Route funding a [`FundingRequest`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingPlan {
+    /// Token spent
+    pub token: MultichainToken,
+    /// Chain the amount is spent on
+    pub destination_chain_id: ChainId,
+    /// Amount needed on the destination chain
+    pub amount: U256,
+    /// Part of `amount` already held on the destination chain
+    pub destination_balance: U256,
+    /// Vouchers bringing the rest, one per source chain
+    pub vouchers: Vec<PlannedVoucher>,
+}
+
+impl FundingPlan {
+    /// Voucher requests to add to the source batches
+    pub fn voucher_requests(&self) -> Vec<SdkVoucherRequest> {
+        self.vouchers
+            .iter()
+            .map(|voucher| SdkVoucherRequest {
+                ref_id: voucher.ref_id.clone(),
+                source_chain_id: Some(voucher.source_chain_id),
+                destination_chain_id: self.destination_chain_id,
+                tokens: vec![TokenAmount {
+                    token: self.token.clone(),
+                    amount: Amount::Fixed(voucher.amount),
+                    min_provider_deposit: None,
+                }],
+                target: None,
+            })
+            .collect()
+    }
+
+    /// Highest total fee of the vouchers
+    pub fn max_fee(&self) -> U256 {
+        self.vouchers.iter().fold(U256::ZERO, |total, voucher| {
+            total.saturating_add(voucher.max_fee)
+        })
+    }
+}
+
+/// Plan the funding of `request` from the balances of `account`
+///
+/// Only chains configured in `network_env` are considered.
+pub async fn plan_funding(
+    network_env: &NetworkEnvironment,
+    account: &dyn MultiChainSmartAccount,
+    request: &FundingRequest,
+    strategy: &dyn FundingStrategy,
+) -> Result<FundingPlan> {
+    let config = network_env.config();
+    let fee_rule = AtomicSwapFeeRule::from_config(&config.fee_config)?;
+    let destination = request.destination_chain_id;
+
+    let mut token = request.token.clone();
+    token
+        .deployments
+        .retain(|chain_id, _| config.chain_info(*chain_id).is_some());
+    if !token.is_deployed_on(destination) {
+        return Err(crate::EilError::InvalidAddress {
+            chain_id: destination,
+            address: format!("Token {} not deployed", token.name),
+        });
+    }
+
+    let balances = token.total_balance_of(network_env, account).await?;
+    let destination_balance = balances
+        .per_chain_balance
+        .iter()
+        .find(|(chain_id, _)| *chain_id == destination)
+        .map(|(_, balance)| *balance)
+        .unwrap_or_default();
+    let candidates: Vec<(ChainId, U256)> = balances
+        .per_chain_balance
+        .into_iter()
+        .filter(|(chain_id, balance)| *chain_id != destination && !balance.is_zero())
+        .collect();
+
+    let mut sources = Vec::new();
+    let mut xlp_limit = U256::MAX;
+    if request.amount > destination_balance && !candidates.is_empty() {
+        let shortfall = request.amount - destination_balance;
+        xlp_limit = xlp_voucher_limit(network_env, &token, destination, shortfall).await?;
+        sources = try_join_all(candidates.into_iter().map(|(chain_id, balance)| {
+            let (fee_rule, token) = (&fee_rule, &token);
+            async move {
+                let capacity = max_voucher_amount(balance, fee_rule);
+                let request = source_voucher_request(
+                    network_env,
+                    account,
+                    token,
+                    chain_id,
+                    destination,
+                    capacity,
+                    fee_rule,
+                )?;
+                Ok::<_, crate::EilError>(FundingSource {
+                    chain_id,
+                    balance,
+                    capacity,
+                    estimated_cost: estimate_source_cost(network_env, account, &request).await?,
+                })
+            }
+        }))
+        .await?;
+    }
+
+    select_sources(
+        request,
+        destination_balance,
+        sources,
+        xlp_limit,
+        strategy,
+        &fee_rule,
+    )
+}
+
+/// Pick the vouchers covering what `destination_balance` leaves of `request.amount`
+/// The vouchers together amount to at most `xlp_limit`.
+pub fn select_sources(
+    request: &FundingRequest,
+    destination_balance: U256,
+    mut sources: Vec<FundingSource>,
+    xlp_limit: U256,
+    strategy: &dyn FundingStrategy,
+    fee_rule: &AtomicSwapFeeRule,
+) -> Result<FundingPlan> {
+    let destination = request.destination_chain_id;
+    let destination_balance = destination_balance.min(request.amount);
+    let mut missing = request.amount - destination_balance;
+    let mut xlp_remaining = xlp_limit;
+
+    strategy.rank(&mut sources);
+    let mut vouchers = Vec::new();
+    for source in sources {
+        let amount = source.capacity.min(missing).min(xlp_remaining);
+        if amount.is_zero() {
+            continue;
+        }
+        missing -= amount;
+        xlp_remaining -= amount;
+        vouchers.push(PlannedVoucher {
+            ref_id: format!(
+                "fund-{}-{}-{}",
+                request.token.name, source.chain_id, destination
+            ),
+            source_chain_id: source.chain_id,
+            amount,
+            max_fee: fee_rule.max_fee(amount),
+        });
+    }
+
+    if !missing.is_zero() {
+        return Err(crate::EilError::InsufficientFunds {
+            token: request.token.name.clone(),
+            chain_id: destination,
+            required: request.amount,
+            available: request.amount - missing,
+        });
+    }
+
+    Ok(FundingPlan {
+        token: request.token.clone(),
+        destination_chain_id: destination,
+        amount: request.amount,
+        destination_balance,
+        vouchers,
+    })
+}
+
+/// Voucher request a source batch on `chain_id` would create for `amount`
+/// Priced like the builder's requests, before the destination batch cost is known.
+fn source_voucher_request(
+    network_env: &NetworkEnvironment,
+    account: &dyn MultiChainSmartAccount,
+    token: &MultichainToken,
+    chain_id: ChainId,
+    destination: ChainId,
+    amount: U256,
+    fee_rule: &AtomicSwapFeeRule,
+) -> Result<VoucherRequest> {
+    let asset = |chain_id| Asset {
+        erc20_token: token.address_on(chain_id).unwrap_or_default(),
+        amount,
+    };
+    Ok(VoucherRequest {
+        origination: SourceSwapComponent {
+            chain_id,
+            sender: account.address_on(chain_id)?,
+            paymaster: network_env.paymaster(chain_id)?,
+            assets: vec![asset(chain_id)],
+            fee_rule: fee_rule.clone(),
+            sender_nonce: U256::ZERO,
+            allowed_xlps: Vec::new(),
+        },
+        destination: DestinationSwapComponent {
+            chain_id: destination,
+            sender: account.address_on(destination)?,
+            paymaster: network_env.paymaster(destination)?,
+            assets: vec![asset(destination)],
+            max_user_op_cost: U256::ZERO,
+            expires_at: U256::from(
+                crate::utils::now_seconds() + network_env.config().expire_time_seconds,
+            ),
+        },
+    })
+}
+
+/// Estimated cost in wei of the source batch creating `request`
+/// Gas limits times the max fee per gas, plus the L1 data fee of the UserOperation.
+async fn estimate_source_cost(
+    network_env: &NetworkEnvironment,
+    account: &dyn MultiChainSmartAccount,
+    request: &VoucherRequest,
+) -> Result<U256> {
+    let chain_id = request.origination.chain_id;
+    let calls = crate::voucher::encode_voucher_request_calls(request);
+    let mut user_op = UserOperation {
+        sender: request.origination.sender,
+        nonce: U256::ZERO,
+        factory: None,
+        factory_data: None,
+        call_data: account.encode_calls(chain_id, calls).await?,
+        call_gas_limit: U256::ZERO,
+        verification_gas_limit: U256::ZERO,
+        pre_verification_gas: U256::ZERO,
+        max_fee_per_gas: U256::ZERO,
+        max_priority_fee_per_gas: U256::ZERO,
+        paymaster: None,
+        paymaster_verification_gas_limit: None,
+        paymaster_post_op_gas_limit: None,
+        paymaster_data: None,
+        paymaster_signature: None,
+        signature: Hex::new(),
+        chain_id: Some(chain_id),
+        entry_point_address: Some(network_env.entry_point(chain_id)?),
+    };
+    let gas_config = &network_env.config().gas_config;
+    let estimate = crate::gas::estimate_user_op_gas(network_env, &user_op, gas_config).await?;
+    estimate.apply_to(&mut user_op);
+    let l1_data_fee = crate::gas::estimate_l1_data_fee(network_env, &user_op).await?;
+    Ok(estimate.max_cost().saturating_add(l1_data_fee))
+}
+
+/// Largest voucher amount `balance` covers together with its maximum fee
+pub fn max_voucher_amount(balance: U256, fee_rule: &AtomicSwapFeeRule) -> U256 {
+    let denominator = U256::from(FEE_DENOMINATOR);
+    balance.saturating_mul(denominator) / (denominator + fee_rule.max_fee_percent_numerator)
+}
+
+/// Largest total voucher amount enough destination XLPs can cover
+///
+/// Mirrors [`crate::voucher::select_xlps`]: the amount is capped so that at least
+/// `min_xlps` XLPs hold it times `deposit_reserve_factor`.
+async fn xlp_voucher_limit(
+    network_env: &NetworkEnvironment,
+    token: &MultichainToken,
+    chain_id: ChainId,
+    amount: U256,
+) -> Result<U256> {
+    let config = &network_env.config().xlp_selection_config;
+    let tokens = [TokenAmount {
+        token: token.clone(),
+        amount: Amount::Fixed(amount),
+        min_provider_deposit: None,
+    }];
+    let xlps = crate::voucher::get_solvent_xlps(
+        network_env,
+        chain_id,
+        network_env.paymaster(chain_id)?,
+        &tokens,
+        config.include_balance,
+    )
+    .await?;
+
+    let token_address = token.address_on(chain_id).unwrap_or_default();
+    let mut available: Vec<U256> = xlps
+        .iter()
+        .filter_map(|xlp| {
+            let deposit = xlp.deposits.first().copied().unwrap_or_default();
+            let balance = xlp.balances.first().copied().unwrap_or_default();
+            let accepted = config.custom_xlp_filter.as_ref().is_none_or(|filter| {
+                filter(
+                    chain_id,
+                    xlp.xlp_entry.l2_xlp_address,
+                    token_address,
+                    deposit,
+                    balance,
+                )
+            });
+            let available = if config.include_balance {
+                deposit.saturating_add(balance)
+            } else {
+                deposit
+            };
+            accepted.then_some(available)
+        })
+        .collect();
+    available.sort_unstable_by_key(|available| Reverse(*available));
+
+    if available.is_empty() {
+        return Err(crate::EilError::NoXlpsFound(chain_id));
+    }
+    let required = config.min_xlps.max(1);
+    let Some(limit) = available.get(required - 1) else {
+        return Err(crate::EilError::InsufficientXlps {
+            found: available.len(),
+            required,
+            chain_id,
+        });
+    };
+    Ok(unscale_floor(*limit, config.deposit_reserve_factor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::FeeConfig,
+        contracts::{ICrossChainPaymaster, IGasPriceOracle, IERC20},
+        test_utils::{
+            call_request, create_test_config, create_test_token, MockAccount, MockRpcServer,
+        },
+    };
+    use alloy::sol_types::{SolCall, SolValue};
+
+    fn create_rule() -> AtomicSwapFeeRule {
+        AtomicSwapFeeRule::from_config(&FeeConfig::default()).unwrap()
+    }
+
+    fn create_request(amount: u64) -> FundingRequest {
+        FundingRequest {
+            token: create_test_token("USDC", vec![1, 10, 42161]),
+            amount: U256::from(amount),
+            destination_chain_id: 42161,
+        }
+    }
+
+    fn source(chain_id: ChainId, capacity: u64, estimated_cost: u64) -> FundingSource {
+        FundingSource {
+            chain_id,
+            balance: U256::from(capacity),
+            capacity: U256::from(capacity),
+            estimated_cost: U256::from(estimated_cost),
+        }
+    }
+
+    fn planned(plan: &FundingPlan) -> Vec<(ChainId, u64)> {
+        plan.vouchers
+            .iter()
+            .map(|v| (v.source_chain_id, v.amount.to::<u64>()))
+            .collect()
+    }
+
+    #[test]
+    fn test_strategies() {
+        let sources = vec![source(1, 400, 5), source(10, 700, 1), source(8453, 300, 2)];
+        let request = create_request(900);
+        let rule = create_rule();
+        let plan = |strategy: &dyn FundingStrategy| {
+            select_sources(
+                &request,
+                U256::from(100),
+                sources.clone(),
+                U256::MAX,
+                strategy,
+                &rule,
+            )
+            .unwrap()
+        };
+
+        let fewest = plan(&FewestChains);
+        assert_eq!(planned(&fewest), vec![(10, 700), (1, 100)]);
+        assert_eq!(fewest.destination_balance, U256::from(100));
+        assert_eq!(fewest.vouchers[0].ref_id, "fund-USDC-10-42161");
+        assert_eq!(fewest.vouchers[0].max_fee, U256::from(35));
+        assert_eq!(fewest.max_fee(), U256::from(40));
+
+        let cheapest = plan(&LowestFee);
+        assert_eq!(planned(&cheapest), vec![(10, 700), (8453, 100)]);
+
+        let preferred = plan(&PreferChains(vec![8453, 1]));
+        assert_eq!(planned(&preferred), vec![(8453, 300), (1, 400), (10, 100)]);
+    }
+
+    #[test]
+    fn test_destination_balance_covers_request() {
+        let plan = select_sources(
+            &create_request(500),
+            U256::from(800),
+            vec![source(1, 400, 1)],
+            U256::MAX,
+            &FewestChains,
+            &create_rule(),
+        )
+        .unwrap();
+
+        assert!(plan.vouchers.is_empty());
+        assert_eq!(plan.destination_balance, U256::from(500));
+    }
+
+    #[test]
+    fn test_insufficient_funds() {
+        let result = select_sources(
+            &create_request(1_000),
+            U256::from(100),
+            vec![source(1, 400, 1), source(10, 300, 1)],
+            U256::MAX,
+            &FewestChains,
+            &create_rule(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(crate::EilError::InsufficientFunds { available, .. }) if available == U256::from(800)
+        ));
+    }
+
+    #[test]
+    fn test_xlp_limit_caps_total_vouchers() {
+        let sources = vec![source(1, 600, 1), source(10, 300, 1)];
+        let select = |amount: u64, xlp_limit: u64| {
+            select_sources(
+                &create_request(amount),
+                U256::ZERO,
+                sources.clone(),
+                U256::from(xlp_limit),
+                &FewestChains,
+                &create_rule(),
+            )
+        };
+
+        let plan = select(900, 1_000).unwrap();
+        assert_eq!(planned(&plan), vec![(1, 600), (10, 300)]);
+
+        let plan = select(800, 800).unwrap();
+        assert_eq!(planned(&plan), vec![(1, 600), (10, 200)]);
+
+        assert!(matches!(
+            select(900, 600),
+            Err(crate::EilError::InsufficientFunds { available, .. }) if available == U256::from(600)
+        ));
+    }
+
+    #[test]
+    fn test_max_voucher_amount_covers_fee() {
+        let rule = create_rule();
+        for balance in [0u64, 1, 105, 1_050, 123_456_789] {
+            let amount = max_voucher_amount(U256::from(balance), &rule);
+            assert!(amount + rule.max_fee(amount) <= U256::from(balance));
+        }
+        assert_eq!(
+            max_voucher_amount(U256::from(1_050), &rule),
+            U256::from(1_000)
+        );
+    }
+
+    /// Mock node of `chain_id` where the account holds `balance`, the paymaster has one XLP
+    /// with `xlp_deposit` and the OP Stack gas price oracle charges `l1_fee`
+    async fn start_funding_chain(
+        chain_id: ChainId,
+        balance: u64,
+        xlp_deposit: u64,
+        l1_fee: u64,
+    ) -> MockRpcServer {
+        MockRpcServer::start(move |method, params| match method {
+            "eth_chainId" => Ok(serde_json::json!(format!("0x{:x}", chain_id))),
+            "eth_feeHistory" => Ok(serde_json::json!({
+                "oldestBlock": "0x1",
+                "baseFeePerGas": [format!("0x{:x}", chain_id), format!("0x{:x}", chain_id)],
+                "gasUsedRatio": [0.5],
+                "reward": [["0x0"]],
+            })),
+            "eth_estimateGas" => Ok(serde_json::json!("0x186a0")),
+            "eth_call" => {
+                let (_, data) = call_request(params);
+                let result = if data.starts_with(&ICrossChainPaymaster::getXlpsCall::SELECTOR) {
+                    vec![ICrossChainPaymaster::XlpEntry {
+                        l1XlpAddress: Address::repeat_byte(1),
+                        l2XlpAddress: Address::repeat_byte(2),
+                        bond: U256::from(5),
+                    }]
+                    .abi_encode()
+                } else if data.starts_with(&ICrossChainPaymaster::getDepositsCall::SELECTOR) {
+                    vec![U256::from(xlp_deposit)].abi_encode()
+                } else if data.starts_with(&IGasPriceOracle::getL1FeeCall::SELECTOR) {
+                    U256::from(l1_fee).abi_encode()
+                } else {
+                    assert!(data.starts_with(&IERC20::balanceOfCall::SELECTOR));
+                    U256::from(balance).abi_encode()
+                };
+                Ok(serde_json::json!(Hex::from(result)))
+            }
+            _ => Err((-32601, format!("method {} not found", method))),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_plan_funding() {
+        let chain1 = start_funding_chain(1, 4_200, 0, 0).await;
+        let chain10 = start_funding_chain(10, 2_100, 0, 0).await;
+        let destination = start_funding_chain(42161, 500, 5_000, 0).await;
+        let mut config = create_test_config(vec![1, 10, 42161]);
+        for (info, server) in config
+            .chain_infos
+            .iter_mut()
+            .zip([&chain1, &chain10, &destination])
+        {
+            info.rpc_url = server.url.clone();
+        }
+        let env = NetworkEnvironment::new(&config);
+
+        let plan = plan_funding(
+            &env,
+            &MockAccount::new(),
+            &create_request(5_500),
+            &LowestFee,
+        )
+        .await
+        .unwrap();
+
+        // Chain 10 could fund 2_000 but the XLP only covers 5_000 in total
+        assert_eq!(plan.destination_balance, U256::from(500));
+        assert_eq!(planned(&plan), vec![(1, 4_000), (10, 1_000)]);
+        let requests = plan.voucher_requests();
+        assert_eq!(requests[0].source_chain_id, Some(1));
+        assert_eq!(requests[0].destination_chain_id, 42161);
+        assert_eq!(requests[0].tokens[0].voucher_amount(), U256::from(4_000));
+    }
+
+    #[tokio::test]
+    async fn test_lowest_fee_counts_l1_data_fee() {
+        // Gas is cheapest on chain 1, but it is a rollup with a high L1 data fee
+        let chain1 = start_funding_chain(1, 4_200, 0, 1_000_000_000_000_000).await;
+        let chain10 = start_funding_chain(10, 2_100, 0, 0).await;
+        let destination = start_funding_chain(42161, 500, 5_000, 0).await;
+        let mut config = create_test_config(vec![1, 10, 42161]);
+        for (info, server) in config
+            .chain_infos
+            .iter_mut()
+            .zip([&chain1, &chain10, &destination])
+        {
+            info.rpc_url = server.url.clone();
+        }
+        config.chain_infos[0].l2_kind = crate::config::L2Kind::OpStack;
+        let env = NetworkEnvironment::new(&config);
+
+        let plan = plan_funding(
+            &env,
+            &MockAccount::new(),
+            &create_request(5_500),
+            &LowestFee,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(planned(&plan), vec![(10, 2_000), (1, 3_000)]);
+    }
+}
@@ -0,0 +1,313 @@
+//! Revert data decoding
+//!
+//! Decodes EntryPoint `FailedOp`/`FailedOpWithRevert` errors into their `AAxx` codes,
+//! `Error(string)` and `Panic(uint256)`, and custom errors declared in the ABIs of a
+//! batch's calls. The [`RevertSource`] tells apart failures of the account, the
+//! paymaster, the EntryPoint itself and the calls executed by the account.
+
+use crate::{contracts::IEntryPoint, types::*};
+use alloy::{
+    dyn_abi::{DynSolValue, JsonAbiExt},
+    json_abi::Error as AbiError,
+    sol_types::{Panic, Revert, SolError},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Entity a revert originates from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RevertSource {
+    /// Account or factory validation (`AA1x`, `AA2x`)
+    Account,
+    /// Paymaster validation or postOp (`AA3x`, `AA5x`)
+    Paymaster,
+    /// EntryPoint checks (`AA4x`, `AA9x`)
+    EntryPoint,
+    /// Calls executed by the account
+    Call,
+}
+
+impl RevertSource {
+    /// Source of an `AAxx` code
+    pub fn from_code(code: &str) -> Self {
+        match code.as_bytes().get(2) {
+            Some(b'1' | b'2') => Self::Account,
+            Some(b'3' | b'5') => Self::Paymaster,
+            _ => Self::EntryPoint,
+        }
+    }
+}
+
+/// Decoded revert data
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedRevert {
+    /// Entity the revert originates from
+    pub source: RevertSource,
+    /// `AAxx` code of an EntryPoint validation failure
+    pub code: Option<String>,
+    /// Readable reason, e.g. `AA21 didn't pay prefund` or `revert: nope`
+    pub reason: String,
+    /// Raw revert data
+    pub data: Hex,
+}
+
+impl DecodedRevert {
+    /// Explanation of the `AAxx` code, if known
+    pub fn description(&self) -> Option<&'static str> {
+        self.code.as_deref().and_then(aa_description)
+    }
+}
+
+impl fmt::Display for DecodedRevert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.reason)
+    }
+}
+
+impl From<DecodedRevert> for crate::EilError {
+    fn from(revert: DecodedRevert) -> Self {
+        let code = revert.code.unwrap_or_default();
+        let reason = revert.reason;
+        match revert.source {
+            RevertSource::Account => crate::EilError::AccountValidationFailed { code, reason },
+            RevertSource::Paymaster => crate::EilError::PaymasterValidationFailed { code, reason },
+            RevertSource::EntryPoint => crate::EilError::EntryPointRejected { code, reason },
+            RevertSource::Call => crate::EilError::CallReverted(reason),
+        }
+    }
+}
+
+/// Decode revert data
+///
+/// EntryPoint validation failures take their source from the `AAxx` code; any other
+/// revert is attributed to `source`. Custom errors are looked up in `errors`.
+pub fn decode_revert(data: &[u8], source: RevertSource, errors: &[AbiError]) -> DecodedRevert {
+    if let Ok(error) = IEntryPoint::FailedOp::abi_decode(data, true) {
+        return failed_op(data, error.reason, None);
+    }
+    if let Ok(error) = IEntryPoint::FailedOpWithRevert::abi_decode(data, true) {
+        let inner = decode_revert(&error.inner, source, errors);
+        return failed_op(data, error.reason, Some(inner));
+    }
+
+    DecodedRevert {
+        source,
+        code: None,
+        reason: decode_reason(data, errors),
+        data: Hex::copy_from_slice(data),
+    }
+}
+
+fn failed_op(data: &[u8], reason: String, inner: Option<DecodedRevert>) -> DecodedRevert {
+    let code = reason
+        .get(..4)
+        .filter(|code| code.starts_with("AA") && code[2..].bytes().all(|b| b.is_ascii_digit()))
+        .map(str::to_string);
+    let source = code
+        .as_deref()
+        .map_or(RevertSource::EntryPoint, RevertSource::from_code);
+    let reason = match inner {
+        Some(inner) => format!("{}: {}", reason, inner.reason),
+        None => reason,
+    };
+    DecodedRevert {
+        source,
+        code,
+        reason,
+        data: Hex::copy_from_slice(data),
+    }
+}
+
+/// Readable reason of `Error(string)`, `Panic(uint256)` or a custom error, falling back
+/// to the hex data
+fn decode_reason(data: &[u8], errors: &[AbiError]) -> String {
+    if let Ok(revert) = Revert::abi_decode(data, true) {
+        return revert.to_string();
+    }
+    if let Ok(panic) = Panic::abi_decode(data, true) {
+        return panic.to_string();
+    }
+    if let Some(custom) = decode_custom_error(data, errors) {
+        return custom;
+    }
+    format!("0x{}", alloy::hex::encode(data))
+}
+
+/// Decode a custom error as `Name(arg, ...)`
+fn decode_custom_error(data: &[u8], errors: &[AbiError]) -> Option<String> {
+    let (selector, args) = data.split_at_checked(4)?;
+    errors.iter().find_map(|error| {
+        if error.selector().as_slice() != selector {
+            return None;
+        }
+        let values = error.abi_decode_input(args, true).ok()?;
+        let args: Vec<String> = values.iter().map(format_value).collect();
+        Some(format!("{}({})", error.name, args.join(", ")))
+    })
+}
+
+fn format_value(value: &DynSolValue) -> String {
+    match value {
+        DynSolValue::Address(address) => address.to_string(),
+        DynSolValue::Bool(value) => value.to_string(),
+        DynSolValue::Int(value, _) => value.to_string(),
+        DynSolValue::Uint(value, _) => value.to_string(),
+        DynSolValue::FixedBytes(word, size) => format!("0x{}", alloy::hex::encode(&word[..*size])),
+        DynSolValue::Bytes(bytes) => format!("0x{}", alloy::hex::encode(bytes)),
+        DynSolValue::String(value) => format!("{:?}", value),
+        DynSolValue::Array(values) | DynSolValue::FixedArray(values) => {
+            let values: Vec<String> = values.iter().map(format_value).collect();
+            format!("[{}]", values.join(", "))
+        }
+        DynSolValue::Tuple(values) => {
+            let values: Vec<String> = values.iter().map(format_value).collect();
+            format!("({})", values.join(", "))
+        }
+        other => format!("{:?}", other),
+    }
+}
+
+/// Explanation of an EntryPoint v0.7 `AAxx` code
+pub fn aa_description(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "AA10" => "sender already constructed: initCode must be empty for a deployed account",
+        "AA13" => "initCode failed or ran out of verification gas",
+        "AA14" => "initCode returned a different sender address",
+        "AA15" => "initCode did not deploy the sender",
+        "AA20" => "account not deployed and no initCode provided",
+        "AA21" => "account did not pay the prefund: deposit and balance too low",
+        "AA22" => "account signature expired or not yet valid",
+        "AA23" => "account validateUserOp reverted",
+        "AA24" => "account signature validation failed",
+        "AA25" => "invalid account nonce",
+        "AA26" => "account validation exceeded verificationGasLimit",
+        "AA30" => "paymaster not deployed",
+        "AA31" => "paymaster deposit too low to pay for the UserOperation",
+        "AA32" => "paymaster signature expired or not yet valid",
+        "AA33" => "paymaster validatePaymasterUserOp reverted",
+        "AA34" => "paymaster signature validation failed",
+        "AA36" => "paymaster validation exceeded paymasterVerificationGasLimit",
+        "AA40" => "verification gas used exceeded verificationGasLimit",
+        "AA41" => "verificationGasLimit too low",
+        "AA50" => "paymaster postOp reverted",
+        "AA51" => "prefund below the actual gas cost",
+        "AA90" => "invalid beneficiary",
+        "AA91" => "failed to send fees to the beneficiary",
+        "AA92" => "internal call only",
+        "AA93" => "invalid paymasterAndData",
+        "AA94" => "gas values overflow",
+        "AA95" => "out of gas while executing the bundle",
+        "AA96" => "invalid aggregator",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::{primitives::U256, sol_types::SolValue};
+
+    fn failed_op_data(reason: &str) -> Vec<u8> {
+        IEntryPoint::FailedOp {
+            opIndex: U256::ZERO,
+            reason: reason.to_string(),
+        }
+        .abi_encode()
+    }
+
+    #[test]
+    fn test_decode_failed_op() {
+        let revert = decode_revert(
+            &failed_op_data("AA21 didn't pay prefund"),
+            RevertSource::Call,
+            &[],
+        );
+        assert_eq!(revert.source, RevertSource::Account);
+        assert_eq!(revert.code.as_deref(), Some("AA21"));
+        assert_eq!(revert.reason, "AA21 didn't pay prefund");
+        assert!(revert.description().unwrap().contains("prefund"));
+
+        let revert = decode_revert(
+            &failed_op_data("AA31 paymaster deposit too low"),
+            RevertSource::Call,
+            &[],
+        );
+        assert_eq!(revert.source, RevertSource::Paymaster);
+        let revert = decode_revert(&failed_op_data("AA95 out of gas"), RevertSource::Call, &[]);
+        assert_eq!(revert.source, RevertSource::EntryPoint);
+    }
+
+    #[test]
+    fn test_decode_failed_op_with_revert() {
+        let data = IEntryPoint::FailedOpWithRevert {
+            opIndex: U256::ZERO,
+            reason: "AA33 reverted".to_string(),
+            inner: Revert::from("bad voucher").abi_encode().into(),
+        }
+        .abi_encode();
+
+        let revert = decode_revert(&data, RevertSource::Call, &[]);
+        assert_eq!(revert.source, RevertSource::Paymaster);
+        assert_eq!(revert.code.as_deref(), Some("AA33"));
+        assert_eq!(revert.reason, "AA33 reverted: revert: bad voucher");
+    }
+
+    #[test]
+    fn test_decode_error_and_panic() {
+        let revert = decode_revert(&Revert::from("nope").abi_encode(), RevertSource::Call, &[]);
+        assert_eq!(revert.source, RevertSource::Call);
+        assert_eq!(revert.code, None);
+        assert_eq!(revert.reason, "revert: nope");
+
+        let panic = Panic {
+            code: U256::from(0x11),
+        };
+        let revert = decode_revert(&panic.abi_encode(), RevertSource::Call, &[]);
+        assert_eq!(
+            revert.reason,
+            "panic: arithmetic underflow or overflow (0x11)"
+        );
+    }
+
+    #[test]
+    fn test_decode_custom_error() {
+        let abi: alloy::json_abi::JsonAbi = serde_json::from_str(
+            r#"[{"type":"error","name":"InsufficientBalance","inputs":[
+                {"name":"account","type":"address"},{"name":"needed","type":"uint256"}]}]"#,
+        )
+        .unwrap();
+        let errors: Vec<AbiError> = abi.errors().cloned().collect();
+        let mut data = errors[0].selector().to_vec();
+        data.extend((Address::repeat_byte(0x11), U256::from(5)).abi_encode_params());
+
+        let revert = decode_revert(&data, RevertSource::Call, &errors);
+        assert_eq!(
+            revert.reason,
+            format!("InsufficientBalance({}, 5)", Address::repeat_byte(0x11))
+        );
+
+        let unknown = decode_revert(&[0xde, 0xad, 0xbe, 0xef], RevertSource::Call, &[]);
+        assert_eq!(unknown.reason, "0xdeadbeef");
+    }
+
+    #[test]
+    fn test_revert_into_error() {
+        let revert = decode_revert(
+            &failed_op_data("AA24 signature error"),
+            RevertSource::Call,
+            &[],
+        );
+        assert!(matches!(
+            crate::EilError::from(revert),
+            crate::EilError::AccountValidationFailed { code, .. } if code == "AA24"
+        ));
+
+        let revert = decode_revert(&Revert::from("nope").abi_encode(), RevertSource::Call, &[]);
+        assert!(matches!(
+            crate::EilError::from(revert),
+            crate::EilError::CallReverted(reason) if reason == "revert: nope"
+        ));
+    }
+}
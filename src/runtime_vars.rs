@@ -0,0 +1,311 @@
+//! Runtime variables encoding through the RuntimeVarsHelper contract
+//!
+//! A [`crate::actions::SetVarAction`] stores the return value of a static call under
+//! an 8-character name. Later calls in the same batch can read it: their calldata is
+//! encoded with a placeholder word, and the helper overwrites that word with the
+//! stored value before forwarding the call.
+//!
+//! A batch using runtime variables is run by a single DELEGATECALL from the account to
+//! the helper's `executeBatch`, so every call, including the spliced ones, is made by
+//! the account rather than by the helper.
+
+use crate::{contracts::IRuntimeVarsHelper, types::*, Result};
+use alloy::{
+    dyn_abi::{DynSolValue, JsonAbiExt},
+    primitives::{FixedBytes, U256},
+    sol_types::SolCall,
+};
+
+/// Reference to a runtime variable inside calldata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VarRef {
+    /// Variable name
+    pub name: String,
+    /// Byte offset of the 32-byte word to overwrite
+    pub offset: usize,
+}
+
+/// Encode a variable name as `bytes8`, right-padded with zeros
+pub fn var_name_to_bytes8(name: &str) -> Result<FixedBytes<8>> {
+    if name.len() > 8 {
+        return Err(crate::EilError::InvalidVariableName(name.to_string()));
+    }
+    let mut bytes = [0u8; 8];
+    bytes[..name.len()].copy_from_slice(name.as_bytes());
+    Ok(FixedBytes::from(bytes))
+}
+
+/// Encode a function call from its ABI, name and arguments
+pub fn encode_function_call(call: &FunctionCall) -> Result<Hex> {
+    let function = call
+        .abi
+        .function(&call.function_name)
+        .and_then(|functions| functions.first())
+        .ok_or_else(|| {
+            crate::EilError::Generic(format!("Function {} not found", call.function_name))
+        })?;
+
+    function
+        .abi_encode_input(&call.args)
+        .map(Hex::from)
+        .map_err(|e| {
+            crate::EilError::Generic(format!("Failed to encode {}: {}", call.function_name, e))
+        })
+}
+
+/// Encode a `setVar` call storing the result of `call` under `name`
+///
+/// The call must be a `view`/`pure` function without value returning a single
+/// static word, otherwise [`crate::EilError::DynamicVariableCall`] is returned.
+pub fn encode_set_var(helper: Address, name: &str, call: &FunctionCall) -> Result<Call> {
+    let function = call
+        .abi
+        .function(&call.function_name)
+        .and_then(|functions| functions.first())
+        .ok_or_else(|| {
+            crate::EilError::Generic(format!("Function {} not found", call.function_name))
+        })?;
+
+    if !is_static_word_call(function) || call.value.is_some_and(|v| !v.is_zero()) {
+        return Err(crate::EilError::DynamicVariableCall(name.to_string()));
+    }
+
+    let data = IRuntimeVarsHelper::setVarCall {
+        name: var_name_to_bytes8(name)?,
+        target: call.target,
+        data: encode_function_call(call)?,
+    }
+    .abi_encode();
+
+    Ok(Call {
+        target: helper,
+        data: data.into(),
+        value: None,
+    })
+}
+
+/// Wrap a call so the helper splices runtime variables into its calldata
+/// Returns the call unchanged if it references no variables
+pub fn encode_call_with_vars(helper: Address, call: Call, refs: &[VarRef]) -> Result<Call> {
+    if refs.is_empty() {
+        return Ok(call);
+    }
+
+    for var_ref in refs {
+        if var_ref.offset + 32 > call.data.len() {
+            return Err(crate::EilError::Generic(format!(
+                "Runtime variable '{}' offset {} out of calldata bounds",
+                var_ref.name, var_ref.offset
+            )));
+        }
+    }
+
+    let value = call.value.unwrap_or_default();
+    let data = IRuntimeVarsHelper::execWithVarsCall {
+        target: call.target,
+        value,
+        data: call.data,
+        refs: refs
+            .iter()
+            .map(|var_ref| {
+                Ok(IRuntimeVarsHelper::VarRef {
+                    name: var_name_to_bytes8(&var_ref.name)?,
+                    offset: U256::from(var_ref.offset),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?,
+    }
+    .abi_encode();
+
+    Ok(Call {
+        target: helper,
+        data: data.into(),
+        value: call.value,
+    })
+}
+
+/// Encode the helper's `executeBatch` calldata running `calls` in the account's context
+pub fn encode_execute_batch(calls: &[Call]) -> Hex {
+    IRuntimeVarsHelper::executeBatchCall {
+        calls: calls
+            .iter()
+            .map(|call| IRuntimeVarsHelper::Call {
+                target: call.target,
+                value: call.value.unwrap_or_default(),
+                data: call.data.clone(),
+            })
+            .collect(),
+    }
+    .abi_encode()
+    .into()
+}
+
+/// Resolve an amount argument at `arg_index` of a call with static arguments
+/// Runtime amounts are encoded as a zero placeholder plus a [`VarRef`] to its word
+pub fn amount_arg(amount: &Amount, arg_index: usize) -> (DynSolValue, Option<VarRef>) {
+    match amount {
+        Amount::Fixed(value) => (DynSolValue::Uint(*value, 256), None),
+        Amount::Runtime(var) => (
+            DynSolValue::Uint(U256::ZERO, 256),
+            Some(VarRef {
+                name: var.name.clone(),
+                offset: 4 + 32 * arg_index,
+            }),
+        ),
+    }
+}
+
+/// Whether a function is a static call returning a single 32-byte word
+fn is_static_word_call(function: &alloy::json_abi::Function) -> bool {
+    use alloy::{dyn_abi::DynSolType, dyn_abi::Specifier, json_abi::StateMutability};
+
+    let is_view = matches!(
+        function.state_mutability,
+        StateMutability::View | StateMutability::Pure
+    );
+    let returns_word = match function.outputs.as_slice() {
+        [output] => matches!(
+            output.resolve(),
+            Ok(DynSolType::Bool
+                | DynSolType::Int(_)
+                | DynSolType::Uint(_)
+                | DynSolType::FixedBytes(_)
+                | DynSolType::Address)
+        ),
+        _ => false,
+    };
+    is_view && returns_word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_call(function_json: &str) -> FunctionCall {
+        FunctionCall {
+            target: Address::repeat_byte(0x11),
+            abi: serde_json::from_str(&format!("[{}]", function_json)).unwrap(),
+            function_name: "f".to_string(),
+            args: vec![DynSolValue::Address(Address::repeat_byte(0x22))],
+            value: None,
+        }
+    }
+
+    const VIEW_UINT: &str = r#"{"type":"function","name":"f","stateMutability":"view",
+        "inputs":[{"name":"a","type":"address"}],"outputs":[{"name":"","type":"uint256"}]}"#;
+
+    #[test]
+    fn test_var_name_to_bytes8() {
+        let name = var_name_to_bytes8("amt").unwrap();
+        assert_eq!(name.as_slice(), b"amt\0\0\0\0\0");
+        assert!(var_name_to_bytes8("123456789").is_err());
+    }
+
+    #[test]
+    fn test_encode_set_var() {
+        let helper = Address::repeat_byte(0xee);
+        let call = encode_set_var(helper, "bal", &create_test_call(VIEW_UINT)).unwrap();
+
+        assert_eq!(call.target, helper);
+        let decoded = IRuntimeVarsHelper::setVarCall::abi_decode(&call.data, true).unwrap();
+        assert_eq!(decoded.name, var_name_to_bytes8("bal").unwrap());
+        assert_eq!(decoded.target, Address::repeat_byte(0x11));
+        assert_eq!(decoded.data.len(), 4 + 32);
+    }
+
+    #[test]
+    fn test_encode_set_var_rejects_dynamic_output() {
+        let call = create_test_call(
+            r#"{"type":"function","name":"f","stateMutability":"view",
+            "inputs":[{"name":"a","type":"address"}],"outputs":[{"name":"","type":"string"}]}"#,
+        );
+        let result = encode_set_var(Address::ZERO, "name", &call);
+        assert!(matches!(result, Err(crate::EilError::DynamicVariableCall(n)) if n == "name"));
+    }
+
+    #[test]
+    fn test_encode_set_var_rejects_non_view() {
+        let call = create_test_call(
+            r#"{"type":"function","name":"f","stateMutability":"nonpayable",
+            "inputs":[{"name":"a","type":"address"}],"outputs":[{"name":"","type":"uint256"}]}"#,
+        );
+        let result = encode_set_var(Address::ZERO, "v", &call);
+        assert!(matches!(
+            result,
+            Err(crate::EilError::DynamicVariableCall(_))
+        ));
+    }
+
+    #[test]
+    fn test_encode_call_with_vars() {
+        let helper = Address::repeat_byte(0xee);
+        let (arg, var_ref) = amount_arg(&Amount::Runtime(RuntimeVar::new("bal").unwrap()), 1);
+        assert_eq!(arg, DynSolValue::Uint(U256::ZERO, 256));
+        let var_ref = var_ref.unwrap();
+        assert_eq!(var_ref.offset, 36);
+
+        let call = Call {
+            target: Address::repeat_byte(0x11),
+            data: Hex::from(vec![0u8; 68]),
+            value: None,
+        };
+        let wrapped = encode_call_with_vars(helper, call, &[var_ref]).unwrap();
+
+        assert_eq!(wrapped.target, helper);
+        let decoded =
+            IRuntimeVarsHelper::execWithVarsCall::abi_decode(&wrapped.data, true).unwrap();
+        assert_eq!(decoded.target, Address::repeat_byte(0x11));
+        assert_eq!(decoded.refs.len(), 1);
+        assert_eq!(decoded.refs[0].offset, U256::from(36));
+    }
+
+    #[test]
+    fn test_encode_call_with_vars_out_of_bounds() {
+        let call = Call {
+            target: Address::repeat_byte(0x11),
+            data: Hex::from(vec![0u8; 36]),
+            value: None,
+        };
+        let var_ref = VarRef {
+            name: "bal".to_string(),
+            offset: 36,
+        };
+        assert!(encode_call_with_vars(Address::ZERO, call, &[var_ref]).is_err());
+    }
+
+    #[test]
+    fn test_encode_call_without_vars_unchanged() {
+        let call = Call {
+            target: Address::repeat_byte(0x11),
+            data: Hex::from(vec![1, 2, 3]),
+            value: None,
+        };
+        let result = encode_call_with_vars(Address::ZERO, call.clone(), &[]).unwrap();
+        assert_eq!(result.target, call.target);
+        assert_eq!(result.data, call.data);
+    }
+
+    #[test]
+    fn test_encode_execute_batch() {
+        let calls = vec![
+            Call {
+                target: Address::repeat_byte(0xee),
+                data: Hex::from(vec![1, 2]),
+                value: None,
+            },
+            Call {
+                target: Address::repeat_byte(0x11),
+                data: Hex::from(vec![3]),
+                value: Some(U256::from(5)),
+            },
+        ];
+        let data = encode_execute_batch(&calls);
+
+        let decoded = IRuntimeVarsHelper::executeBatchCall::abi_decode(&data, true).unwrap();
+        assert_eq!(decoded.calls.len(), 2);
+        assert_eq!(decoded.calls[0].target, Address::repeat_byte(0xee));
+        assert_eq!(decoded.calls[0].value, U256::ZERO);
+        assert_eq!(decoded.calls[1].data, Hex::from(vec![3]));
+        assert_eq!(decoded.calls[1].value, U256::from(5));
+    }
+}
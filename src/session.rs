@@ -0,0 +1,156 @@
+//! Ephemeral session key of a CrossChainBuilder
+//!
+//! Destination UserOperations are built before their vouchers exist. At build time
+//! their `paymasterData` commits to the address of a per-session ephemeral key; once
+//! the vouchers are issued, the ephemeral key signs them for the operation's sender and
+//! nonce, producing the [`SessionData`] appended to `paymasterData` with the vouchers.
+//! The EntryPoint v0.7 userOpHash covers the whole `paymasterAndData`, so the user
+//! signs the operation once its vouchers are attached.
+
+use crate::{
+    contract_types::{SessionData, Voucher},
+    contracts::ICrossChainPaymaster,
+    types::*,
+    Result,
+};
+use alloy::{
+    primitives::{keccak256, B256, U256},
+    signers::{local::PrivateKeySigner, SignerSync},
+    sol_types::SolValue,
+};
+use std::fmt;
+
+/// Per-session secp256k1 key authorizing vouchers on behalf of the user
+///
+/// The key is generated from the thread-local CSPRNG, is zeroized when dropped and is
+/// intentionally neither `Clone` nor serializable.
+pub struct EphemeralSigner {
+    signer: PrivateKeySigner,
+}
+
+impl EphemeralSigner {
+    /// Generate a new random ephemeral key
+    pub fn random() -> Self {
+        Self {
+            signer: PrivateKeySigner::random(),
+        }
+    }
+
+    /// Address of the ephemeral key
+    pub fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    /// `paymasterData` prefix committing to the ephemeral key: `abi.encode(address)`
+    pub fn commitment(&self) -> Hex {
+        Hex::from(self.address().abi_encode())
+    }
+
+    /// Sign the vouchers consumed by the UserOperation of `sender` with `nonce` on `chain_id`
+    ///
+    /// `data` is `abi.encode(address ephemeralSigner, uint256 chainId, address sender,
+    /// uint256 nonce)` and the signature covers
+    /// `keccak256(abi.encode(chainId, sender, nonce, keccak256(abi.encode(vouchers))))`.
+    pub fn sign_session(
+        &self,
+        chain_id: ChainId,
+        sender: Address,
+        nonce: U256,
+        vouchers: &[Voucher],
+    ) -> Result<SessionData> {
+        let signature = self
+            .signer
+            .sign_hash_sync(&session_hash(chain_id, sender, nonce, vouchers))
+            .map_err(|e| crate::EilError::AlloySigner(e.to_string()))?;
+
+        Ok(SessionData {
+            data: Hex::from(
+                (self.address(), U256::from(chain_id), sender, nonce).abi_encode_params(),
+            ),
+            ephemeral_signature: Hex::from(signature.as_bytes().to_vec()),
+        })
+    }
+}
+
+impl fmt::Debug for EphemeralSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EphemeralSigner")
+            .field("address", &self.address())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Hash signed by the ephemeral key for the vouchers of a UserOperation
+pub fn session_hash(chain_id: ChainId, sender: Address, nonce: U256, vouchers: &[Voucher]) -> B256 {
+    let vouchers: Vec<ICrossChainPaymaster::Voucher> = vouchers.iter().map(Into::into).collect();
+    keccak256(
+        (
+            U256::from(chain_id),
+            sender,
+            nonce,
+            keccak256(vouchers.abi_encode()),
+        )
+            .abi_encode(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::signers::Signature;
+
+    #[test]
+    fn test_ephemeral_signers_are_unique() {
+        let first = EphemeralSigner::random();
+        let second = EphemeralSigner::random();
+        assert_ne!(first.address(), second.address());
+    }
+
+    #[test]
+    fn test_commitment() {
+        let signer = EphemeralSigner::random();
+        let commitment = signer.commitment();
+        assert_eq!(commitment.len(), 32);
+        assert_eq!(&commitment[12..], signer.address().as_slice());
+    }
+
+    #[test]
+    fn test_sign_session_recovers_ephemeral_address() {
+        let signer = EphemeralSigner::random();
+        let sender = Address::repeat_byte(0x22);
+        let nonce = U256::from(7);
+        let vouchers = vec![Voucher {
+            request: crate::test_utils::create_test_voucher_request(),
+            signature: Hex::from(vec![0xcd; 65]),
+        }];
+
+        let session = signer.sign_session(20, sender, nonce, &vouchers).unwrap();
+
+        let (address, chain_id, session_sender, session_nonce) =
+            <(Address, U256, Address, U256)>::abi_decode_params(&session.data, true).unwrap();
+        assert_eq!(address, signer.address());
+        assert_eq!(chain_id, U256::from(20));
+        assert_eq!(session_sender, sender);
+        assert_eq!(session_nonce, nonce);
+        let signature = Signature::try_from(session.ephemeral_signature.as_ref()).unwrap();
+        let recovered = signature
+            .recover_address_from_prehash(&session_hash(20, sender, nonce, &vouchers))
+            .unwrap();
+        assert_eq!(recovered, signer.address());
+
+        // Bound to the operation it was signed for
+        assert_ne!(
+            session_hash(20, sender, nonce, &vouchers),
+            session_hash(20, sender, nonce + U256::from(1), &vouchers)
+        );
+    }
+
+    #[test]
+    fn test_debug_does_not_leak_key() {
+        let signer = EphemeralSigner::random();
+        let debug = format!("{:?}", signer);
+        let key = hex::encode(signer.signer.to_bytes());
+        assert!(!debug.contains(&key));
+        assert!(debug.contains("address"));
+    }
+}
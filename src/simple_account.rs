@@ -0,0 +1,171 @@
+//! eth-infinitism SimpleAccount implementation of [`MultiChainSmartAccount`](crate::account::MultiChainSmartAccount)
+
+use crate::{
+    account::{CallEncoder, SmartAccount},
+    contracts::ISimpleAccount,
+    types::*,
+};
+use alloy::sol_types::SolCall;
+
+/// SimpleAccount executing calls through `execute` / `executeBatch`
+pub type SimpleAccount = SmartAccount<SimpleAccountEncoder>;
+
+/// SimpleAccount call encoding, see [`encode_simple_account_calls`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimpleAccountEncoder;
+
+impl CallEncoder for SimpleAccountEncoder {
+    fn encode_calls(&self, calls: &[Call]) -> Hex {
+        encode_simple_account_calls(calls)
+    }
+}
+
+/// Encode calls as SimpleAccount `execute` (single call) or `executeBatch` calldata
+pub fn encode_simple_account_calls(calls: &[Call]) -> Hex {
+    match calls {
+        [] => Hex::new(),
+        [call] => ISimpleAccount::executeCall {
+            dest: call.target,
+            value: call.value.unwrap_or_default(),
+            func: call.data.clone(),
+        }
+        .abi_encode()
+        .into(),
+        _ => ISimpleAccount::executeBatchCall {
+            dest: calls.iter().map(|call| call.target).collect(),
+            value: calls
+                .iter()
+                .map(|call| call.value.unwrap_or_default())
+                .collect(),
+            func: calls.iter().map(|call| call.data.clone()).collect(),
+        }
+        .abi_encode()
+        .into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        account::{BaseMultichainSmartAccount, MultiChainSmartAccount},
+        network::NetworkEnvironment,
+        test_utils::{
+            call_request, create_test_config, MockBundlerManager, MockRpcServer, MockSigner,
+        },
+    };
+    use alloy::{
+        primitives::{aliases::U192, B256, U256},
+        sol_types::SolValue,
+    };
+    use std::collections::HashMap;
+
+    const ACCOUNT: Address = Address::repeat_byte(0x22);
+
+    fn create_test_account(url: &str) -> SimpleAccount {
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].rpc_url = url.to_string();
+        let base = BaseMultichainSmartAccount::new(
+            HashMap::from([(10, ACCOUNT)]),
+            Box::new(MockSigner::new()),
+            Box::new(MockBundlerManager::new()),
+        );
+        SimpleAccount::new(base, NetworkEnvironment::new(&config))
+    }
+
+    fn create_test_calls(count: u8) -> Vec<Call> {
+        (0..count)
+            .map(|i| Call {
+                target: Address::repeat_byte(0x30 + i),
+                data: Hex::from(vec![i; 4]),
+                value: Some(U256::from(i)),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_empty() {
+        assert!(encode_simple_account_calls(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_encode_single_call() {
+        let calls = create_test_calls(1);
+        let data = encode_simple_account_calls(&calls);
+
+        let decoded = ISimpleAccount::executeCall::abi_decode(&data, true).unwrap();
+        assert_eq!(decoded.dest, calls[0].target);
+        assert_eq!(decoded.value, U256::ZERO);
+        assert_eq!(decoded.func, calls[0].data);
+    }
+
+    #[test]
+    fn test_encode_batch() {
+        let calls = create_test_calls(3);
+        let data = encode_simple_account_calls(&calls);
+
+        let decoded = ISimpleAccount::executeBatchCall::abi_decode(&data, true).unwrap();
+        assert_eq!(
+            decoded.dest,
+            calls.iter().map(|c| c.target).collect::<Vec<_>>()
+        );
+        assert_eq!(decoded.value[2], U256::from(2));
+        assert_eq!(decoded.func[1], calls[1].data);
+    }
+
+    #[tokio::test]
+    async fn test_get_nonce() {
+        let server = MockRpcServer::start(|method, _| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            "eth_call" => Ok(serde_json::json!(Hex::from(U256::from(42).abi_encode()))),
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let account = create_test_account(&server.url).with_nonce_key(U192::from(7));
+
+        assert_eq!(account.get_nonce(10).await.unwrap(), U256::from(42));
+
+        let requests = server.requests.lock().unwrap();
+        let (_, params) = requests.iter().find(|(m, _)| m == "eth_call").unwrap();
+        let (_, input) = call_request(params);
+        let decoded =
+            crate::contracts::IEntryPoint::getNonceCall::abi_decode(&input, true).unwrap();
+        assert_eq!(decoded.sender, ACCOUNT);
+        assert_eq!(decoded.key, U192::from(7));
+    }
+
+    #[tokio::test]
+    async fn test_get_factory_args() {
+        let server = MockRpcServer::start(|method, _| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            "eth_getCode" => Ok(serde_json::json!("0x")),
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let factory = Address::repeat_byte(0xfa);
+        let factory_data = Hex::from(B256::repeat_byte(1).to_vec());
+
+        let account = create_test_account(&server.url);
+        assert_eq!(account.get_factory_args(10).await.unwrap(), (None, None));
+
+        let account = account.with_factory(factory, factory_data.clone());
+        assert_eq!(
+            account.get_factory_args(10).await.unwrap(),
+            (Some(factory), Some(factory_data))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_factory_args_deployed() {
+        let server = MockRpcServer::start(|method, _| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            "eth_getCode" => Ok(serde_json::json!("0x6080")),
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let account = create_test_account(&server.url)
+            .with_factory(Address::repeat_byte(0xfa), Hex::from(vec![1, 2, 3]));
+
+        assert_eq!(account.get_factory_args(10).await.unwrap(), (None, None));
+    }
+}
@@ -0,0 +1,580 @@
+//! Pre-execution simulation of batches
+//!
+//! Each UserOperation is dry-run through `EntryPointSimulations.simulateHandleOp` in an
+//! `eth_simulateV1` request: an `eth_call` over a block with state overrides, which also
+//! returns the emitted logs. The simulations code is overridden at the EntryPoint
+//! address, so unsigned UserOperations can be simulated with a dummy signature.
+//! A plain `eth_call` is not enough: `simulateHandleOp` does not return whether the
+//! execution phase succeeded, which is only reported by the EntryPoint events.
+//!
+//! Vouchers of destination batches are not issued yet, so their paymaster cannot be
+//! exercised. Instead, the paymaster crediting the voucher assets is modelled by ERC20
+//! transfers from the paymaster to the voucher recipient, executed in the same block
+//! before the UserOperation, which is run without its paymaster and with its sender
+//! prefunded through a balance override. Native assets are added to that override.
+
+use crate::{
+    contract_types::{SingleChainBatch, VoucherRequest},
+    contracts::{IEntryPoint, IEntryPointSimulations, IERC20},
+    gas::DUMMY_SIGNATURE,
+    multichain::NATIVE_TOKEN,
+    network::NetworkEnvironment,
+    revert::{decode_revert, DecodedRevert, RevertSource},
+    types::*,
+    Result,
+};
+use alloy::{
+    eips::BlockNumberOrTag,
+    json_abi::Error as AbiError,
+    primitives::U256,
+    providers::Provider,
+    rpc::types::{
+        simulate::{SimBlock, SimCallResult, SimulatePayload},
+        state::{AccountOverride, StateOverride},
+        Log, TransactionRequest,
+    },
+    sol_types::SolCall,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// JSON-RPC error code of nodes without `eth_simulateV1`
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// Outcome of simulating a batch
+#[derive(Debug, Clone)]
+pub struct BatchSimulation {
+    /// Index of the batch
+    pub index: usize,
+    /// Chain the batch was simulated on
+    pub chain_id: ChainId,
+    /// Whether validation and execution succeeded
+    pub success: bool,
+    /// Gas used by the UserOperation
+    pub gas_used: U256,
+    /// Decoded revert of a failed batch
+    pub revert: Option<DecodedRevert>,
+    /// Logs emitted by the UserOperation
+    pub logs: Vec<Log>,
+}
+
+impl BatchSimulation {
+    /// Fail with the decoded revert if the simulation failed
+    pub fn ensure_success(&self) -> Result<()> {
+        match &self.revert {
+            _ if self.success => Ok(()),
+            Some(revert) => Err(revert.clone().into()),
+            None => Err(crate::EilError::CallReverted(format!(
+                "batch {} reverted without data",
+                self.index
+            ))),
+        }
+    }
+}
+
+/// Call results of a simulated block; the block header is ignored
+#[derive(Debug, Deserialize)]
+struct SimulatedCalls {
+    calls: Vec<SimCallResult>,
+}
+
+/// Simulate all batches concurrently
+///
+/// `voucher_requests` holds the voucher requests by ref ID, used to credit the
+/// assets of destination batches.
+pub async fn simulate_batches(
+    network_env: &NetworkEnvironment,
+    batches: &[SingleChainBatch],
+    voucher_requests: &HashMap<String, VoucherRequest>,
+) -> Result<Vec<BatchSimulation>> {
+    futures::future::try_join_all(
+        batches
+            .iter()
+            .enumerate()
+            .map(|(index, batch)| simulate_batch(network_env, index, batch, voucher_requests)),
+    )
+    .await
+}
+
+/// Simulate a single batch
+pub async fn simulate_batch(
+    network_env: &NetworkEnvironment,
+    index: usize,
+    batch: &SingleChainBatch,
+    voucher_requests: &HashMap<String, VoucherRequest>,
+) -> Result<BatchSimulation> {
+    let chain_id = batch.chain_id;
+    let entry_point = network_env.entry_point(chain_id)?;
+    let code = network_env
+        .config()
+        .chain_info(chain_id)
+        .and_then(|info| info.entry_point_simulations.clone())
+        .ok_or(crate::EilError::SimulationNotConfigured(chain_id))?;
+    let provider = network_env.create_provider(chain_id).await?;
+
+    let mut user_op = batch.user_op.clone();
+    if user_op.signature.is_empty() {
+        user_op.signature = Hex::from(DUMMY_SIGNATURE);
+    }
+    let mut overrides = StateOverride::default();
+    overrides.insert(
+        entry_point,
+        AccountOverride {
+            code: Some(code),
+            ..Default::default()
+        },
+    );
+
+    let mut calls = Vec::new();
+    if !batch.input_voucher_requests.is_empty() {
+        let mut native_credit = U256::ZERO;
+        for voucher in &batch.input_voucher_requests {
+            let request = voucher_requests
+                .get(&voucher.ref_id)
+                .ok_or_else(|| crate::EilError::VoucherNotFound(voucher.ref_id.clone()))?;
+            calls.extend(credit_calls(request));
+            native_credit += native_credit_of(request);
+        }
+
+        let balance = provider
+            .get_balance(user_op.sender)
+            .await
+            .map_err(|e| crate::EilError::AlloyProvider(e.to_string()))?;
+        overrides.insert(
+            user_op.sender,
+            AccountOverride {
+                balance: Some(balance + batch.gas_estimate.max_cost() + native_credit),
+                ..Default::default()
+            },
+        );
+        user_op.paymaster = None;
+        user_op.paymaster_verification_gas_limit = None;
+        user_op.paymaster_post_op_gas_limit = None;
+        user_op.paymaster_data = None;
+    }
+    let credits = calls.len();
+
+    let simulate_call = IEntryPointSimulations::simulateHandleOpCall {
+        op: crate::user_op::pack_user_op(&user_op)?.into(),
+        target: Address::ZERO,
+        targetCallData: Hex::new(),
+    };
+    calls.push(
+        TransactionRequest::default()
+            .to(entry_point)
+            .input(simulate_call.abi_encode().into()),
+    );
+
+    let payload = SimulatePayload::default().extend(
+        SimBlock::default()
+            .with_state_overrides(overrides)
+            .extend_calls(calls),
+    );
+    let blocks: Vec<SimulatedCalls> = provider
+        .raw_request("eth_simulateV1".into(), (payload, BlockNumberOrTag::Latest))
+        .await
+        .map_err(|e| match e.as_error_resp() {
+            Some(error) if error.code == METHOD_NOT_FOUND => {
+                crate::EilError::SimulationUnsupported(chain_id)
+            }
+            _ => crate::EilError::AlloyProvider(format!("eth_simulateV1: {}", e)),
+        })?;
+
+    let mut results = blocks
+        .into_iter()
+        .next()
+        .map(|block| block.calls)
+        .unwrap_or_default();
+    if results.len() != credits + 1 {
+        return Err(crate::EilError::Generic(format!(
+            "eth_simulateV1 returned {} call results, expected {}",
+            results.len(),
+            credits + 1
+        )));
+    }
+
+    let result = results.pop().expect("checked length");
+    if let Some(credit) = results.iter().find(|credit| !credit.status) {
+        let mut revert = decode_revert(
+            &credit.return_data,
+            RevertSource::Paymaster,
+            &batch.custom_errors,
+        );
+        revert.reason = format!("Voucher credit failed: {}", revert.reason);
+        return Ok(BatchSimulation {
+            index,
+            chain_id,
+            success: false,
+            gas_used: U256::ZERO,
+            revert: Some(revert),
+            logs: Vec::new(),
+        });
+    }
+
+    Ok(simulation_outcome(
+        index,
+        chain_id,
+        entry_point,
+        result,
+        &batch.custom_errors,
+    ))
+}
+
+/// ERC20 transfers modelling the destination paymaster crediting a voucher's assets
+/// Native assets are credited through the sender's balance override instead.
+fn credit_calls(request: &VoucherRequest) -> Vec<TransactionRequest> {
+    let destination = &request.destination;
+    destination
+        .assets
+        .iter()
+        .filter(|asset| asset.erc20_token != NATIVE_TOKEN)
+        .map(|asset| {
+            let data = IERC20::transferCall {
+                to: destination.sender,
+                amount: asset.amount,
+            }
+            .abi_encode();
+            TransactionRequest::default()
+                .from(destination.paymaster)
+                .to(asset.erc20_token)
+                .input(data.into())
+        })
+        .collect()
+}
+
+/// Native currency credited to the destination sender by a voucher
+fn native_credit_of(request: &VoucherRequest) -> U256 {
+    request
+        .destination
+        .assets
+        .iter()
+        .filter(|asset| asset.erc20_token == NATIVE_TOKEN)
+        .fold(U256::ZERO, |total, asset| total + asset.amount)
+}
+
+/// Read the outcome of a `simulateHandleOp` call from its status and EntryPoint events
+fn simulation_outcome(
+    index: usize,
+    chain_id: ChainId,
+    entry_point: Address,
+    result: SimCallResult,
+    errors: &[AbiError],
+) -> BatchSimulation {
+    let mut simulation = BatchSimulation {
+        index,
+        chain_id,
+        success: result.status,
+        gas_used: U256::from(result.gas_used),
+        revert: None,
+        logs: result.logs,
+    };
+    if !result.status {
+        simulation.revert = Some(decode_revert(
+            &result.return_data,
+            RevertSource::EntryPoint,
+            errors,
+        ));
+        return simulation;
+    }
+
+    for log in simulation
+        .logs
+        .iter()
+        .filter(|log| log.address() == entry_point)
+    {
+        if let Ok(event) = log.log_decode::<IEntryPoint::UserOperationEvent>() {
+            simulation.success = event.inner.success;
+            simulation.gas_used = event.inner.actualGasUsed;
+        } else if let Ok(event) = log.log_decode::<IEntryPoint::UserOperationRevertReason>() {
+            simulation.revert = Some(decode_revert(
+                &event.inner.revertReason,
+                RevertSource::Call,
+                errors,
+            ));
+        }
+    }
+    simulation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contract_types::{Asset, SdkVoucherRequest},
+        test_utils::{
+            create_test_batch, create_test_config, create_test_voucher_request, MockRpcServer,
+        },
+    };
+    use alloy::{
+        primitives::B256,
+        sol_types::{SolError, SolEvent, SolValue},
+    };
+    use std::sync::{Arc, Mutex};
+
+    const ENTRY_POINT: &str = "0x0000000071727De22E5E9d8BAf0edAc6f37da032";
+
+    fn create_test_env(url: &str, chain_id: ChainId) -> NetworkEnvironment {
+        let mut config = create_test_config(vec![chain_id]);
+        config.chain_infos[0].rpc_url = url.to_string();
+        config.chain_infos[0].entry_point_simulations = Some(Hex::from(vec![0x60, 0x00]));
+        NetworkEnvironment::new(&config)
+    }
+
+    fn call_json(
+        status: bool,
+        return_data: Vec<u8>,
+        logs: Vec<serde_json::Value>,
+    ) -> serde_json::Value {
+        serde_json::json!({
+            "status": if status { "0x1" } else { "0x0" },
+            "returnData": Hex::from(return_data),
+            "gasUsed": "0x5208",
+            "logs": logs,
+        })
+    }
+
+    fn log_json(topics: Vec<B256>, data: Vec<u8>) -> serde_json::Value {
+        serde_json::json!({
+            "address": ENTRY_POINT,
+            "topics": topics,
+            "data": Hex::from(data),
+            "blockHash": B256::repeat_byte(0x66),
+            "blockNumber": "0x11",
+            "transactionHash": B256::repeat_byte(0x77),
+            "transactionIndex": "0x0",
+            "logIndex": "0x0",
+            "removed": false,
+        })
+    }
+
+    fn user_op_event(success: bool, gas_used: u64) -> serde_json::Value {
+        log_json(
+            vec![
+                IEntryPoint::UserOperationEvent::SIGNATURE_HASH,
+                B256::ZERO,
+                B256::ZERO,
+                B256::ZERO,
+            ],
+            (U256::ZERO, success, U256::from(1), U256::from(gas_used)).abi_encode_params(),
+        )
+    }
+
+    /// Mock RPC answering `eth_simulateV1` with `calls` and recording its params
+    async fn start_simulation_rpc(
+        calls: Vec<serde_json::Value>,
+    ) -> (MockRpcServer, Arc<Mutex<Vec<serde_json::Value>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let server = MockRpcServer::start(move |method, params| match method {
+            "eth_chainId" => Ok(serde_json::json!("0x14")),
+            "eth_getBalance" => Ok(serde_json::json!("0x64")),
+            "eth_simulateV1" => {
+                recorded.lock().unwrap().push(params.clone());
+                Ok(serde_json::json!([{ "number": "0x11", "calls": calls.clone() }]))
+            }
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        (server, requests)
+    }
+
+    #[tokio::test]
+    async fn test_simulate_batch_success() {
+        let (server, requests) = start_simulation_rpc(vec![call_json(
+            true,
+            Vec::new(),
+            vec![user_op_event(true, 1234)],
+        )])
+        .await;
+        let env = create_test_env(&server.url, 20);
+
+        let simulation = simulate_batch(&env, 0, &create_test_batch(20), &HashMap::new())
+            .await
+            .unwrap();
+
+        assert!(simulation.success);
+        assert_eq!(simulation.gas_used, U256::from(1234));
+        assert!(simulation.revert.is_none());
+        assert!(simulation.ensure_success().is_ok());
+        assert_eq!(simulation.logs.len(), 1);
+
+        let params = &requests.lock().unwrap()[0];
+        let block = &params[0]["blockStateCalls"][0];
+        assert_eq!(
+            block["stateOverrides"][ENTRY_POINT.to_lowercase()]["code"],
+            "0x6000"
+        );
+        let calls = block["calls"].as_array().unwrap();
+        assert_eq!(calls.len(), 1);
+        let data: Hex = serde_json::from_value(calls[0]["input"].clone()).unwrap();
+        assert_eq!(
+            data[..4],
+            IEntryPointSimulations::simulateHandleOpCall::SELECTOR
+        );
+    }
+
+    #[tokio::test]
+    async fn test_simulate_destination_credits_vouchers() {
+        let reason = alloy::sol_types::Revert::from("nope").abi_encode();
+        let revert_log = log_json(
+            vec![
+                IEntryPoint::UserOperationRevertReason::SIGNATURE_HASH,
+                B256::ZERO,
+                B256::ZERO,
+            ],
+            (U256::ZERO, Hex::from(reason)).abi_encode_params(),
+        );
+        let (server, requests) = start_simulation_rpc(vec![
+            call_json(true, true.abi_encode(), Vec::new()),
+            call_json(
+                true,
+                Vec::new(),
+                vec![revert_log, user_op_event(false, 900)],
+            ),
+        ])
+        .await;
+        let env = create_test_env(&server.url, 20);
+
+        let mut batch = create_test_batch(20);
+        batch.input_voucher_requests = vec![SdkVoucherRequest {
+            ref_id: "v1".to_string(),
+            source_chain_id: Some(10),
+            destination_chain_id: 20,
+            tokens: Vec::new(),
+            target: None,
+        }];
+        let mut request = create_test_voucher_request();
+        request.destination.assets = vec![Asset {
+            erc20_token: Address::repeat_byte(0x44),
+            amount: U256::from(500),
+        }];
+        let voucher_requests = HashMap::from([("v1".to_string(), request)]);
+
+        let simulation = simulate_batch(&env, 1, &batch, &voucher_requests)
+            .await
+            .unwrap();
+
+        assert!(!simulation.success);
+        assert_eq!(simulation.index, 1);
+        assert_eq!(simulation.gas_used, U256::from(900));
+        let revert = simulation.revert.unwrap();
+        assert_eq!(revert.source, RevertSource::Call);
+        assert_eq!(revert.reason, "revert: nope");
+
+        let params = &requests.lock().unwrap()[0];
+        let block = &params[0]["blockStateCalls"][0];
+        let sender = Address::repeat_byte(0x22).to_string().to_lowercase();
+        assert!(block["stateOverrides"][sender]["balance"].is_string());
+        let credit = &block["calls"][0];
+        assert_eq!(
+            credit["from"],
+            Address::repeat_byte(0x33).to_string().to_lowercase()
+        );
+        assert_eq!(
+            credit["to"],
+            Address::repeat_byte(0x44).to_string().to_lowercase()
+        );
+        let data: Hex = serde_json::from_value(credit["input"].clone()).unwrap();
+        let transfer = IERC20::transferCall::abi_decode(&data, true).unwrap();
+        assert_eq!(transfer.to, Address::repeat_byte(0x22));
+        assert_eq!(transfer.amount, U256::from(500));
+    }
+
+    #[tokio::test]
+    async fn test_simulate_credits_native_assets_to_balance() {
+        let (server, requests) = start_simulation_rpc(vec![
+            call_json(true, true.abi_encode(), Vec::new()),
+            call_json(true, Vec::new(), vec![user_op_event(true, 900)]),
+        ])
+        .await;
+        let env = create_test_env(&server.url, 20);
+
+        let mut batch = create_test_batch(20);
+        batch.input_voucher_requests = vec![SdkVoucherRequest {
+            ref_id: "v1".to_string(),
+            source_chain_id: Some(10),
+            destination_chain_id: 20,
+            tokens: Vec::new(),
+            target: None,
+        }];
+        let mut request = create_test_voucher_request();
+        request.destination.assets = vec![
+            Asset {
+                erc20_token: NATIVE_TOKEN,
+                amount: U256::from(500),
+            },
+            Asset {
+                erc20_token: Address::repeat_byte(0x44),
+                amount: U256::from(300),
+            },
+        ];
+        let voucher_requests = HashMap::from([("v1".to_string(), request)]);
+
+        let simulation = simulate_batch(&env, 1, &batch, &voucher_requests)
+            .await
+            .unwrap();
+
+        assert!(simulation.success);
+        let params = &requests.lock().unwrap()[0];
+        let block = &params[0]["blockStateCalls"][0];
+        // Only the ERC20 asset is credited by a transfer call
+        assert_eq!(block["calls"].as_array().unwrap().len(), 2);
+        let sender = Address::repeat_byte(0x22).to_string().to_lowercase();
+        let balance: U256 =
+            serde_json::from_value(block["stateOverrides"][sender]["balance"].clone()).unwrap();
+        assert_eq!(
+            balance,
+            U256::from(0x64) + batch.gas_estimate.max_cost() + U256::from(500)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_simulate_decodes_failed_op() {
+        let failed = IEntryPoint::FailedOp {
+            opIndex: U256::ZERO,
+            reason: "AA21 didn't pay prefund".to_string(),
+        };
+        let (server, _) =
+            start_simulation_rpc(vec![call_json(false, failed.abi_encode(), Vec::new())]).await;
+        let env = create_test_env(&server.url, 20);
+
+        let simulation = simulate_batch(&env, 0, &create_test_batch(20), &HashMap::new())
+            .await
+            .unwrap();
+
+        assert!(!simulation.success);
+        assert_eq!(
+            simulation.revert.as_ref().unwrap().reason,
+            "AA21 didn't pay prefund"
+        );
+        assert!(matches!(
+            simulation.ensure_success(),
+            Err(crate::EilError::AccountValidationFailed { code, .. }) if code == "AA21"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_simulate_requires_simulations_code() {
+        let env = NetworkEnvironment::new(&create_test_config(vec![20]));
+        let result = simulate_batch(&env, 0, &create_test_batch(20), &HashMap::new()).await;
+        assert!(matches!(
+            result,
+            Err(crate::EilError::SimulationNotConfigured(20))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_simulate_requires_simulate_v1() {
+        let server = MockRpcServer::start(|method, _| match method {
+            "eth_chainId" => Ok(serde_json::json!("0x14")),
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+        let env = create_test_env(&server.url, 20);
+        let result = simulate_batch(&env, 0, &create_test_batch(20), &HashMap::new()).await;
+        assert!(matches!(
+            result,
+            Err(crate::EilError::SimulationUnsupported(20))
+        ));
+    }
+}
@@ -0,0 +1,215 @@
+//! Persistence of execution state
+//!
+//! The executor saves an [`ExecutionState`] after every state change, so an execution
+//! interrupted by a restart can be continued with [`crate::executor::CrossChainExecutor::resume`].
+
+use crate::{
+    contract_types::{BatchStatusInfo, VoucherRequest},
+    Result,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Serializable state of a cross-chain execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionState {
+    /// Execution ID
+    pub id: String,
+    /// Batches with their status, tx hashes and collected vouchers
+    pub batches: Vec<BatchStatusInfo>,
+    /// Voucher requests by reference ID
+    pub voucher_requests: HashMap<String, VoucherRequest>,
+}
+
+/// Storage backend for execution state
+#[async_trait]
+pub trait ExecutionStore: Send + Sync {
+    /// Save the state, replacing any previous state with the same ID
+    async fn save(&self, state: &ExecutionState) -> Result<()>;
+
+    /// Load the state with the given ID
+    async fn load(&self, id: &str) -> Result<Option<ExecutionState>>;
+
+    /// Remove the state with the given ID
+    async fn remove(&self, id: &str) -> Result<()>;
+}
+
+/// In-memory execution store, lost when the process exits
+#[derive(Debug, Default)]
+pub struct InMemoryExecutionStore {
+    states: Mutex<HashMap<String, ExecutionState>>,
+}
+
+impl InMemoryExecutionStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ExecutionStore for InMemoryExecutionStore {
+    async fn save(&self, state: &ExecutionState) -> Result<()> {
+        self.states
+            .lock()
+            .unwrap()
+            .insert(state.id.clone(), state.clone());
+        Ok(())
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<ExecutionState>> {
+        Ok(self.states.lock().unwrap().get(id).cloned())
+    }
+
+    async fn remove(&self, id: &str) -> Result<()> {
+        self.states.lock().unwrap().remove(id);
+        Ok(())
+    }
+}
+
+/// Execution store writing one JSON file per execution into a directory
+///
+/// Files are written to a temporary path and renamed, so a crash never leaves a
+/// partially written state behind.
+#[derive(Debug, Clone)]
+pub struct FileExecutionStore {
+    dir: PathBuf,
+}
+
+impl FileExecutionStore {
+    /// Create a store in the given directory (created on first save)
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Path of the state file of an execution
+    fn path(&self, id: &str) -> Result<PathBuf> {
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(crate::EilError::ExecutionStore(format!(
+                "Invalid execution ID '{}'",
+                id
+            )));
+        }
+        Ok(self.dir.join(format!("{}.json", id)))
+    }
+}
+
+#[async_trait]
+impl ExecutionStore for FileExecutionStore {
+    async fn save(&self, state: &ExecutionState) -> Result<()> {
+        let path = self.path(&state.id)?;
+        let tmp_path = path.with_extension("json.tmp");
+        let json = serde_json::to_vec_pretty(state)?;
+
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| crate::EilError::ExecutionStore(e.to_string()))?;
+        tokio::fs::write(&tmp_path, json)
+            .await
+            .map_err(|e| crate::EilError::ExecutionStore(e.to_string()))?;
+        tokio::fs::rename(&tmp_path, &path)
+            .await
+            .map_err(|e| crate::EilError::ExecutionStore(e.to_string()))
+    }
+
+    async fn load(&self, id: &str) -> Result<Option<ExecutionState>> {
+        match tokio::fs::read(self.path(id)?).await {
+            Ok(json) => Ok(Some(serde_json::from_slice(&json)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(crate::EilError::ExecutionStore(e.to_string())),
+        }
+    }
+
+    async fn remove(&self, id: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(id)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(crate::EilError::ExecutionStore(e.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_utils::create_test_batch, types::OperationStatus};
+
+    fn create_test_state(id: &str) -> ExecutionState {
+        ExecutionState {
+            id: id.to_string(),
+            batches: vec![BatchStatusInfo {
+                index: 0,
+                batch: create_test_batch(10),
+                status: OperationStatus::Executing,
+                vouchers: HashMap::new(),
+                request_ids: None,
+                tx_hash: None,
+                revert_reason: None,
+                revert: None,
+                submitted_block: None,
+            }],
+            voucher_requests: HashMap::from([(
+                "v1".to_string(),
+                crate::test_utils::create_test_voucher_request(),
+            )]),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_store_roundtrip() {
+        let store = InMemoryExecutionStore::new();
+        store.save(&create_test_state("run-1")).await.unwrap();
+
+        let loaded = store.load("run-1").await.unwrap().unwrap();
+        assert_eq!(loaded.batches[0].status, OperationStatus::Executing);
+        assert!(store.load("run-2").await.unwrap().is_none());
+
+        store.remove("run-1").await.unwrap();
+        assert!(store.load("run-1").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_file_store_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("eil-store-{}", std::process::id()));
+        let store = FileExecutionStore::new(&dir);
+        let state = create_test_state("run-1");
+        store.save(&state).await.unwrap();
+
+        let loaded = store.load("run-1").await.unwrap().unwrap();
+        assert_eq!(loaded.id, "run-1");
+        assert_eq!(
+            loaded.batches[0].batch.user_op_hash,
+            state.batches[0].batch.user_op_hash
+        );
+        assert_eq!(
+            loaded.batches[0].batch.gas_estimate,
+            state.batches[0].batch.gas_estimate
+        );
+        assert!(loaded.voucher_requests.contains_key("v1"));
+
+        store.remove("run-1").await.unwrap();
+        assert!(store.load("run-1").await.unwrap().is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_file_store_rejects_path_ids() {
+        let store = FileExecutionStore::new(std::env::temp_dir());
+        assert!(matches!(
+            store.load("../run").await,
+            Err(crate::EilError::ExecutionStore(_))
+        ));
+    }
+}
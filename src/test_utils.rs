@@ -16,6 +16,12 @@ pub struct MockSigner {
     pub address: Address,
 }
 
+impl Default for MockSigner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MockSigner {
     pub fn new() -> Self {
         Self {
@@ -33,6 +39,10 @@ impl Signer for MockSigner {
         Ok(Hex::from(vec![0u8; 65]))
     }
 
+    async fn sign_typed_data_hash(&self, _hash: &[u8; 32]) -> Result<Hex> {
+        Ok(Hex::from(vec![0u8; 65]))
+    }
+
     fn address(&self) -> Address {
         self.address
     }
@@ -43,6 +53,12 @@ pub struct MockBundlerManager {
     pub submitted_ops: std::sync::Arc<std::sync::Mutex<Vec<UserOperation>>>,
 }
 
+impl Default for MockBundlerManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MockBundlerManager {
     pub fn new() -> Self {
         Self {
@@ -79,6 +95,12 @@ pub struct MockAccount {
     pub bundler: MockBundlerManager,
 }
 
+impl Default for MockAccount {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl MockAccount {
     pub fn new() -> Self {
         let mut addresses = HashMap::new();
@@ -132,7 +154,7 @@ impl MultiChainSmartAccount for MockAccount {
         self.addresses
             .get(&chain_id)
             .copied()
-            .ok_or_else(|| crate::EilError::UnsupportedChain(chain_id))
+            .ok_or(crate::EilError::UnsupportedChain(chain_id))
     }
 
     async fn sign_user_ops(&self, mut user_ops: Vec<UserOperation>) -> Result<Vec<UserOperation>> {
@@ -148,6 +170,15 @@ impl MultiChainSmartAccount for MockAccount {
         Ok(Hex::from(vec![0u8; 32]))
     }
 
+    async fn encode_delegate_call(
+        &self,
+        _chain_id: ChainId,
+        _target: Address,
+        _data: Hex,
+    ) -> Result<Hex> {
+        Ok(Hex::from(vec![0u8; 32]))
+    }
+
     async fn send_user_operation(&self, user_op: UserOperation) -> Result<Hex> {
         let entry_point = user_op
             .entry_point_address
@@ -170,11 +201,15 @@ impl MultiChainSmartAccount for MockAccount {
     async fn get_factory_args(&self, _chain_id: ChainId) -> Result<(Option<Address>, Option<Hex>)> {
         Ok((None, None))
     }
+
+    fn signer(&self) -> Option<&dyn Signer> {
+        Some(&self.signer)
+    }
 }
 
 /// Create a test configuration with the specified chains
 pub fn create_test_config(chain_ids: Vec<ChainId>) -> crate::config::CrossChainConfig {
-    use crate::config::{ChainInfo, CrossChainConfig};
+    use crate::config::{ChainInfo, CrossChainConfig, L2Kind};
 
     let chain_infos: Vec<ChainInfo> = chain_ids
         .into_iter()
@@ -188,12 +223,106 @@ pub fn create_test_config(chain_ids: Vec<ChainId>) -> crate::config::CrossChainC
                 .parse()
                 .unwrap(),
             bundler_url: None,
+            runtime_vars_helper: None,
+            l2_kind: L2Kind::L1,
+            entry_point_simulations: None,
+            multicall3: None,
+            permit2: None,
         })
         .collect();
 
     CrossChainConfig::new(chain_infos)
 }
 
+/// Create a voucher request from chain 10 to chain 20 with no assets
+pub fn create_test_voucher_request() -> crate::contract_types::VoucherRequest {
+    serde_json::from_value(serde_json::json!({
+        "origination": {
+            "chainId": 10,
+            "sender": Address::repeat_byte(0x22),
+            "paymaster": Address::repeat_byte(0x33),
+            "assets": [],
+            "feeRule": {
+                "startFeePercentNumerator": "0x0",
+                "maxFeePercentNumerator": "0x0",
+                "feeIncreasePerSecond": "0x0",
+                "unspentVoucherFee": "0x0",
+            },
+            "senderNonce": "0x0",
+            "allowedXlps": [],
+        },
+        "destination": {
+            "chainId": 20,
+            "sender": Address::repeat_byte(0x22),
+            "paymaster": Address::repeat_byte(0x33),
+            "assets": [],
+            "maxUserOpCost": "0x0",
+            "expiresAt": "0x0",
+        },
+    }))
+    .unwrap()
+}
+
+/// Create an unsigned test UserOperation for the v0.7 EntryPoint on the specified chain
+pub fn create_test_user_op(chain_id: ChainId) -> UserOperation {
+    UserOperation {
+        sender: "0x2222222222222222222222222222222222222222"
+            .parse()
+            .unwrap(),
+        nonce: U256::ZERO,
+        factory: None,
+        factory_data: None,
+        call_data: Hex::from(vec![0u8; 32]),
+        call_gas_limit: U256::from(100_000),
+        verification_gas_limit: U256::from(150_000),
+        pre_verification_gas: U256::from(60_000),
+        max_fee_per_gas: U256::from(2_000_000_000u64),
+        max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+        paymaster: None,
+        paymaster_verification_gas_limit: None,
+        paymaster_post_op_gas_limit: None,
+        paymaster_data: None,
+        paymaster_signature: None,
+        signature: Hex::new(),
+        chain_id: Some(chain_id),
+        entry_point_address: Some(
+            "0x0000000071727De22E5E9d8BAf0edAc6f37da032"
+                .parse()
+                .unwrap(),
+        ),
+    }
+}
+
+/// Create a signed test batch on the specified chain, without vouchers
+pub fn create_test_batch(chain_id: ChainId) -> crate::contract_types::SingleChainBatch {
+    use crate::gas::{FeeEstimate, GasEstimate, GasEstimateSource};
+
+    let mut user_op = create_test_user_op(chain_id);
+    user_op.signature = Hex::from(vec![0xabu8; 65]);
+    let user_op_hash = crate::user_op::compute_user_op_hash(&user_op).unwrap();
+
+    crate::contract_types::SingleChainBatch {
+        gas_estimate: GasEstimate {
+            call_gas_limit: user_op.call_gas_limit,
+            verification_gas_limit: user_op.verification_gas_limit,
+            pre_verification_gas: user_op.pre_verification_gas,
+            paymaster_verification_gas_limit: None,
+            paymaster_post_op_gas_limit: None,
+            fees: FeeEstimate {
+                max_fee_per_gas: user_op.max_fee_per_gas,
+                max_priority_fee_per_gas: user_op.max_priority_fee_per_gas,
+            },
+            source: GasEstimateSource::Rpc,
+        },
+        user_op,
+        user_op_hash: Hex::from(user_op_hash.to_vec()),
+        chain_id,
+        input_voucher_requests: Vec::new(),
+        out_voucher_requests: Vec::new(),
+        custom_errors: Vec::new(),
+    }
+}
+
 /// Create a test token deployed on the specified chains
 pub fn create_test_token(
     name: &str,
@@ -209,3 +338,174 @@ pub fn create_test_token(
 
     MultichainToken::new(name.to_string(), deployments)
 }
+
+/// Handler for the mock JSON-RPC server: maps `(method, params)` to a result
+/// or a JSON-RPC error `(code, message)`
+pub type MockRpcHandler = std::sync::Arc<
+    dyn Fn(&str, &serde_json::Value) -> std::result::Result<serde_json::Value, (i64, String)>
+        + Send
+        + Sync,
+>;
+
+/// Minimal HTTP JSON-RPC server for testing RPC and bundler clients offline
+pub struct MockRpcServer {
+    /// Server URL (`http://127.0.0.1:<port>`)
+    pub url: String,
+    /// Received requests as `(method, params)`
+    pub requests: std::sync::Arc<std::sync::Mutex<Vec<(String, serde_json::Value)>>>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl MockRpcServer {
+    /// Start a server answering every request with the given handler
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&str, &serde_json::Value) -> std::result::Result<serde_json::Value, (i64, String)>
+            + Send
+            + Sync
+            + 'static,
+    {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind mock RPC server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let handler: MockRpcHandler = std::sync::Arc::new(handler);
+
+        let recorded = requests.clone();
+        let handle = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+                let recorded = recorded.clone();
+                tokio::spawn(async move {
+                    let _ = serve_connection(stream, handler, recorded).await;
+                });
+            }
+        });
+
+        Self {
+            url,
+            requests,
+            handle,
+        }
+    }
+
+    /// Get the methods received so far, in order
+    pub fn methods(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(method, _)| method.clone())
+            .collect()
+    }
+}
+
+impl Drop for MockRpcServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+async fn serve_connection(
+    mut stream: tokio::net::TcpStream,
+    handler: MockRpcHandler,
+    recorded: std::sync::Arc<std::sync::Mutex<Vec<(String, serde_json::Value)>>>,
+) -> std::io::Result<()> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let headers = String::from_utf8_lossy(&buf[..header_end]).to_lowercase();
+    let content_length = headers
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length:"))
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while buf.len() < header_end + content_length {
+        let n = stream.read(&mut chunk).await?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let request: serde_json::Value =
+        serde_json::from_slice(&buf[header_end..]).unwrap_or(serde_json::Value::Null);
+    let answer = |request: &serde_json::Value| {
+        let method = request["method"].as_str().unwrap_or_default().to_string();
+        let params = request["params"].clone();
+        recorded.lock().unwrap().push((method.clone(), params.clone()));
+        match handler(&method, &params) {
+            Ok(result) => serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": result,
+            }),
+            Err((code, message)) => serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": { "code": code, "message": message },
+            }),
+        }
+    };
+    let response = match &request {
+        serde_json::Value::Array(requests) => {
+            serde_json::Value::Array(requests.iter().map(answer).collect())
+        }
+        request => answer(request),
+    };
+
+    let body = response.to_string();
+    let http = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    stream.write_all(http.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Start a mock node for `chain_id` answering the calls made while building batches
+/// (`eth_chainId`, `eth_feeHistory`, `eth_estimateGas`, `eth_getCode`)
+pub async fn start_mock_chain(chain_id: ChainId) -> MockRpcServer {
+    MockRpcServer::start(move |method, _| match method {
+        "eth_chainId" => Ok(serde_json::json!(format!("0x{:x}", chain_id))),
+        "eth_feeHistory" => Ok(serde_json::json!({
+            "oldestBlock": "0x1",
+            "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00"],
+            "gasUsedRatio": [0.5],
+            "reward": [["0x3b9aca00"]],
+        })),
+        "eth_estimateGas" => Ok(serde_json::json!("0x186a0")),
+        "eth_getCode" => Ok(serde_json::json!("0x")),
+        _ => Err((-32601, format!("method {} not found", method))),
+    })
+    .await
+}
+
+/// Extract the target and calldata of an `eth_call`/`eth_estimateGas` request
+pub fn call_request(params: &serde_json::Value) -> (Address, Hex) {
+    let request = &params[0];
+    let to = request["to"]
+        .as_str()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_default();
+    let data = request["input"]
+        .as_str()
+        .or_else(|| request["data"].as_str())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_default();
+    (to, data)
+}
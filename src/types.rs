@@ -92,7 +92,7 @@ pub fn to_address(chain_id: ChainId, entity: &dyn MultiChainEntity) -> crate::Re
 pub type AddressPerChain = HashMap<ChainId, Address>;
 
 /// Token amount with token reference
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenAmount {
     /// Token reference (can be multichain)
     pub token: crate::multichain::MultichainToken,
@@ -102,6 +102,17 @@ pub struct TokenAmount {
     pub min_provider_deposit: Option<U256>,
 }
 
+impl TokenAmount {
+    /// Amount locked in a voucher
+    /// Runtime amounts lock the minimum provider deposit, or 1 if unset.
+    pub fn voucher_amount(&self) -> U256 {
+        match &self.amount {
+            Amount::Fixed(amount) => *amount,
+            Amount::Runtime(_) => self.min_provider_deposit.unwrap_or(U256::from(1)),
+        }
+    }
+}
+
 /// Call structure for contract interactions
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Call {
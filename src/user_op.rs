@@ -0,0 +1,273 @@
+//! ERC-4337 EntryPoint v0.7 UserOperation packing and hashing
+//!
+//! The EntryPoint v0.7 hashes a `PackedUserOperation`, where gas limits and fees are
+//! packed in pairs of `uint128` into single `bytes32` words, and the factory and
+//! paymaster fields are concatenated into `initCode` and `paymasterAndData`.
+
+use crate::{contract_types::UserOperation, types::*, Result};
+use alloy::{
+    primitives::{keccak256, B256, U256},
+    sol_types::SolValue,
+};
+
+alloy::sol! {
+    /// EntryPoint v0.7 `PackedUserOperation`
+    #[derive(Debug, PartialEq, Eq)]
+    struct PackedUserOperation {
+        address sender;
+        uint256 nonce;
+        bytes initCode;
+        bytes callData;
+        bytes32 accountGasLimits;
+        uint256 preVerificationGas;
+        bytes32 gasFees;
+        bytes paymasterAndData;
+        bytes signature;
+    }
+}
+
+/// Pack two `uint128` values into a single `bytes32` word (`high << 128 | low`)
+pub fn pack_uint128_pair(high: U256, low: U256) -> Result<B256> {
+    let high = to_uint128(high)?;
+    let low = to_uint128(low)?;
+    let mut word = [0u8; 32];
+    word[..16].copy_from_slice(&high.to_be_bytes());
+    word[16..].copy_from_slice(&low.to_be_bytes());
+    Ok(B256::from(word))
+}
+
+/// Build the `initCode` field: `factory ++ factoryData`, or empty if no factory is set
+pub fn pack_init_code(user_op: &UserOperation) -> Hex {
+    match user_op.factory {
+        Some(factory) => {
+            let mut init_code = factory.to_vec();
+            if let Some(data) = &user_op.factory_data {
+                init_code.extend_from_slice(data);
+            }
+            init_code.into()
+        }
+        None => Hex::new(),
+    }
+}
+
+/// Build the `paymasterAndData` field:
+/// `paymaster ++ uint128(verificationGasLimit) ++ uint128(postOpGasLimit) ++ paymasterData`,
+/// or empty if no paymaster is set
+pub fn pack_paymaster_and_data(user_op: &UserOperation) -> Result<Hex> {
+    let Some(paymaster) = user_op.paymaster else {
+        return Ok(Hex::new());
+    };
+
+    let verification_gas =
+        to_uint128(user_op.paymaster_verification_gas_limit.unwrap_or_default())?;
+    let post_op_gas = to_uint128(user_op.paymaster_post_op_gas_limit.unwrap_or_default())?;
+
+    let mut data = paymaster.to_vec();
+    data.extend_from_slice(&verification_gas.to_be_bytes());
+    data.extend_from_slice(&post_op_gas.to_be_bytes());
+    if let Some(paymaster_data) = &user_op.paymaster_data {
+        data.extend_from_slice(paymaster_data);
+    }
+    Ok(data.into())
+}
+
+/// Pack a UserOperation into the EntryPoint v0.7 `PackedUserOperation` layout
+pub fn pack_user_op(user_op: &UserOperation) -> Result<PackedUserOperation> {
+    Ok(PackedUserOperation {
+        sender: user_op.sender,
+        nonce: user_op.nonce,
+        initCode: pack_init_code(user_op),
+        callData: user_op.call_data.clone(),
+        accountGasLimits: pack_uint128_pair(
+            user_op.verification_gas_limit,
+            user_op.call_gas_limit,
+        )?,
+        preVerificationGas: user_op.pre_verification_gas,
+        gasFees: pack_uint128_pair(user_op.max_priority_fee_per_gas, user_op.max_fee_per_gas)?,
+        paymasterAndData: pack_paymaster_and_data(user_op)?,
+        signature: user_op.signature.clone(),
+    })
+}
+
+/// Compute the EntryPoint v0.7 userOpHash
+///
+/// `keccak256(abi.encode(keccak256(packedFields), entryPoint, chainId))`, where the
+/// packed fields exclude the signature and hash the dynamic byte fields.
+/// The chain ID and EntryPoint are taken from `chain_id` and `entry_point_address`.
+pub fn compute_user_op_hash(user_op: &UserOperation) -> Result<B256> {
+    let chain_id = user_op
+        .chain_id
+        .ok_or_else(|| crate::EilError::Generic("UserOperation chain ID not set".into()))?;
+    let entry_point = user_op
+        .entry_point_address
+        .ok_or_else(|| crate::EilError::Generic("EntryPoint address not set".into()))?;
+
+    let packed = pack_user_op(user_op)?;
+    let inner_hash = keccak256(
+        (
+            packed.sender,
+            packed.nonce,
+            keccak256(&packed.initCode),
+            keccak256(&packed.callData),
+            packed.accountGasLimits,
+            packed.preVerificationGas,
+            packed.gasFees,
+            keccak256(&packed.paymasterAndData),
+        )
+            .abi_encode(),
+    );
+
+    Ok(keccak256(
+        (inner_hash, entry_point, U256::from(chain_id)).abi_encode(),
+    ))
+}
+
+fn to_uint128(value: U256) -> Result<u128> {
+    u128::try_from(value)
+        .map_err(|_| crate::EilError::Generic(format!("Value {} does not fit in uint128", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::create_test_user_op;
+
+    /// Test UserOperation the hash vectors were computed for
+    fn create_vector_user_op() -> UserOperation {
+        let mut user_op = create_test_user_op(1);
+        user_op.sender = "0x1111111111111111111111111111111111111111"
+            .parse()
+            .unwrap();
+        user_op.nonce = U256::from(7);
+        user_op.call_data = Hex::from(vec![0xde, 0xad, 0xbe, 0xef]);
+        user_op.verification_gas_limit = U256::from(200_000);
+        user_op.pre_verification_gas = U256::from(50_000);
+        user_op
+    }
+
+    #[test]
+    fn test_pack_uint128_pair() {
+        let packed = pack_uint128_pair(U256::from(1), U256::from(2)).unwrap();
+        let mut expected = [0u8; 32];
+        expected[15] = 1;
+        expected[31] = 2;
+        assert_eq!(packed, B256::from(expected));
+    }
+
+    #[test]
+    fn test_pack_uint128_pair_overflow() {
+        let result = pack_uint128_pair(U256::from(u128::MAX) + U256::from(1), U256::ZERO);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_pack_init_code() {
+        let mut user_op = create_test_user_op(1);
+        assert!(pack_init_code(&user_op).is_empty());
+
+        user_op.factory = Some(
+            "0x2222222222222222222222222222222222222222"
+                .parse()
+                .unwrap(),
+        );
+        user_op.factory_data = Some(Hex::from(vec![0x01, 0x02]));
+        let init_code = pack_init_code(&user_op);
+        assert_eq!(init_code.len(), 22);
+        assert_eq!(&init_code[20..], &[0x01, 0x02]);
+    }
+
+    #[test]
+    fn test_pack_paymaster_and_data() {
+        let mut user_op = create_test_user_op(1);
+        assert!(pack_paymaster_and_data(&user_op).unwrap().is_empty());
+
+        user_op.paymaster = Some(
+            "0x3333333333333333333333333333333333333333"
+                .parse()
+                .unwrap(),
+        );
+        user_op.paymaster_verification_gas_limit = Some(U256::from(0x1234));
+        user_op.paymaster_post_op_gas_limit = Some(U256::from(0x5678));
+        user_op.paymaster_data = Some(Hex::from(vec![0xaa]));
+
+        let data = pack_paymaster_and_data(&user_op).unwrap();
+        assert_eq!(data.len(), 20 + 16 + 16 + 1);
+        assert_eq!(&data[34..36], &[0x12, 0x34]);
+        assert_eq!(&data[50..52], &[0x56, 0x78]);
+        assert_eq!(data[52], 0xaa);
+    }
+
+    #[test]
+    fn test_compute_user_op_hash_vector() {
+        let hash = compute_user_op_hash(&create_vector_user_op()).unwrap();
+        assert_eq!(
+            hash,
+            "0x34c15137094fa1954a08cd92ec376d6f8ab7a018eebbd6672e483c363eb22e5f"
+                .parse::<B256>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_compute_user_op_hash_vector_with_factory_and_paymaster() {
+        let mut user_op = create_vector_user_op();
+        user_op.factory = Some(
+            "0x2222222222222222222222222222222222222222"
+                .parse()
+                .unwrap(),
+        );
+        user_op.factory_data = Some(Hex::from(vec![0x01, 0x02, 0x03]));
+        user_op.paymaster = Some(
+            "0x3333333333333333333333333333333333333333"
+                .parse()
+                .unwrap(),
+        );
+        user_op.paymaster_verification_gas_limit = Some(U256::from(60_000));
+        user_op.paymaster_post_op_gas_limit = Some(U256::from(40_000));
+        user_op.paymaster_data = Some(Hex::from(vec![0xaa, 0xbb]));
+        user_op.chain_id = Some(10);
+
+        let hash = compute_user_op_hash(&user_op).unwrap();
+        assert_eq!(
+            hash,
+            "0x9dff77098b15bf22887b94bcc66362a6b8a05657f85f8bd11fb3f5d9cc67aa95"
+                .parse::<B256>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_compute_user_op_hash_ignores_signature() {
+        let user_op = create_test_user_op(1);
+        let mut signed = user_op.clone();
+        signed.signature = Hex::from(vec![0xab; 65]);
+
+        assert_eq!(
+            compute_user_op_hash(&user_op).unwrap(),
+            compute_user_op_hash(&signed).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_compute_user_op_hash_depends_on_chain() {
+        let user_op = create_test_user_op(1);
+        let mut other_chain = user_op.clone();
+        other_chain.chain_id = Some(10);
+
+        assert_ne!(
+            compute_user_op_hash(&user_op).unwrap(),
+            compute_user_op_hash(&other_chain).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_compute_user_op_hash_requires_domain() {
+        let mut user_op = create_test_user_op(1);
+        user_op.entry_point_address = None;
+        assert!(compute_user_op_hash(&user_op).is_err());
+
+        let mut user_op = create_test_user_op(1);
+        user_op.chain_id = None;
+        assert!(compute_user_op_hash(&user_op).is_err());
+    }
+}
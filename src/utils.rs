@@ -8,7 +8,72 @@ pub fn now_seconds() -> u64 {
         .as_secs()
 }
 
+/// Scale an amount by a non-negative factor, rounding up
+pub fn scale_ceil(amount: alloy::primitives::U256, factor: f64) -> alloy::primitives::U256 {
+    const PRECISION: u64 = 1_000_000;
+    let factor = alloy::primitives::U256::from((factor.max(0.0) * PRECISION as f64).round() as u64);
+    (amount * factor).div_ceil(alloy::primitives::U256::from(PRECISION))
+}
+
+/// Largest amount whose [`scale_ceil`] by `factor` does not exceed `limit`
+pub fn unscale_floor(limit: alloy::primitives::U256, factor: f64) -> alloy::primitives::U256 {
+    const PRECISION: u64 = 1_000_000;
+    let factor = (factor.max(0.0) * PRECISION as f64).round() as u64;
+    if factor == 0 {
+        return alloy::primitives::U256::MAX;
+    }
+    limit.saturating_mul(alloy::primitives::U256::from(PRECISION))
+        / alloy::primitives::U256::from(factor)
+}
+
 /// Convert fee percentage (0.0 to 1.0) to numerator out of 10_000
-pub fn fee_percent_to_numerator(percent: f64) -> alloy::primitives::U256 {
-    alloy::primitives::U256::from((percent * 10_000.0) as u64)
+///
+/// Percentages finer than one basis point are rejected rather than rounded; the tolerance
+/// only absorbs float error such as `0.0029 * 10_000 = 28.999999999999996`.
+pub fn fee_percent_to_numerator(percent: f64) -> crate::Result<alloy::primitives::U256> {
+    let numerator = percent * 10_000.0;
+    if !(0.0..=1.0).contains(&percent) || (numerator - numerator.round()).abs() > 1e-6 {
+        return Err(crate::EilError::InvalidFeePercent(percent));
+    }
+    Ok(alloy::primitives::U256::from(numerator.round() as u64))
+}
+
+/// Parse a decimal amount such as `"90.5"` into base units of a token with `decimals`
+///
+/// Amounts with more fractional digits than `decimals` are rejected rather than truncated.
+pub fn parse_units(amount: &str, decimals: u8) -> crate::Result<alloy::primitives::U256> {
+    let invalid =
+        |reason: String| crate::EilError::InvalidAmount(format!("'{}': {}", amount, reason));
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    let is_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(invalid("not a decimal number".to_string()));
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    let decimals = decimals as usize;
+    if fraction.len() > decimals {
+        return Err(invalid(format!("more than {} decimals", decimals)));
+    }
+    let digits = format!(
+        "{}{}{}",
+        whole,
+        fraction,
+        "0".repeat(decimals - fraction.len())
+    );
+    alloy::primitives::U256::from_str_radix(&digits, 10)
+        .map_err(|_| invalid("too large".to_string()))
+}
+
+/// Format base units of a token with `decimals` as a decimal amount, without trailing zeros
+pub fn format_units(value: alloy::primitives::U256, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    let digits = format!("{:0>width$}", value.to_string(), width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
 }
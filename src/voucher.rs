@@ -1,8 +1,17 @@
 use crate::{
-    contract_types::{SdkVoucherRequest, Voucher, VoucherRequest},
+    config::XlpSelectionConfig,
+    contract_types::{SdkVoucherRequest, SessionData, UserOperation, Voucher, VoucherRequest},
+    contracts::{ICrossChainPaymaster, IERC20},
+    multichain::NATIVE_TOKEN,
+    network::NetworkEnvironment,
+    session::EphemeralSigner,
     types::*,
     Result,
 };
+use alloy::{
+    primitives::U256,
+    sol_types::{SolCall, SolValue},
+};
 use std::collections::HashMap;
 
 /// Internal voucher information tracking
@@ -147,25 +156,221 @@ pub struct SolventXlpInfo {
     /// XLP entry
     pub xlp_entry: crate::contract_types::XlpEntry,
     /// Deposits per token
-    pub deposits: Vec<alloy::primitives::U256>,
+    pub deposits: Vec<U256>,
     /// Balances per token
-    pub balances: Vec<alloy::primitives::U256>,
+    pub balances: Vec<U256>,
 }
 
-/// Get solvent XLPs for a destination chain
-/// This is a placeholder - actual implementation would query the paymaster contract
+/// Query registered XLPs on a destination chain with their deposits and balances
+///
+/// Deposits are read from the CrossChainPaymaster, balances from each token's
+/// `balanceOf`. Both vectors follow the order of `tokens`; balances are zero when
+/// `include_balance` is false.
 pub async fn get_solvent_xlps(
-    _chain_id: ChainId,
-    _paymaster: Address,
-    _tokens: &[TokenAmount],
-    _include_balance: bool,
+    network_env: &NetworkEnvironment,
+    chain_id: ChainId,
+    paymaster: Address,
+    tokens: &[TokenAmount],
+    include_balance: bool,
 ) -> Result<Vec<SolventXlpInfo>> {
-    // TODO: Implement actual XLP querying logic
-    // This would involve:
-    // 1. Querying the paymaster contract for all XLPs
-    // 2. Checking their deposits and balances
-    // 3. Filtering for solvent ones
-    Ok(Vec::new())
+    let provider = network_env.create_provider(chain_id).await?;
+    let token_addresses = tokens
+        .iter()
+        .map(|t| {
+            t.token
+                .address_on(chain_id)
+                .ok_or_else(|| crate::EilError::InvalidAddress {
+                    chain_id,
+                    address: format!("Token {} not deployed", t.token.name),
+                })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let paymaster_contract = ICrossChainPaymaster::new(paymaster, provider.clone());
+    let xlps = paymaster_contract
+        .getXlps()
+        .call()
+        .await
+        .map_err(|e| crate::EilError::AlloyContract(format!("getXlps: {}", e)))?
+        .xlps;
+
+    let infos = xlps.into_iter().map(|entry| {
+        let paymaster_contract = &paymaster_contract;
+        let token_addresses = &token_addresses;
+        async move {
+            let deposits = paymaster_contract
+                .getDeposits(entry.l2XlpAddress, token_addresses.clone())
+                .call()
+                .await
+                .map_err(|e| crate::EilError::AlloyContract(format!("getDeposits: {}", e)))?
+                .deposits;
+
+            let balances = if include_balance {
+                let queries: Vec<(Address, Address)> = token_addresses
+                    .iter()
+                    .map(|token| (*token, entry.l2XlpAddress))
+                    .collect();
+                crate::multichain::balances_of(network_env, chain_id, &queries, BlockNumber::Latest)
+                    .await?
+            } else {
+                vec![U256::ZERO; token_addresses.len()]
+            };
+
+            Ok::<_, crate::EilError>(SolventXlpInfo {
+                xlp_entry: entry.into(),
+                deposits,
+                balances,
+            })
+        }
+    });
+
+    futures::future::try_join_all(infos).await
+}
+
+/// Select the XLPs allowed to fill a voucher on `chain_id`
+///
+/// An XLP qualifies when, for every token, its deposit (plus its balance if
+/// `include_balance` is set) covers the requested amount times
+/// `deposit_reserve_factor`, and `custom_xlp_filter` (if any) accepts it.
+/// Qualifying XLPs are ordered by bond, largest first, and capped at `max_xlps`.
+/// Fails if fewer than `min_xlps` qualify.
+pub fn select_xlps(
+    chain_id: ChainId,
+    xlps: &[SolventXlpInfo],
+    tokens: &[TokenAmount],
+    config: &XlpSelectionConfig,
+) -> Result<Vec<Address>> {
+    let required = tokens
+        .iter()
+        .map(|t| {
+            let token = t
+                .token
+                .address_on(chain_id)
+                .ok_or_else(|| crate::EilError::InvalidAddress {
+                    chain_id,
+                    address: format!("Token {} not deployed", t.token.name),
+                })?;
+            Ok((
+                token,
+                crate::utils::scale_ceil(t.voucher_amount(), config.deposit_reserve_factor),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut solvent: Vec<&SolventXlpInfo> = xlps
+        .iter()
+        .filter(|xlp| {
+            required.iter().enumerate().all(|(i, (token, amount))| {
+                let deposit = xlp.deposits.get(i).copied().unwrap_or_default();
+                let balance = xlp.balances.get(i).copied().unwrap_or_default();
+                let available = if config.include_balance {
+                    deposit.saturating_add(balance)
+                } else {
+                    deposit
+                };
+                let accepted = config.custom_xlp_filter.as_ref().is_none_or(|filter| {
+                    filter(chain_id, xlp.xlp_entry.l2_xlp_address, *token, deposit, balance)
+                });
+                available >= *amount && accepted
+            })
+        })
+        .collect();
+
+    solvent.sort_by_key(|xlp| std::cmp::Reverse(xlp.xlp_entry.bond));
+    solvent.truncate(config.max_xlps);
+
+    if solvent.is_empty() {
+        return Err(crate::EilError::NoXlpsFound(chain_id));
+    }
+    if solvent.len() < config.min_xlps {
+        return Err(crate::EilError::InsufficientXlps {
+            found: solvent.len(),
+            required: config.min_xlps,
+            chain_id,
+        });
+    }
+
+    Ok(solvent
+        .into_iter()
+        .map(|xlp| xlp.xlp_entry.l2_xlp_address)
+        .collect())
+}
+
+/// Source chain calls creating a voucher request
+///
+/// Each ERC-20 asset is approved to the source CrossChainPaymaster, then
+/// `createVoucherRequest` locks the assets, with native assets sent as value.
+pub fn encode_voucher_request_calls(request: &VoucherRequest) -> Vec<Call> {
+    let paymaster = request.origination.paymaster;
+    let mut native_amount = U256::ZERO;
+    let mut calls = Vec::new();
+    for asset in &request.origination.assets {
+        if asset.erc20_token == NATIVE_TOKEN {
+            native_amount += asset.amount;
+            continue;
+        }
+        calls.push(Call {
+            target: asset.erc20_token,
+            data: IERC20::approveCall {
+                spender: paymaster,
+                amount: asset.amount,
+            }
+            .abi_encode()
+            .into(),
+            value: None,
+        });
+    }
+    calls.push(Call {
+        target: paymaster,
+        data: ICrossChainPaymaster::createVoucherRequestCall {
+            request: request.into(),
+        }
+        .abi_encode()
+        .into(),
+        value: (!native_amount.is_zero()).then_some(native_amount),
+    });
+    calls
+}
+
+/// Encode the CrossChainPaymaster `paymasterData` of a voucher-consuming UserOperation:
+/// `commitment ++ abi.encode(Voucher[] vouchers, SessionData session)`
+pub fn encode_paymaster_data(commitment: &Hex, vouchers: &[Voucher], session: &SessionData) -> Hex {
+    let vouchers: Vec<ICrossChainPaymaster::Voucher> = vouchers.iter().map(Into::into).collect();
+    let session = ICrossChainPaymaster::SessionData {
+        data: session.data.clone(),
+        ephemeralSignature: session.ephemeral_signature.clone(),
+    };
+    let mut data = commitment.to_vec();
+    data.extend_from_slice(&(vouchers, session).abi_encode_params());
+    Hex::from(data)
+}
+
+/// Attach signed vouchers to a voucher-consuming UserOperation
+///
+/// The ephemeral key signs the vouchers for the operation's sender and nonce, and both
+/// are appended to the commitment in `paymasterData`. This changes the userOpHash: the
+/// operation must be signed by the user afterwards. Fails with
+/// [`crate::EilError::CannotOverridePaymaster`] unless the operation uses `paymaster`
+/// with the ephemeral key's commitment as `paymasterData`.
+pub fn apply_vouchers(
+    user_op: &mut UserOperation,
+    paymaster: Address,
+    vouchers: &[Voucher],
+    ephemeral_signer: &EphemeralSigner,
+) -> Result<()> {
+    let commitment = ephemeral_signer.commitment();
+    if user_op.paymaster != Some(paymaster) || user_op.paymaster_data.as_ref() != Some(&commitment)
+    {
+        return Err(crate::EilError::CannotOverridePaymaster);
+    }
+    let chain_id = user_op
+        .chain_id
+        .ok_or_else(|| crate::EilError::Generic("UserOperation chain ID not set".into()))?;
+
+    let session =
+        ephemeral_signer.sign_session(chain_id, user_op.sender, user_op.nonce, vouchers)?;
+    user_op.paymaster_data = Some(encode_paymaster_data(&commitment, vouchers, &session));
+    Ok(())
 }
 
 #[cfg(test)]
@@ -338,6 +543,295 @@ mod tests {
         }
     }
 
+    fn create_test_xlp(address: u8, bond: u64, deposit: u64, balance: u64) -> SolventXlpInfo {
+        SolventXlpInfo {
+            xlp_entry: crate::contract_types::XlpEntry {
+                l1_xlp_address: Address::repeat_byte(address),
+                l2_xlp_address: Address::repeat_byte(address),
+                bond: U256::from(bond),
+            },
+            deposits: vec![U256::from(deposit)],
+            balances: vec![U256::from(balance)],
+        }
+    }
+
+    #[test]
+    fn test_select_xlps_by_deposit() {
+        let voucher = create_test_voucher("v1", 10);
+        let xlps = vec![
+            create_test_xlp(1, 10, 100, 0),
+            create_test_xlp(2, 20, 99, 1000),
+            create_test_xlp(3, 30, 500, 0),
+        ];
+
+        let selected =
+            select_xlps(10, &xlps, &voucher.tokens, &XlpSelectionConfig::default()).unwrap();
+
+        // Ordered by bond, XLP 2 lacks deposit
+        assert_eq!(
+            selected,
+            vec![Address::repeat_byte(3), Address::repeat_byte(1)]
+        );
+    }
+
+    #[test]
+    fn test_select_xlps_include_balance() {
+        let voucher = create_test_voucher("v1", 10);
+        let xlps = vec![create_test_xlp(1, 10, 50, 50)];
+        let config = XlpSelectionConfig {
+            include_balance: true,
+            ..Default::default()
+        };
+
+        let selected = select_xlps(10, &xlps, &voucher.tokens, &config).unwrap();
+        assert_eq!(selected, vec![Address::repeat_byte(1)]);
+    }
+
+    #[test]
+    fn test_select_xlps_reserve_factor() {
+        let voucher = create_test_voucher("v1", 10);
+        let xlps = vec![create_test_xlp(1, 10, 149, 0), create_test_xlp(2, 10, 150, 0)];
+        let config = XlpSelectionConfig {
+            deposit_reserve_factor: 1.5,
+            ..Default::default()
+        };
+
+        let selected = select_xlps(10, &xlps, &voucher.tokens, &config).unwrap();
+        assert_eq!(selected, vec![Address::repeat_byte(2)]);
+    }
+
+    #[test]
+    fn test_select_xlps_max_and_custom_filter() {
+        let voucher = create_test_voucher("v1", 10);
+        let xlps: Vec<_> = (1..=4).map(|i| create_test_xlp(i, i as u64, 100, 0)).collect();
+        let config = XlpSelectionConfig {
+            max_xlps: 2,
+            custom_xlp_filter: Some(std::sync::Arc::new(|_, xlp, _, _, _| {
+                xlp != Address::repeat_byte(4)
+            })),
+            ..Default::default()
+        };
+
+        let selected = select_xlps(10, &xlps, &voucher.tokens, &config).unwrap();
+        assert_eq!(
+            selected,
+            vec![Address::repeat_byte(3), Address::repeat_byte(2)]
+        );
+    }
+
+    #[test]
+    fn test_select_xlps_none_found() {
+        let voucher = create_test_voucher("v1", 10);
+        let xlps = vec![create_test_xlp(1, 10, 1, 0)];
+
+        let result = select_xlps(10, &xlps, &voucher.tokens, &XlpSelectionConfig::default());
+        assert!(matches!(result, Err(crate::EilError::NoXlpsFound(10))));
+    }
+
+    #[test]
+    fn test_select_xlps_insufficient() {
+        let voucher = create_test_voucher("v1", 10);
+        let xlps = vec![create_test_xlp(1, 10, 100, 0)];
+        let config = XlpSelectionConfig {
+            min_xlps: 2,
+            ..Default::default()
+        };
+
+        match select_xlps(10, &xlps, &voucher.tokens, &config) {
+            Err(crate::EilError::InsufficientXlps {
+                found,
+                required,
+                chain_id,
+            }) => {
+                assert_eq!(found, 1);
+                assert_eq!(required, 2);
+                assert_eq!(chain_id, 10);
+            }
+            other => panic!("Expected InsufficientXlps error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_solvent_xlps() {
+        use crate::test_utils::{call_request, create_test_config, MockRpcServer};
+        use alloy::sol_types::{SolCall, SolValue};
+
+        let paymaster = Address::repeat_byte(0xaa);
+        let server = MockRpcServer::start(move |method, params| match method {
+            "eth_chainId" => Ok(serde_json::json!("0xa")),
+            "eth_call" => {
+                let (to, data) = call_request(params);
+                let result = if data.starts_with(&ICrossChainPaymaster::getXlpsCall::SELECTOR) {
+                    assert_eq!(to, paymaster);
+                    vec![ICrossChainPaymaster::XlpEntry {
+                        l1XlpAddress: Address::repeat_byte(1),
+                        l2XlpAddress: Address::repeat_byte(2),
+                        bond: U256::from(5),
+                    }]
+                    .abi_encode()
+                } else if data.starts_with(&ICrossChainPaymaster::getDepositsCall::SELECTOR) {
+                    vec![U256::from(300)].abi_encode()
+                } else {
+                    U256::from(7).abi_encode()
+                };
+                Ok(serde_json::json!(Hex::from(result)))
+            }
+            _ => Err((-32601, "method not found".to_string())),
+        })
+        .await;
+
+        let mut config = create_test_config(vec![10]);
+        config.chain_infos[0].rpc_url = server.url.clone();
+        let env = NetworkEnvironment::new(&config);
+        let voucher = create_test_voucher("v1", 10);
+
+        let xlps = get_solvent_xlps(&env, 10, paymaster, &voucher.tokens, true)
+            .await
+            .unwrap();
+
+        assert_eq!(xlps.len(), 1);
+        assert_eq!(xlps[0].xlp_entry.l2_xlp_address, Address::repeat_byte(2));
+        assert_eq!(xlps[0].deposits, vec![U256::from(300)]);
+        assert_eq!(xlps[0].balances, vec![U256::from(7)]);
+    }
+
+    fn create_test_signed_voucher() -> Voucher {
+        Voucher {
+            request: crate::test_utils::create_test_voucher_request(),
+            signature: Hex::from(vec![0xcd; 65]),
+        }
+    }
+
+    #[test]
+    fn test_encode_voucher_request_calls() {
+        let token = Address::repeat_byte(0x44);
+        let mut request = crate::test_utils::create_test_voucher_request();
+        request.origination.assets = vec![
+            crate::contract_types::Asset {
+                erc20_token: token,
+                amount: U256::from(100),
+            },
+            crate::contract_types::Asset {
+                erc20_token: NATIVE_TOKEN,
+                amount: U256::from(5),
+            },
+        ];
+
+        let calls = encode_voucher_request_calls(&request);
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].target, token);
+        let approve = IERC20::approveCall::abi_decode(&calls[0].data, true).unwrap();
+        assert_eq!(approve.spender, Address::repeat_byte(0x33));
+        assert_eq!(approve.amount, U256::from(100));
+        assert_eq!(calls[1].target, Address::repeat_byte(0x33));
+        assert_eq!(calls[1].value, Some(U256::from(5)));
+        let create =
+            ICrossChainPaymaster::createVoucherRequestCall::abi_decode(&calls[1].data, true)
+                .unwrap();
+        assert_eq!(create.request.origination.assets.len(), 2);
+        assert_eq!(create.request.destination.chainId, U256::from(20));
+    }
+
+    fn create_test_voucher_user_op(
+        paymaster: Address,
+        ephemeral_signer: &EphemeralSigner,
+    ) -> UserOperation {
+        let mut user_op = crate::test_utils::create_test_batch(20).user_op;
+        user_op.paymaster = Some(paymaster);
+        user_op.paymaster_verification_gas_limit = Some(U256::from(250_000));
+        user_op.paymaster_post_op_gas_limit = Some(U256::from(50_000));
+        user_op.paymaster_data = Some(ephemeral_signer.commitment());
+        user_op
+    }
+
+    #[test]
+    fn test_apply_vouchers() {
+        let paymaster = Address::repeat_byte(0x33);
+        let ephemeral_signer = EphemeralSigner::random();
+        let mut user_op = create_test_voucher_user_op(paymaster, &ephemeral_signer);
+        let built = user_op.clone();
+
+        apply_vouchers(
+            &mut user_op,
+            paymaster,
+            &[create_test_signed_voucher()],
+            &ephemeral_signer,
+        )
+        .unwrap();
+
+        // The vouchers are hashed, so the user signs the final operation
+        assert_ne!(
+            crate::user_op::compute_user_op_hash(&user_op).unwrap(),
+            crate::user_op::compute_user_op_hash(&built).unwrap()
+        );
+        assert_eq!(user_op.paymaster, built.paymaster);
+        assert_eq!(
+            user_op.paymaster_verification_gas_limit,
+            built.paymaster_verification_gas_limit
+        );
+        assert_eq!(
+            user_op.paymaster_post_op_gas_limit,
+            built.paymaster_post_op_gas_limit
+        );
+
+        let paymaster_data = user_op.paymaster_data.unwrap();
+        assert_eq!(&paymaster_data[..32], ephemeral_signer.commitment().as_ref());
+        let (vouchers, session) =
+            <(
+                Vec<ICrossChainPaymaster::Voucher>,
+                ICrossChainPaymaster::SessionData,
+            )>::abi_decode_params(&paymaster_data[32..], true)
+            .unwrap();
+        assert_eq!(vouchers.len(), 1);
+        assert_eq!(vouchers[0].request.destination.chainId, U256::from(20));
+        assert_eq!(vouchers[0].signature, Hex::from(vec![0xcd; 65]));
+        let (ephemeral_address, chain_id, sender, nonce) =
+            <(Address, U256, Address, U256)>::abi_decode_params(&session.data, true).unwrap();
+        assert_eq!(ephemeral_address, ephemeral_signer.address());
+        assert_eq!(chain_id, U256::from(20));
+        assert_eq!(sender, built.sender);
+        assert_eq!(nonce, built.nonce);
+    }
+
+    #[test]
+    fn test_apply_vouchers_rejects_paymaster_override() {
+        let ephemeral_signer = EphemeralSigner::random();
+        let mut user_op =
+            create_test_voucher_user_op(Address::repeat_byte(0x99), &ephemeral_signer);
+
+        let result = apply_vouchers(
+            &mut user_op,
+            Address::repeat_byte(0x33),
+            &[create_test_signed_voucher()],
+            &ephemeral_signer,
+        );
+        assert!(matches!(
+            result,
+            Err(crate::EilError::CannotOverridePaymaster)
+        ));
+    }
+
+    #[test]
+    fn test_apply_vouchers_rejects_paymaster_data_override() {
+        let paymaster = Address::repeat_byte(0x33);
+        let ephemeral_signer = EphemeralSigner::random();
+        let mut user_op = create_test_voucher_user_op(paymaster, &ephemeral_signer);
+        user_op.paymaster_data = Some(Hex::from(vec![0xaa]));
+
+        let result = apply_vouchers(
+            &mut user_op,
+            paymaster,
+            &[create_test_signed_voucher()],
+            &ephemeral_signer,
+        );
+        assert!(matches!(
+            result,
+            Err(crate::EilError::CannotOverridePaymaster)
+        ));
+        assert_eq!(user_op.paymaster_data, Some(Hex::from(vec![0xaa])));
+    }
+
     #[test]
     fn test_voucher_coordinator_set_allowed_xlps() {
         let mut coordinator = VoucherCoordinator::new();
@@ -8,7 +8,7 @@ mod tests {
         actions::*,
         contract_types::SdkVoucherRequest,
         test_utils::*,
-        types::{Amount, TokenAmount},
+        types::{Amount, FunctionCall, RuntimeVar, TokenAmount},
     };
     use alloy::primitives::U256;
 
@@ -17,7 +17,7 @@ mod tests {
         let config = create_test_config(vec![1, 10]);
         let sdk = EilSdk::new(config);
 
-        let builder = sdk.create_builder();
+        let _builder = sdk.create_builder();
 
         // Cannot build without setting account
         // This is enforced by the type system
@@ -35,6 +35,18 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_builders_use_distinct_ephemeral_keys() {
+        let config = create_test_config(vec![1, 10]);
+        let sdk = EilSdk::new(config);
+
+        let first = sdk.create_builder();
+        let second = sdk.create_builder();
+
+        assert_ne!(first.ephemeral_address(), second.ephemeral_address());
+        assert_ne!(first.ephemeral_address(), alloy::primitives::Address::ZERO);
+    }
+
     #[tokio::test]
     async fn test_builder_single_batch() {
         let config = create_test_config(vec![1, 10]);
@@ -123,6 +135,57 @@ mod tests {
         assert!(result.is_ok());
         let builder = result.unwrap().end_batch();
         assert_eq!(builder.batch_count(), 2);
+
+        // Default fee: 0.1% rising by 0.01% per second up to 5%
+        let quotes = builder.quote_voucher_fees(40).unwrap();
+        assert_eq!(quotes.len(), 1);
+        assert_eq!(quotes[0].ref_id, "v1");
+        assert_eq!(quotes[0].seconds_to_max_fee, Some(490));
+        assert_eq!(quotes[0].tokens[0].amount, U256::from(100));
+        assert_eq!(quotes[0].tokens[0].max_fee, U256::from(5));
+    }
+
+    #[tokio::test]
+    async fn test_builder_start_funded_batch() {
+        let config = create_test_config(vec![1, 10, 42161]);
+        let sdk = EilSdk::new(config);
+        let account = Arc::new(MockAccount::new());
+        let token = create_test_token("USDC", vec![1, 10, 42161]);
+        let voucher = |source_chain_id, amount: u64| planner::PlannedVoucher {
+            ref_id: format!("fund-USDC-{}-42161", source_chain_id),
+            source_chain_id,
+            amount: U256::from(amount),
+            max_fee: U256::from(amount / 20),
+        };
+        let plan = planner::FundingPlan {
+            token,
+            destination_chain_id: 42161,
+            amount: U256::from(1_000),
+            destination_balance: U256::from(100),
+            vouchers: vec![voucher(1, 600), voucher(10, 300)],
+        };
+
+        let builder = sdk
+            .create_builder()
+            .use_account(account)
+            .unwrap()
+            .start_funded_batch(&plan)
+            .unwrap()
+            .end_batch();
+
+        // One source batch per voucher, then the destination batch
+        assert_eq!(builder.batch_count(), 3);
+        let quotes = builder.quote_voucher_fees(0).unwrap();
+        let mut ref_ids: Vec<&str> = quotes.iter().map(|q| q.ref_id.as_str()).collect();
+        ref_ids.sort();
+        assert_eq!(ref_ids, vec!["fund-USDC-1-42161", "fund-USDC-10-42161"]);
+        assert!(quotes.iter().all(|q| q.destination_chain_id == 42161));
+
+        // Planning the same vouchers twice is rejected
+        assert!(matches!(
+            builder.start_funded_batch(&plan),
+            Err(EilError::DuplicateVoucher(_))
+        ));
     }
 
     #[tokio::test]
@@ -183,4 +246,297 @@ mod tests {
         //  which is fine - that's tested through other means)
         assert_eq!(builder.batch_count(), 1);
     }
+
+    #[tokio::test]
+    async fn test_native_transfer_actions() {
+        let config = create_test_config(vec![1]);
+        let sdk = EilSdk::new(config);
+        let account = Arc::new(MockAccount::new());
+        let eth = eil::multichain::MultichainToken::native("ETH", &[1]);
+        let recipient: alloy::primitives::Address = "0x3333333333333333333333333333333333333333"
+            .parse()
+            .unwrap();
+
+        let batch = sdk.create_builder().use_account(account).unwrap().start_batch(1);
+
+        let calls = NativeTransferAction {
+            recipient,
+            amount: U256::from(7),
+        }
+        .encode_call(&batch)
+        .await
+        .unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].target, recipient);
+        assert!(calls[0].data.is_empty());
+        assert_eq!(calls[0].value, Some(U256::from(7)));
+
+        // A native token transfer is a value transfer too
+        let calls = TransferAction {
+            token: eth.clone(),
+            recipient,
+            amount: Amount::Fixed(U256::from(9)),
+        }
+        .encode_call(&batch)
+        .await
+        .unwrap();
+        assert_eq!(calls[0].target, recipient);
+        assert_eq!(calls[0].value, Some(U256::from(9)));
+
+        let approve = ApproveAction {
+            token: eth,
+            spender: recipient,
+            value: Amount::Fixed(U256::from(9)),
+        }
+        .encode_call(&batch)
+        .await;
+        assert!(matches!(
+            approve,
+            Err(EilError::UnsupportedNativeOperation(_))
+        ));
+    }
+
+    fn create_balance_of_call(token: &eil::multichain::MultichainToken, chain_id: u64) -> FunctionCall {
+        FunctionCall {
+            target: token.address_on(chain_id).unwrap(),
+            abi: token.abi(),
+            function_name: "balanceOf".to_string(),
+            args: vec![alloy::dyn_abi::DynSolValue::Address(
+                "0x2222222222222222222222222222222222222222"
+                    .parse()
+                    .unwrap(),
+            )],
+            value: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_builder_runtime_var_set_then_used() {
+        let server = start_mock_chain(1).await;
+        let mut config = create_test_config(vec![1]);
+        config.chain_infos[0].rpc_url = server.url.clone();
+        config.chain_infos[0].runtime_vars_helper = Some(
+            "0x5555555555555555555555555555555555555555"
+                .parse()
+                .unwrap(),
+        );
+        let sdk = EilSdk::new(config);
+        let account = Arc::new(MockAccount::new());
+        let token = create_test_token("USDC", vec![1]);
+
+        let mut builder = sdk
+            .create_builder()
+            .use_account(account)
+            .unwrap()
+            .start_batch(1)
+            .add_action(SetVarAction::new("bal", create_balance_of_call(&token, 1)).unwrap())
+            .add_action(TransferAction {
+                token: token.clone(),
+                recipient: "0x3333333333333333333333333333333333333333"
+                    .parse()
+                    .unwrap(),
+                amount: Amount::Runtime(RuntimeVar::new("bal").unwrap()),
+            })
+            .end_batch();
+
+        let batches = builder.build_single_chain_batches().await;
+        assert!(batches.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_builder_estimates_gas() {
+        let server = start_mock_chain(1).await;
+        let mut config = create_test_config(vec![1]);
+        config.chain_infos[0].rpc_url = server.url.clone();
+        let sdk = EilSdk::new(config);
+        let account = Arc::new(MockAccount::new());
+        let token = create_test_token("USDC", vec![1]);
+
+        let mut builder = sdk
+            .create_builder()
+            .use_account(account)
+            .unwrap()
+            .start_batch(1)
+            .add_action(TransferAction {
+                token: token.clone(),
+                recipient: "0x3333333333333333333333333333333333333333"
+                    .parse()
+                    .unwrap(),
+                amount: Amount::Fixed(U256::from(100)),
+            })
+            .end_batch();
+
+        let batches = builder.build_single_chain_batches().await.unwrap();
+        let batch = &batches[0];
+        assert_eq!(batch.gas_estimate.source, eil::gas::GasEstimateSource::Rpc);
+        assert_eq!(
+            batch.user_op.call_gas_limit,
+            batch.gas_estimate.call_gas_limit
+        );
+        assert_eq!(
+            batch.user_op.max_fee_per_gas,
+            batch.gas_estimate.fees.max_fee_per_gas
+        );
+        assert!(!batch.user_op.call_data.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_builder_runtime_var_used_before_set() {
+        let server = start_mock_chain(1).await;
+        let mut config = create_test_config(vec![1]);
+        config.chain_infos[0].rpc_url = server.url.clone();
+        config.chain_infos[0].runtime_vars_helper = Some(
+            "0x5555555555555555555555555555555555555555"
+                .parse()
+                .unwrap(),
+        );
+        let sdk = EilSdk::new(config);
+        let account = Arc::new(MockAccount::new());
+        let token = create_test_token("USDC", vec![1]);
+
+        let mut builder = sdk
+            .create_builder()
+            .use_account(account)
+            .unwrap()
+            .start_batch(1)
+            .add_action(TransferAction {
+                token: token.clone(),
+                recipient: "0x3333333333333333333333333333333333333333"
+                    .parse()
+                    .unwrap(),
+                amount: Amount::Runtime(RuntimeVar::new("bal").unwrap()),
+            })
+            .add_action(SetVarAction::new("bal", create_balance_of_call(&token, 1)).unwrap())
+            .end_batch();
+
+        match builder.build_single_chain_batches().await {
+            Err(EilError::RuntimeVarNotSet(name)) => assert_eq!(name, "bal"),
+            other => panic!("Expected RuntimeVarNotSet error, got {:?}", other.err()),
+        }
+    }
+
+    /// Mock chain also answering `eth_call` (EntryPoint `getNonce`) with a zero word
+    async fn start_account_chain() -> MockRpcServer {
+        MockRpcServer::start(|method, _| match method {
+            "eth_chainId" => Ok(serde_json::json!("0x1")),
+            "eth_feeHistory" => Ok(serde_json::json!({
+                "oldestBlock": "0x1",
+                "baseFeePerGas": ["0x3b9aca00", "0x3b9aca00"],
+                "gasUsedRatio": [0.5],
+                "reward": [["0x3b9aca00"]],
+            })),
+            "eth_estimateGas" => Ok(serde_json::json!("0x186a0")),
+            "eth_call" => Ok(serde_json::json!(format!("0x{}", "00".repeat(32)))),
+            _ => Err((-32601, format!("method {} not found", method))),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_builder_runtime_vars_called_by_account() {
+        use alloy::sol_types::SolCall;
+        use eil::contracts::{IERC7579Account, IRuntimeVarsHelper, IERC20};
+
+        let server = start_account_chain().await;
+        let helper: alloy::primitives::Address = "0x5555555555555555555555555555555555555555"
+            .parse()
+            .unwrap();
+        let mut config = create_test_config(vec![1]);
+        config.chain_infos[0].rpc_url = server.url.clone();
+        config.chain_infos[0].runtime_vars_helper = Some(helper);
+        let sdk = EilSdk::new(config.clone());
+        let base = eil::account::BaseMultichainSmartAccount::new(
+            std::collections::HashMap::from([(1, alloy::primitives::Address::repeat_byte(0x22))]),
+            Box::new(MockSigner::new()),
+            Box::new(MockBundlerManager::new()),
+        );
+        let account = eil::erc7579_account::Erc7579Account::new(
+            base,
+            eil::network::NetworkEnvironment::new(&config),
+        );
+        let token = create_test_token("USDC", vec![1]);
+        let recipient: alloy::primitives::Address = "0x3333333333333333333333333333333333333333"
+            .parse()
+            .unwrap();
+
+        let mut builder = sdk
+            .create_builder()
+            .use_account(Arc::new(account))
+            .unwrap()
+            .start_batch(1)
+            .add_action(SetVarAction::new("bal", create_balance_of_call(&token, 1)).unwrap())
+            .add_action(TransferAction {
+                token: token.clone(),
+                recipient,
+                amount: Amount::Runtime(RuntimeVar::new("bal").unwrap()),
+            })
+            .end_batch();
+        let batches = builder.build_single_chain_batches().await.unwrap();
+
+        // The account DELEGATECALLs the helper, which then runs in the account's context
+        let execute =
+            IERC7579Account::executeCall::abi_decode(&batches[0].user_op.call_data, true).unwrap();
+        assert_eq!(execute.mode[0], eil::erc7579_account::CALLTYPE_DELEGATECALL);
+        assert_eq!(&execute.executionCalldata[..20], helper.as_slice());
+        let batch = IRuntimeVarsHelper::executeBatchCall::abi_decode(
+            &execute.executionCalldata[20..],
+            true,
+        )
+        .unwrap();
+        assert_eq!(batch.calls.len(), 2);
+        assert_eq!(batch.calls[0].target, helper);
+        IRuntimeVarsHelper::setVarCall::abi_decode(&batch.calls[0].data, true).unwrap();
+
+        // The token transfer is made from the account's context with the spliced amount
+        assert_eq!(batch.calls[1].target, helper);
+        let exec =
+            IRuntimeVarsHelper::execWithVarsCall::abi_decode(&batch.calls[1].data, true).unwrap();
+        assert_eq!(exec.target, token.address_on(1).unwrap());
+        let transfer = IERC20::transferCall::abi_decode(&exec.data, true).unwrap();
+        assert_eq!(transfer.to, recipient);
+        assert_eq!(exec.refs.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_builder_runtime_vars_require_delegate_call() {
+        let server = start_account_chain().await;
+        let mut config = create_test_config(vec![1]);
+        config.chain_infos[0].rpc_url = server.url.clone();
+        config.chain_infos[0].runtime_vars_helper = Some(
+            "0x5555555555555555555555555555555555555555"
+                .parse()
+                .unwrap(),
+        );
+        let sdk = EilSdk::new(config.clone());
+        let base = eil::account::BaseMultichainSmartAccount::new(
+            std::collections::HashMap::from([(1, alloy::primitives::Address::repeat_byte(0x22))]),
+            Box::new(MockSigner::new()),
+            Box::new(MockBundlerManager::new()),
+        );
+        let account = eil::simple_account::SimpleAccount::new(
+            base,
+            eil::network::NetworkEnvironment::new(&config),
+        );
+        let token = create_test_token("USDC", vec![1]);
+
+        let mut builder = sdk
+            .create_builder()
+            .use_account(Arc::new(account))
+            .unwrap()
+            .start_batch(1)
+            .add_action(SetVarAction::new("bal", create_balance_of_call(&token, 1)).unwrap())
+            .add_action(TransferAction {
+                token: token.clone(),
+                recipient: "0x3333333333333333333333333333333333333333"
+                    .parse()
+                    .unwrap(),
+                amount: Amount::Runtime(RuntimeVar::new("bal").unwrap()),
+            })
+            .end_batch();
+
+        match builder.build_single_chain_batches().await {
+            Err(EilError::DelegateCallUnsupported(1)) => {}
+            other => panic!("Expected DelegateCallUnsupported, got {:?}", other.err()),
+        }
+    }
 }